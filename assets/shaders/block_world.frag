@@ -0,0 +1,58 @@
+#version 330 core
+in vec4 vColor;
+in vec3 vWorldPos;
+in vec3 vNormal;
+in vec2 vUV;
+in float vLayer;
+out vec4 FragColor;
+#include "camera.glsl"
+#include "lighting.glsl"
+#include "shadows.glsl"
+uniform sampler2DArray uTextures;
+#ifdef DEBUG_CHUNK_COLORS
+flat in vec3 vChunkColor;
+#endif
+
+// Render mode variants (see `RenderMode::shader_define`) replace the usual shading
+#if defined(DEBUG_NORMALS)
+void main() {
+    FragColor = vec4(normalize(vNormal) * 0.5 + 0.5, 1.0);
+}
+#elif defined(DEBUG_CHUNK_COLORS)
+void main() {
+    // Keep the face shading so block shapes stay readable
+    FragColor = vec4(vChunkColor * vColor.rgb, 1.0);
+}
+#elif defined(DEBUG_OVERDRAW)
+void main() {
+    // Blended additively: each layer of fragments adds one step towards white
+    FragColor = vec4(0.08, 0.03, 0.01, 1.0);
+}
+#elif defined(DEBUG_LIGHTING)
+void main() {
+    float shadow = shadowFactor(vWorldPos, vNormal);
+    vec3 light = applyLighting(vColor.rgb, vNormal, shadow);
+    float level = clamp(dot(light, vec3(0.2126, 0.7152, 0.0722)), 0.0, 1.0);
+    // Blue in the dark, through green, to red in full light
+    vec3 ramp = clamp(vec3(level * 2.0 - 1.0, 1.0 - abs(level * 2.0 - 1.0), 1.0 - level * 2.0), 0.0, 1.0);
+    FragColor = vec4(ramp, 1.0);
+}
+#elif defined(DEBUG_TEXTURE_OFF)
+void main() {
+    float shadow = shadowFactor(vWorldPos, vNormal);
+    vec3 lit = applyLighting(vColor.rgb, vNormal, shadow);
+    FragColor = vec4(applyFog(lit, vWorldPos), vColor.a);
+}
+#else
+void main() {
+    // Sample the block's layer of the texture array
+    vec4 texColor = texture(uTextures, vec3(vUV, round(vLayer)));
+    
+    // Combine texture with vertex color (for tinting/variation)
+    vec4 baseColor = texColor * vColor;
+    
+    float shadow = shadowFactor(vWorldPos, vNormal);
+    vec3 lit = applyLighting(baseColor.rgb, vNormal, shadow);
+    FragColor = vec4(applyFog(lit, vWorldPos), baseColor.a);
+}
+#endif
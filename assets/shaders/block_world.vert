@@ -0,0 +1,35 @@
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aUV;
+layout (location = 3) in vec4 aColor;
+layout (location = 4) in vec3 aChunkOffset; // per draw: instanced attribute or constant
+layout (location = 5) in float aLayer;
+#include "camera.glsl"
+out vec4 vColor;
+out vec3 vWorldPos;
+out vec3 vNormal;
+out vec2 vUV;
+out float vLayer;
+#ifdef DEBUG_CHUNK_COLORS
+flat out vec3 vChunkColor;
+
+// Cheap hash of the chunk's offset into a bright, stable color
+vec3 chunkColor(vec3 offset) {
+    vec3 p = fract(offset * vec3(0.1031, 0.1030, 0.0973));
+    p += dot(p, p.yxz + 33.33);
+    return 0.3 + 0.7 * fract((p.xxy + p.yxx) * p.zyx);
+}
+#endif
+void main() {
+    vec4 world = vec4(aPos + aChunkOffset, 1.0);
+    vWorldPos = world.xyz;
+    vNormal = aNormal;
+    vColor = aColor;
+    vUV = aUV;
+    vLayer = aLayer;
+#ifdef DEBUG_CHUNK_COLORS
+    vChunkColor = chunkColor(aChunkOffset);
+#endif
+    gl_Position = uViewProj * world;
+}
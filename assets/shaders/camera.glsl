@@ -0,0 +1,7 @@
+// Shared by every shader that needs the camera; filled from `CameraUniform`.
+layout (std140) uniform Camera {
+    mat4 uViewProj;
+    mat4 uView;
+    mat4 uProjection;
+    vec4 uCameraPosition;
+};
@@ -0,0 +1,11 @@
+#version 330 core
+in vec4 vColor;
+in vec3 vWorldPos;
+in vec3 vNormal;
+out vec4 FragColor;
+#include "camera.glsl"
+#include "lighting.glsl"
+void main() {
+    vec3 lit = applyLighting(vColor.rgb, vNormal);
+    FragColor = vec4(applyFog(lit, vWorldPos), vColor.a);
+}
@@ -0,0 +1,18 @@
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aUV;
+layout (location = 3) in vec4 aColor;
+layout (location = 6) in mat4 aModel; // per-instance, occupies locations 6-9
+layout (location = 10) in vec4 aTint; // per-instance
+#include "camera.glsl"
+out vec4 vColor;
+out vec3 vWorldPos;
+out vec3 vNormal;
+void main() {
+    vec4 world = aModel * vec4(aPos, 1.0);
+    vWorldPos = world.xyz;
+    vNormal = mat3(aModel) * aNormal;
+    vColor = aColor * aTint;
+    gl_Position = uViewProj * world;
+}
@@ -0,0 +1,8 @@
+#version 330 core
+out vec2 vUV;
+void main() {
+    // One triangle covering the screen, generated from the vertex index (no vertex buffer)
+    vec2 pos = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    vUV = pos;
+    gl_Position = vec4(pos * 2.0 - 1.0, 0.0, 1.0);
+}
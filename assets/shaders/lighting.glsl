@@ -0,0 +1,33 @@
+// Filled from `SunUniform`: direction towards the scene, then color with ambient in .a
+layout (std140) uniform Sun {
+    vec4 uSunDirection;
+    vec4 uSunColor;
+};
+
+// Filled from `FogUniform`: color, then (start, end, max factor, unused)
+layout (std140) uniform Fog {
+    vec4 uFogColor;
+    vec4 uFogParams;
+};
+
+#define LIGHT_DIR (uSunDirection.xyz)
+#define LIGHT_COLOR (uSunColor.rgb)
+
+// `shadow` scales the direct sun term: 1 = fully lit, 0 = in shadow
+vec3 applyLighting(vec3 albedo, vec3 normal, float shadow) {
+    float diff = max(dot(normalize(normal), -LIGHT_DIR), 0.0) * shadow;
+    return albedo * (LIGHT_COLOR * (uSunColor.a + diff * 0.6));
+}
+
+vec3 applyLighting(vec3 albedo, vec3 normal) {
+    return applyLighting(albedo, normal, 1.0);
+}
+
+float fogFactor(vec3 worldPos) {
+    float dist = length(worldPos - uCameraPosition.xyz);
+    return clamp((dist - uFogParams.x) / max(uFogParams.y - uFogParams.x, 0.001), 0.0, 1.0) * uFogParams.z;
+}
+
+vec3 applyFog(vec3 color, vec3 worldPos) {
+    return mix(color, uFogColor.rgb, fogFactor(worldPos));
+}
@@ -0,0 +1,10 @@
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+uniform sampler2D uInput;
+uniform sampler2D uBloom;
+uniform float uIntensity;
+void main() {
+    vec3 color = texture(uInput, vUV).rgb + texture(uBloom, vUV).rgb * uIntensity;
+    FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,16 @@
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+uniform sampler2D uInput;
+uniform vec2 uDirection; // one texel along the blur axis
+// 9-tap separable Gaussian using linear sampling between texel pairs
+void main() {
+    const float OFFSETS[3] = float[](0.0, 1.3846153846, 3.2307692308);
+    const float WEIGHTS[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+    vec3 sum = texture(uInput, vUV).rgb * WEIGHTS[0];
+    for (int i = 1; i < 3; i++) {
+        sum += texture(uInput, vUV + uDirection * OFFSETS[i]).rgb * WEIGHTS[i];
+        sum += texture(uInput, vUV - uDirection * OFFSETS[i]).rgb * WEIGHTS[i];
+    }
+    FragColor = vec4(sum, 1.0);
+}
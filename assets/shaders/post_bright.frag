@@ -0,0 +1,15 @@
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+uniform sampler2D uInput;
+uniform float uThreshold;
+// Keeps only the part of each pixel brighter than the threshold, with a soft knee
+void main() {
+    vec3 color = texture(uInput, vUV).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    float knee = uThreshold * 0.5;
+    float soft = clamp(brightness - uThreshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 1e-4);
+    float contribution = max(soft, brightness - uThreshold) / max(brightness, 1e-4);
+    FragColor = vec4(color * contribution, 1.0);
+}
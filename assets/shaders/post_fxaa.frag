@@ -0,0 +1,33 @@
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+uniform sampler2D uInput;
+uniform vec2 uTexelSize;
+
+const float FXAA_SPAN_MAX = 8.0;
+const float FXAA_REDUCE_MUL = 1.0 / 8.0;
+const float FXAA_REDUCE_MIN = 1.0 / 128.0;
+
+float luma(vec3 color) { return dot(color, vec3(0.299, 0.587, 0.114)); }
+
+// FXAA (Lottes): blur along the local edge direction found from luma differences
+void main() {
+    vec3 rgbNW = texture(uInput, vUV + vec2(-1.0, -1.0) * uTexelSize).rgb;
+    vec3 rgbNE = texture(uInput, vUV + vec2(1.0, -1.0) * uTexelSize).rgb;
+    vec3 rgbSW = texture(uInput, vUV + vec2(-1.0, 1.0) * uTexelSize).rgb;
+    vec3 rgbSE = texture(uInput, vUV + vec2(1.0, 1.0) * uTexelSize).rgb;
+    vec3 rgbM = texture(uInput, vUV).rgb;
+    float lumaNW = luma(rgbNW), lumaNE = luma(rgbNE), lumaSW = luma(rgbSW), lumaSE = luma(rgbSE), lumaM = luma(rgbM);
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    vec2 dir = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)), (lumaNW + lumaSW) - (lumaNE + lumaSE));
+    float dirReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+    float rcpDirMin = 1.0 / (min(abs(dir.x), abs(dir.y)) + dirReduce);
+    dir = clamp(dir * rcpDirMin, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX)) * uTexelSize;
+
+    vec3 rgbA = 0.5 * (texture(uInput, vUV + dir * (1.0 / 3.0 - 0.5)).rgb + texture(uInput, vUV + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgbB = rgbA * 0.5 + 0.25 * (texture(uInput, vUV - dir * 0.5).rgb + texture(uInput, vUV + dir * 0.5).rgb);
+    float lumaB = luma(rgbB);
+    FragColor = vec4((lumaB < lumaMin || lumaB > lumaMax) ? rgbA : rgbB, 1.0);
+}
@@ -0,0 +1,9 @@
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+uniform sampler2D uInput;
+uniform float uGamma;
+void main() {
+    vec3 color = texture(uInput, vUV).rgb;
+    FragColor = vec4(pow(max(color, vec3(0.0)), vec3(1.0 / uGamma)), 1.0);
+}
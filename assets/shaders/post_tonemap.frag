@@ -0,0 +1,13 @@
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+uniform sampler2D uInput;
+uniform float uExposure;
+// ACES filmic curve fit (Narkowicz 2015): HDR linear -> [0, 1] linear
+vec3 aces(vec3 x) {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+void main() {
+    vec3 color = texture(uInput, vUV).rgb * uExposure;
+    FragColor = vec4(aces(color), 1.0);
+}
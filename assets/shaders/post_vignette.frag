@@ -0,0 +1,12 @@
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+uniform sampler2D uInput;
+uniform float uStrength;
+uniform float uRadius; // distance from the center (in half-screens) where darkening starts
+void main() {
+    vec3 color = texture(uInput, vUV).rgb;
+    float dist = length(vUV - 0.5) * 2.0;
+    float vignette = 1.0 - smoothstep(uRadius, 1.5, dist) * uStrength;
+    FragColor = vec4(color * vignette, 1.0);
+}
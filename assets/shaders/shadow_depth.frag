@@ -0,0 +1,3 @@
+#version 330 core
+// Depth only; the shadow map framebuffer has no color attachment
+void main() {}
@@ -0,0 +1,7 @@
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 4) in vec3 aChunkOffset; // per draw: instanced attribute or constant
+uniform mat4 uLightViewProj;
+void main() {
+    gl_Position = uLightViewProj * vec4(aPos + aChunkOffset, 1.0);
+}
@@ -0,0 +1,47 @@
+// Cascaded sun shadows, set up by `ShadowMap::apply`. Needs camera.glsl and lighting.glsl.
+const int MAX_SHADOW_CASCADES = 4; // matches MAX_SHADOW_CASCADES in shadow.rs
+
+uniform sampler2DArrayShadow uShadowMap;
+uniform mat4 uCascadeViewProj[MAX_SHADOW_CASCADES];
+uniform vec4 uCascadeSplits;  // far view distance of each cascade
+uniform int uCascadeCount;
+uniform float uShadowTexelSize;
+uniform bool uShadowsEnabled;
+
+// Fraction of sunlight reaching a surface: 1 = lit, 0 = fully shadowed
+float shadowFactor(vec3 worldPos, vec3 normal) {
+    if (!uShadowsEnabled) return 1.0;
+    float viewDist = -(uView * vec4(worldPos, 1.0)).z;
+    int cascade = uCascadeCount;
+    for (int i = 0; i < uCascadeCount; i++) {
+        if (viewDist < uCascadeSplits[i]) { cascade = i; break; }
+    }
+    if (cascade >= uCascadeCount) return 1.0;
+
+    // Faces turned away from the sun are already dark
+    vec3 n = normalize(normal);
+    float nDotL = dot(n, -LIGHT_DIR);
+    if (nDotL <= 0.0) return 0.0;
+
+    // Normal offset grows with the cascade's texel footprint to avoid acne
+    float texelWorld = 2.0 * uCascadeSplits[cascade] * uShadowTexelSize;
+    vec3 offsetPos = worldPos + n * texelWorld * 1.5;
+    vec4 lightClip = uCascadeViewProj[cascade] * vec4(offsetPos, 1.0);
+    vec3 coord = lightClip.xyz / lightClip.w * 0.5 + 0.5;
+    if (coord.z > 1.0) return 1.0;
+
+    // 3x3 PCF; each tap is itself bilinearly filtered by the hardware comparison
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 uv = coord.xy + vec2(x, y) * uShadowTexelSize;
+            lit += texture(uShadowMap, vec4(uv, float(cascade), coord.z));
+        }
+    }
+    lit /= 9.0;
+
+    // Fade out towards the end of the last cascade instead of cutting off
+    float lastFar = uCascadeSplits[uCascadeCount - 1];
+    float fade = clamp((viewDist - lastFar * 0.85) / (lastFar * 0.15), 0.0, 1.0);
+    return mix(lit, 1.0, fade);
+}
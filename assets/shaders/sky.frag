@@ -0,0 +1,96 @@
+#version 330 core
+in vec3 vRay;
+out vec4 FragColor;
+#include "camera.glsl"
+uniform vec3 uZenithColor;
+uniform vec3 uHorizonColor;   // also the fog color, so terrain fades into the sky
+uniform vec3 uSunDir;         // towards the sun
+uniform vec3 uMoonDir;
+uniform vec3 uSunColor;
+uniform float uStarVisibility;
+uniform float uTime;
+uniform float uCloudCoverage;
+uniform float uCloudAltitude;
+uniform float uCloudScale;
+uniform vec2 uCloudWind;
+
+const float SUN_SIZE = 0.9995;   // cosine of the disk radius
+const float MOON_SIZE = 0.9996;
+const vec3 MOON_COLOR = vec3(0.8, 0.85, 0.95);
+const vec3 GROUND_COLOR = vec3(0.04, 0.04, 0.05);
+
+float hash(vec2 p) {
+    return fract(sin(dot(p, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+float hash(vec3 p) {
+    return fract(sin(dot(p, vec3(127.1, 311.7, 74.7))) * 43758.5453);
+}
+
+float valueNoise(vec2 p) {
+    vec2 i = floor(p);
+    vec2 f = fract(p);
+    f = f * f * (3.0 - 2.0 * f);
+    return mix(mix(hash(i), hash(i + vec2(1.0, 0.0)), f.x),
+               mix(hash(i + vec2(0.0, 1.0)), hash(i + vec2(1.0, 1.0)), f.x), f.y);
+}
+
+float fbm(vec2 p) {
+    float sum = 0.0;
+    float amp = 0.5;
+    for (int i = 0; i < 5; i++) {
+        sum += valueNoise(p) * amp;
+        p = p * 2.03 + vec2(17.0, 31.0);
+        amp *= 0.5;
+    }
+    return sum;
+}
+
+vec3 gradient(vec3 dir) {
+    if (dir.y < 0.0) {
+        return mix(uHorizonColor, GROUND_COLOR * (uHorizonColor + 0.1), clamp(-dir.y * 4.0, 0.0, 1.0));
+    }
+    return mix(uHorizonColor, uZenithColor, pow(dir.y, 0.5));
+}
+
+vec3 stars(vec3 dir) {
+    // One candidate star per cell of a grid on the unit sphere
+    vec3 cell = floor(dir * 300.0);
+    float h = hash(cell);
+    if (h < 0.997) return vec3(0.0);
+    float twinkle = 0.6 + 0.4 * sin(uTime * (2.0 + h * 3.0) + h * 100.0);
+    return vec3((h - 0.997) / 0.003 * 2.0 * twinkle);
+}
+
+// Coverage (rgb lit color, a opacity) of the flat cloud layer along `dir`
+vec4 clouds(vec3 dir) {
+    float height = uCloudAltitude - uCameraPosition.y;
+    if (dir.y * height <= 0.0) return vec4(0.0);
+    float dist = height / dir.y;
+    vec2 p = (uCameraPosition.xz + dir.xz * dist + uCloudWind * uTime) * uCloudScale;
+    float density = smoothstep(1.0 - uCloudCoverage, 1.0 - uCloudCoverage + 0.25, fbm(p));
+    // Thin out towards the horizon so the layer doesn't end in a hard line
+    density *= 1.0 - smoothstep(2000.0, 6000.0, dist);
+    // Lit by the sky plus the sun while it is up; denser clouds are darker underneath
+    float light = smoothstep(-0.05, 0.3, uSunDir.y);
+    vec3 lit = (uHorizonColor * 0.8 + uSunColor * light * 0.7) * (1.0 - density * 0.35);
+    return vec4(lit, density * 0.9);
+}
+
+void main() {
+    vec3 dir = normalize(vRay);
+    vec3 color = gradient(dir);
+
+    float sunDot = dot(dir, uSunDir);
+    color += uSunColor * pow(max(sunDot, 0.0), 300.0) * 0.5;   // glow
+    if (dir.y > -0.02) {
+        color += stars(dir) * uStarVisibility;
+        if (sunDot > SUN_SIZE) color = uSunColor * 20.0;
+        float moonDot = dot(dir, uMoonDir);
+        if (moonDot > MOON_SIZE) color = MOON_COLOR * 1.5 * uStarVisibility + color * (1.0 - uStarVisibility);
+    }
+
+    vec4 cloud = clouds(dir);
+    color = mix(color, cloud.rgb, cloud.a);
+    FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,12 @@
+#version 330 core
+#include "camera.glsl"
+out vec3 vRay;
+void main() {
+    // Fullscreen triangle on the far plane; the world-space view ray is interpolated per pixel
+    vec2 pos = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2) * 2.0 - 1.0;
+    // Unproject through the full inverse so off-center (tiled screenshot) projections work too
+    vec4 viewPoint = inverse(uProjection) * vec4(pos, 1.0, 1.0);
+    vec3 viewRay = viewPoint.xyz / viewPoint.w;
+    vRay = transpose(mat3(uView)) * viewRay;
+    gl_Position = vec4(pos, 1.0, 1.0);
+}
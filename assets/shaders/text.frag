@@ -0,0 +1,13 @@
+#version 330 core
+in vec2 vUV;
+in vec4 vColor;
+
+uniform sampler2D uFont;
+
+out vec4 FragColor;
+
+void main() {
+    float coverage = texture(uFont, vUV).r;
+    if (coverage < 0.5) discard;
+    FragColor = vColor;
+}
@@ -0,0 +1,75 @@
+#version 330 core
+in vec3 vWorldPos;
+in vec3 vFaceNormal;
+in vec2 vUV;
+in float vLayer;
+out vec4 FragColor;
+#include "camera.glsl"
+#include "lighting.glsl"
+#include "waves.glsl"
+uniform sampler2DArray uTextures;
+uniform sampler2D uSceneColor;   // opaque scene, copied before the water pass
+uniform sampler2D uSceneDepth;
+uniform vec2 uScreenSize;
+uniform bool uUnderwater;
+uniform float uRefractionStrength;
+uniform vec3 uAbsorption;
+uniform vec3 uDeepColor;
+
+const vec3 ZENITH_COLOR = vec3(0.25, 0.45, 0.85);
+
+// View-space distance of a depth buffer value, for any perspective projection in uProjection
+float linearDepth(float depth) {
+    float ndc = depth * 2.0 - 1.0;
+    return uProjection[3][2] / (ndc + uProjection[2][2]);
+}
+
+vec3 skyColor(vec3 dir) {
+    return mix(uFogColor.rgb, ZENITH_COLOR, clamp(dir.y, 0.0, 1.0));
+}
+
+void main() {
+    // Per-pixel wave normal on the open surface, face normal on the sides
+    vec3 normal = normalize(vFaceNormal);
+    if (vFaceNormal.y > 0.5) {
+        vec3 wave = waveHeight(vWorldPos.xz, 4);
+        normal = normalize(vec3(-wave.y, 1.0, -wave.z));
+    }
+    vec3 viewDir = normalize(uCameraPosition.xyz - vWorldPos);
+    if (dot(normal, viewDir) < 0.0) normal = -normal; // seen from the other side
+
+    // Screen-space refraction: bend the lookup by the wave slope, but never pick up
+    // geometry in front of the water surface
+    vec2 screenUV = gl_FragCoord.xy / uScreenSize;
+    float surfaceDist = linearDepth(gl_FragCoord.z);
+    vec2 refractUV = clamp(screenUV + (normal.xz - vFaceNormal.xz) * uRefractionStrength, 0.0, 1.0);
+    float sceneDist = linearDepth(texture(uSceneDepth, refractUV).r);
+    if (sceneDist < surfaceDist) {
+        refractUV = screenUV;
+        sceneDist = linearDepth(texture(uSceneDepth, screenUV).r);
+    }
+    vec3 refracted = texture(uSceneColor, refractUV).rgb;
+
+    // Absorption along the water column behind the surface (underwater fog covers the
+    // camera-to-surface part when looking up from below)
+    float thickness = uUnderwater ? 0.0 : max(sceneDist - surfaceDist, 0.0);
+    vec3 transmittance = exp(-uAbsorption * thickness);
+    vec3 deep = applyLighting(uDeepColor, vec3(0.0, 1.0, 0.0));
+    vec3 body = refracted * transmittance + deep * (1.0 - transmittance);
+    vec3 detail = texture(uTextures, vec3(vUV, round(vLayer))).rgb;
+    body = mix(body, body * detail * 2.0, 0.15);
+
+    // Fresnel (Schlick) blend towards the reflected sky, plus a sun glint
+    float cosTheta = max(dot(normal, viewDir), 0.0);
+    float fresnel = 0.02 + 0.98 * pow(1.0 - cosTheta, 5.0);
+    vec3 color;
+    if (uUnderwater) {
+        // From below, grazing angles reflect the dark water instead of the sky
+        color = mix(body, deep, fresnel);
+    } else {
+        vec3 reflected = reflect(-viewDir, normal);
+        float glint = pow(max(dot(reflected, -LIGHT_DIR), 0.0), 256.0);
+        color = mix(body, skyColor(reflected), fresnel) + LIGHT_COLOR * glint * 1.5;
+    }
+    FragColor = vec4(applyFog(color, vWorldPos), 1.0);
+}
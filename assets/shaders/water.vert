@@ -0,0 +1,25 @@
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aUV;
+layout (location = 3) in vec4 aColor;
+layout (location = 4) in vec3 aChunkOffset; // per draw: instanced attribute or constant
+layout (location = 5) in float aLayer;
+#include "camera.glsl"
+#include "waves.glsl"
+out vec3 vWorldPos;
+out vec3 vFaceNormal;
+out vec2 vUV;
+out float vLayer;
+void main() {
+    vec3 world = aPos + aChunkOffset;
+    if (aNormal.y > 0.5) {
+        // Open surface: sit a little below the block top so crests stay inside the block
+        world.y += waveHeight(world.xz, 2).x - 2.0 * uWaveAmplitude;
+    }
+    vWorldPos = world;
+    vFaceNormal = aNormal;
+    vUV = aUV;
+    vLayer = aLayer;
+    gl_Position = uViewProj * vec4(world, 1.0);
+}
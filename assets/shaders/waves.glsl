@@ -0,0 +1,19 @@
+uniform float uTime;
+uniform float uWaveAmplitude;
+uniform float uWaveSpeed;
+
+// Sum of directional sine waves at a world xz position: returns (height, d/dx, d/dz).
+// The vertex shader uses a couple of long waves, the fragment shader adds finer ones.
+vec3 waveHeight(vec2 p, int count) {
+    const vec2 DIRECTIONS[4] = vec2[](vec2(0.96, 0.28), vec2(-0.37, 0.93), vec2(0.66, -0.75), vec2(-0.98, -0.2));
+    const float FREQUENCIES[4] = float[](0.45, 0.7, 1.6, 2.7);
+    vec3 sum = vec3(0.0);
+    float amplitude = uWaveAmplitude;
+    for (int i = 0; i < count; i++) {
+        float phase = dot(DIRECTIONS[i], p) * FREQUENCIES[i] + uTime * uWaveSpeed * (1.0 + 0.35 * float(i));
+        sum.x += amplitude * sin(phase);
+        sum.yz += amplitude * FREQUENCIES[i] * cos(phase) * DIRECTIONS[i];
+        amplitude *= 0.55;
+    }
+    return sum;
+}
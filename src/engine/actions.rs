@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use glfw::{GamepadAxis, GamepadButton, Key, MouseButton};
+use crate::engine::constants::ACTION_PRESS_THRESHOLD;
+use crate::engine::input::InputState;
+
+/// Actions the engine and its camera controllers query.
+pub const MOVE_FORWARD: &str = "move_forward";
+pub const MOVE_RIGHT: &str = "move_right";
+pub const MOVE_UP: &str = "move_up";
+pub const LOOK_RIGHT: &str = "look_right";
+pub const LOOK_UP: &str = "look_up";
+pub const SPRINT: &str = "sprint";
+pub const QUIT: &str = "quit";
+pub const TOGGLE_CURSOR: &str = "toggle_cursor";
+pub const CYCLE_CAMERA: &str = "cycle_camera";
+pub const TOGGLE_BLOOM: &str = "toggle_bloom";
+pub const TOGGLE_TONEMAP: &str = "toggle_tonemap";
+pub const TOGGLE_GAMMA: &str = "toggle_gamma";
+pub const TOGGLE_FXAA: &str = "toggle_fxaa";
+pub const TOGGLE_VIGNETTE: &str = "toggle_vignette";
+pub const TOGGLE_RECORDING: &str = "toggle_recording";
+pub const TOGGLE_DEBUG_HUD: &str = "toggle_debug_hud";
+pub const TOGGLE_CHUNK_BORDERS: &str = "toggle_chunk_borders";
+pub const TOGGLE_CHUNK_STATES: &str = "toggle_chunk_states";
+pub const FREEZE_FRUSTUM: &str = "freeze_frustum";
+pub const CYCLE_RENDER_MODE: &str = "cycle_render_mode";
+pub const TOGGLE_PROFILE_CAPTURE: &str = "toggle_profile_capture";
+pub const SCREENSHOT: &str = "screenshot";
+pub const TILED_SCREENSHOT: &str = "tiled_screenshot";
+pub const TOGGLE_FRAME_SEQUENCE: &str = "toggle_frame_sequence";
+/// Gameplay actions bound by default for games to use.
+pub const JUMP: &str = "jump";
+pub const BREAK_BLOCK: &str = "break_block";
+pub const PLACE_BLOCK: &str = "place_block";
+
+/// A single physical input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    Key(Key),
+    MouseButton(MouseButton),
+    GamepadButton(GamepadButton),
+    /// One half of a gamepad axis; `true` for the positive direction
+    GamepadAxis(GamepadAxis, bool),
+}
+
+impl InputSource {
+    /// How far the input is pushed, 0..1.
+    pub fn value(&self, input: &InputState) -> f32 {
+        let pressed = |down: bool| if down { 1.0 } else { 0.0 };
+        match *self {
+            InputSource::Key(key) => pressed(input.key(key).down),
+            InputSource::MouseButton(button) => pressed(input.mouse_button(button).down),
+            InputSource::GamepadButton(button) => pressed(input.gamepad_button(button).down),
+            InputSource::GamepadAxis(axis, positive) => {
+                let value = input.gamepad_axis(axis);
+                (if positive { value } else { -value }).max(0.0)
+            }
+        }
+    }
+
+    /// Parses the config file form: `key:W`, `mouse:Left`, `pad:A` or `pad_axis:LeftY+`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (kind, name) = text.split_once(':').ok_or_else(|| format!("Input '{}' has no type prefix", text))?;
+        let unknown = || format!("Unknown {} '{}'", kind, name);
+        match kind {
+            "key" => KEYS.iter().find(|(_, n)| *n == name).map(|&(k, _)| InputSource::Key(k)).ok_or_else(unknown),
+            "mouse" => MOUSE_BUTTONS.iter().find(|(_, n)| *n == name).map(|&(b, _)| InputSource::MouseButton(b)).ok_or_else(unknown),
+            "pad" => PAD_BUTTONS.iter().find(|(_, n)| *n == name).map(|&(b, _)| InputSource::GamepadButton(b)).ok_or_else(unknown),
+            "pad_axis" => {
+                let (axis, positive) = match name.strip_suffix('+') {
+                    Some(axis) => (axis, true),
+                    None => (name.strip_suffix('-').ok_or_else(|| format!("Axis '{}' needs a + or - direction", name))?, false),
+                };
+                PAD_AXES.iter().find(|(_, n)| *n == axis).map(|&(a, _)| InputSource::GamepadAxis(a, positive)).ok_or_else(unknown)
+            }
+            _ => Err(format!("Unknown input type '{}'", kind)),
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn name<T: PartialEq>(table: &[(T, &'static str)], value: T) -> &'static str {
+            table.iter().find(|(v, _)| *v == value).map(|(_, n)| *n).unwrap_or("Unknown")
+        }
+        match *self {
+            InputSource::Key(key) => write!(f, "key:{}", name(KEYS, key)),
+            InputSource::MouseButton(button) => write!(f, "mouse:{}", name(MOUSE_BUTTONS, button)),
+            InputSource::GamepadButton(button) => write!(f, "pad:{}", name(PAD_BUTTONS, button)),
+            InputSource::GamepadAxis(axis, positive) => write!(f, "pad_axis:{}{}", name(PAD_AXES, axis), if positive { '+' } else { '-' }),
+        }
+    }
+}
+
+/// Inputs that trigger an action together (a chord when there are several), and the
+/// value they give it. A chord suppresses any binding made of a subset of its inputs,
+/// so Ctrl+S doesn't also fire an action bound to plain S.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub inputs: Vec<InputSource>,
+    pub scale: f32,
+}
+
+impl Binding {
+    pub fn new(input: InputSource) -> Self { Self { inputs: vec![input], scale: 1.0 } }
+
+    pub fn chord(inputs: &[InputSource]) -> Self { Self { inputs: inputs.to_vec(), scale: 1.0 } }
+
+    /// The same binding contributing `scale` times its value, e.g. -1 for the negative half of an axis action.
+    pub fn scaled(mut self, scale: f32) -> Self { self.scale = scale; self }
+
+    /// Unscaled value: how far the least pushed input is pushed.
+    fn raw_value(&self, input: &InputState) -> f32 {
+        self.inputs.iter().map(|i| i.value(input)).fold(1.0, f32::min)
+    }
+
+    /// True if this binding's inputs are a strict subset of `other`'s.
+    fn is_part_of(&self, other: &Binding) -> bool {
+        self.inputs.len() < other.inputs.len() && self.inputs.iter().all(|i| other.inputs.contains(i))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (inputs, scale) = match text.split_once('*') {
+            Some((inputs, scale)) => (inputs, scale.trim().parse::<f32>().map_err(|_| format!("Bad scale in '{}'", text))?),
+            None => (text, 1.0),
+        };
+        let inputs = inputs.split('+')
+            .map(str::trim)
+            // A trailing '+' belongs to an axis direction, not the chord separator
+            .fold(Vec::<String>::new(), |mut parts, part| {
+                match parts.last_mut() {
+                    Some(last) if part.is_empty() => last.push('+'),
+                    _ => parts.push(part.to_string()),
+                }
+                parts
+            })
+            .iter()
+            .map(|part| InputSource::parse(part))
+            .collect::<Result<Vec<_>, _>>()?;
+        if inputs.is_empty() { return Err(format!("Empty binding '{}'", text)); }
+        Ok(Self { inputs, scale })
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, input) in self.inputs.iter().enumerate() {
+            if i > 0 { write!(f, " + ")?; }
+            write!(f, "{}", input)?;
+        }
+        if self.scale != 1.0 { write!(f, " * {}", self.scale)?; }
+        Ok(())
+    }
+}
+
+/// State of an action this frame. Button-like actions use `down`/`pressed`/`released`;
+/// axis actions read `value`, the sum of their active bindings clamped to -1..1.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActionState {
+    pub down: bool,
+    pub pressed: bool,
+    pub released: bool,
+    pub value: f32,
+}
+
+/// Named actions bound to inputs. Updated once per frame from `InputState`; games query
+/// actions instead of raw keys so bindings can be changed in the config file.
+#[derive(Default)]
+pub struct ActionMap {
+    /// In declaration order, so saved files stay stable
+    bindings: Vec<(String, Vec<Binding>)>,
+    states: HashMap<String, ActionState>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self { Self::default() }
+
+    /// The engine's default bindings.
+    pub fn defaults() -> Self {
+        use InputSource::{Key as K, MouseButton as M, GamepadButton as P, GamepadAxis as A};
+        let mut map = Self::new();
+        map.bind(MOVE_FORWARD, Binding::new(K(Key::W)));
+        map.bind(MOVE_FORWARD, Binding::new(K(Key::S)).scaled(-1.0));
+        map.bind(MOVE_FORWARD, Binding::new(A(GamepadAxis::AxisLeftY, false)));
+        map.bind(MOVE_FORWARD, Binding::new(A(GamepadAxis::AxisLeftY, true)).scaled(-1.0));
+        map.bind(MOVE_RIGHT, Binding::new(K(Key::D)));
+        map.bind(MOVE_RIGHT, Binding::new(K(Key::A)).scaled(-1.0));
+        map.bind(MOVE_RIGHT, Binding::new(A(GamepadAxis::AxisLeftX, true)));
+        map.bind(MOVE_RIGHT, Binding::new(A(GamepadAxis::AxisLeftX, false)).scaled(-1.0));
+        map.bind(LOOK_RIGHT, Binding::new(A(GamepadAxis::AxisRightX, true)));
+        map.bind(LOOK_RIGHT, Binding::new(A(GamepadAxis::AxisRightX, false)).scaled(-1.0));
+        map.bind(LOOK_UP, Binding::new(A(GamepadAxis::AxisRightY, false)));
+        map.bind(LOOK_UP, Binding::new(A(GamepadAxis::AxisRightY, true)).scaled(-1.0));
+        map.bind(MOVE_UP, Binding::new(K(Key::Space)));
+        map.bind(MOVE_UP, Binding::new(K(Key::LeftControl)).scaled(-1.0));
+        map.bind(MOVE_UP, Binding::new(P(GamepadButton::ButtonA)));
+        map.bind(MOVE_UP, Binding::new(P(GamepadButton::ButtonB)).scaled(-1.0));
+        map.bind(SPRINT, Binding::new(K(Key::LeftShift)));
+        map.bind(SPRINT, Binding::new(P(GamepadButton::ButtonLeftThumb)));
+        map.bind(JUMP, Binding::new(K(Key::Space)));
+        map.bind(JUMP, Binding::new(P(GamepadButton::ButtonA)));
+        map.bind(BREAK_BLOCK, Binding::new(M(MouseButton::Button1)));
+        map.bind(BREAK_BLOCK, Binding::new(A(GamepadAxis::AxisRightTrigger, true)));
+        map.bind(PLACE_BLOCK, Binding::new(M(MouseButton::Button2)));
+        map.bind(PLACE_BLOCK, Binding::new(A(GamepadAxis::AxisLeftTrigger, true)));
+        map.bind(QUIT, Binding::new(K(Key::Escape)));
+        map.bind(TOGGLE_CURSOR, Binding::new(K(Key::Tab)));
+        map.bind(CYCLE_CAMERA, Binding::new(K(Key::V)));
+        map.bind(CYCLE_CAMERA, Binding::new(P(GamepadButton::ButtonY)));
+        map.bind(SCREENSHOT, Binding::new(K(Key::F2)));
+        map.bind(TILED_SCREENSHOT, Binding::chord(&[K(Key::LeftShift), K(Key::F2)]));
+        map.bind(TOGGLE_FRAME_SEQUENCE, Binding::chord(&[K(Key::LeftControl), K(Key::F2)]));
+        map.bind(TOGGLE_DEBUG_HUD, Binding::new(K(Key::F3)));
+        // Debug views are chords on F4, so F4 alone stays free
+        map.bind(TOGGLE_CHUNK_BORDERS, Binding::chord(&[K(Key::F4), K(Key::G)]));
+        map.bind(TOGGLE_CHUNK_STATES, Binding::chord(&[K(Key::F4), K(Key::B)]));
+        map.bind(FREEZE_FRUSTUM, Binding::chord(&[K(Key::F4), K(Key::F)]));
+        map.bind(CYCLE_RENDER_MODE, Binding::chord(&[K(Key::F4), K(Key::R)]));
+        map.bind(TOGGLE_PROFILE_CAPTURE, Binding::chord(&[K(Key::F4), K(Key::T)]));
+        map.bind(TOGGLE_BLOOM, Binding::new(K(Key::F5)));
+        map.bind(TOGGLE_TONEMAP, Binding::new(K(Key::F6)));
+        map.bind(TOGGLE_GAMMA, Binding::new(K(Key::F7)));
+        map.bind(TOGGLE_FXAA, Binding::new(K(Key::F8)));
+        map.bind(TOGGLE_VIGNETTE, Binding::new(K(Key::F9)));
+        map.bind(TOGGLE_RECORDING, Binding::new(K(Key::F10)));
+        map
+    }
+
+    /// Adds a binding to an action, creating the action if needed.
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        match self.bindings.iter_mut().find(|(name, _)| name == action) {
+            Some((_, list)) => list.push(binding),
+            None => self.bindings.push((action.to_string(), vec![binding])),
+        }
+    }
+
+    /// Recomputes every action from the current input state. Call once per frame after events are polled.
+    pub fn update(&mut self, input: &InputState) {
+        // Chords that are held suppress the bindings made of their parts
+        let active: Vec<&Binding> = self.bindings.iter()
+            .flat_map(|(_, list)| list.iter())
+            .filter(|b| b.inputs.len() > 1 && b.raw_value(input) > ACTION_PRESS_THRESHOLD)
+            .collect();
+
+        for (name, list) in &self.bindings {
+            let mut down = false;
+            let mut value = 0.0;
+            for binding in list {
+                if active.iter().any(|chord| binding.is_part_of(chord)) { continue; }
+                let raw = binding.raw_value(input);
+                down |= raw > ACTION_PRESS_THRESHOLD;
+                value += raw * binding.scale;
+            }
+            let state = self.states.entry(name.clone()).or_default();
+            state.pressed = down && !state.down;
+            state.released = !down && state.down;
+            state.down = down;
+            state.value = value.clamp(-1.0, 1.0);
+        }
+    }
+
+    pub fn state(&self, action: &str) -> ActionState {
+        self.states.get(action).copied().unwrap_or_default()
+    }
+
+    pub fn is_down(&self, action: &str) -> bool { self.state(action).down }
+    pub fn was_pressed(&self, action: &str) -> bool { self.state(action).pressed }
+
+    /// Axis value of an action, -1..1.
+    pub fn value(&self, action: &str) -> f32 { self.state(action).value }
+
+    /// Parses a bindings file, one action per line:
+    /// `action = binding | binding ...`, where a binding is `input [+ input ...] [* scale]`.
+    /// Actions in the file replace the ones in `base`; others keep their `base` bindings.
+    pub fn parse(text: &str, base: ActionMap) -> Result<Self, String> {
+        let mut map = base;
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+            let (action, bindings) = line.split_once('=')
+                .ok_or_else(|| format!("Line {}: expected 'action = bindings'", line_no + 1))?;
+            let action = action.trim();
+            let bindings = bindings.split('|')
+                .map(str::trim)
+                .filter(|b| !b.is_empty())
+                .map(Binding::parse)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Line {}: {}", line_no + 1, e))?;
+            match map.bindings.iter_mut().find(|(name, _)| name == action) {
+                Some((_, list)) => *list = bindings,
+                None => map.bindings.push((action.to_string(), bindings)),
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn to_config_string(&self) -> String {
+        let mut out = String::from("# Input bindings: action = binding | binding ...\n");
+        out.push_str("# A binding is one input or a chord (key:LeftControl + key:S), optionally scaled (key:S * -1).\n");
+        out.push_str("# Inputs: key:<Key>, mouse:<Left|Right|Middle|Button4-8>, pad:<Button>, pad_axis:<Axis>+ or -\n");
+        for (name, list) in &self.bindings {
+            let bindings: Vec<String> = list.iter().map(|b| b.to_string()).collect();
+            out.push_str(&format!("{} = {}\n", name, bindings.join(" | ")));
+        }
+        out
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&text, Self::defaults()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, self.to_config_string()))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Loads the bindings file, or writes the defaults there if it doesn't exist yet.
+    pub fn load_or_default(path: &Path) -> Self {
+        if path.exists() {
+            return Self::load(path).unwrap_or_else(|e| {
+                eprintln!("Warning: {}; using default bindings", e);
+                Self::defaults()
+            });
+        }
+        let map = Self::defaults();
+        if let Err(e) = map.save(path) {
+            eprintln!("Warning: {}", e);
+        }
+        map
+    }
+}
+
+/// Name of a gamepad axis as used in config files, e.g. `LeftX`.
+pub fn gamepad_axis_name(axis: GamepadAxis) -> &'static str {
+    PAD_AXES.iter().find(|(a, _)| *a == axis).map(|(_, n)| *n).unwrap_or("Unknown")
+}
+
+pub fn parse_gamepad_axis(name: &str) -> Option<GamepadAxis> {
+    PAD_AXES.iter().find(|(_, n)| *n == name).map(|&(a, _)| a)
+}
+
+macro_rules! named {
+    ($ty:ident { $($variant:ident => $name:literal),* $(,)? }) => {
+        &[$(($ty::$variant, $name)),*]
+    };
+}
+
+const MOUSE_BUTTONS: &[(MouseButton, &str)] = named!(MouseButton {
+    Button1 => "Left", Button2 => "Right", Button3 => "Middle", Button4 => "Button4",
+    Button5 => "Button5", Button6 => "Button6", Button7 => "Button7", Button8 => "Button8",
+});
+
+const PAD_BUTTONS: &[(GamepadButton, &str)] = named!(GamepadButton {
+    ButtonA => "A", ButtonB => "B", ButtonX => "X", ButtonY => "Y",
+    ButtonLeftBumper => "LeftBumper", ButtonRightBumper => "RightBumper",
+    ButtonBack => "Back", ButtonStart => "Start", ButtonGuide => "Guide",
+    ButtonLeftThumb => "LeftThumb", ButtonRightThumb => "RightThumb",
+    ButtonDpadUp => "DpadUp", ButtonDpadRight => "DpadRight", ButtonDpadDown => "DpadDown", ButtonDpadLeft => "DpadLeft",
+});
+
+const PAD_AXES: &[(GamepadAxis, &str)] = named!(GamepadAxis {
+    AxisLeftX => "LeftX", AxisLeftY => "LeftY", AxisRightX => "RightX", AxisRightY => "RightY",
+    AxisLeftTrigger => "LeftTrigger", AxisRightTrigger => "RightTrigger",
+});
+
+macro_rules! keys {
+    ($($variant:ident),* $(,)?) => {
+        &[$((Key::$variant, stringify!($variant))),*]
+    };
+}
+
+/// Every key by its `glfw::Key` variant name.
+const KEYS: &[(Key, &str)] = keys!(
+    Space, Apostrophe, Comma, Minus, Period, Slash, Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+    Semicolon, Equal, A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    LeftBracket, Backslash, RightBracket, GraveAccent, World1, World2, Escape, Enter, Tab, Backspace, Insert, Delete,
+    Right, Left, Down, Up, PageUp, PageDown, Home, End, CapsLock, ScrollLock, NumLock, PrintScreen, Pause,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, F25,
+    Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+    LeftShift, LeftControl, LeftAlt, LeftSuper, RightShift, RightControl, RightAlt, RightSuper, Menu,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use InputSource::{Key as K, GamepadAxis as A};
+
+    #[test]
+    fn parses_single_inputs() {
+        assert_eq!(Binding::parse("key:W"), Ok(Binding::new(K(Key::W))));
+        assert_eq!(Binding::parse(" mouse:Left "), Ok(Binding::new(InputSource::MouseButton(MouseButton::Button1))));
+        assert_eq!(Binding::parse("pad:A"), Ok(Binding::new(InputSource::GamepadButton(GamepadButton::ButtonA))));
+        assert_eq!(Binding::parse("pad_axis:LeftY-"), Ok(Binding::new(A(GamepadAxis::AxisLeftY, false))));
+    }
+
+    #[test]
+    fn parses_chords_and_scales() {
+        let chord = Binding::chord(&[K(Key::LeftControl), K(Key::S)]);
+        assert_eq!(Binding::parse("key:LeftControl + key:S"), Ok(chord.clone()));
+        assert_eq!(Binding::parse("key:LeftControl+key:S"), Ok(chord));
+        assert_eq!(Binding::parse("key:S * -1"), Ok(Binding::new(K(Key::S)).scaled(-1.0)));
+    }
+
+    #[test]
+    fn trailing_plus_is_an_axis_direction() {
+        let expected = Binding::chord(&[K(Key::LeftControl), A(GamepadAxis::AxisRightTrigger, true)]);
+        // Like Ctrl++: the last '+' belongs to the axis, not the chord separator
+        assert_eq!(Binding::parse("key:LeftControl+pad_axis:RightTrigger+"), Ok(expected.clone()));
+        assert_eq!(Binding::parse("key:LeftControl + pad_axis:RightTrigger+"), Ok(expected));
+        assert_eq!(
+            Binding::parse("pad_axis:LeftX++key:A"),
+            Ok(Binding::chord(&[A(GamepadAxis::AxisLeftX, true), K(Key::A)])),
+        );
+        assert_eq!(Binding::parse("pad_axis:LeftX+ * 0.5"), Ok(Binding::new(A(GamepadAxis::AxisLeftX, true)).scaled(0.5)));
+    }
+
+    #[test]
+    fn rejects_bad_bindings() {
+        for text in ["", "W", "key:Nope", "key:A +", "pad_axis:LeftX", "joystick:A", "key:A * fast"] {
+            assert!(Binding::parse(text).is_err(), "{:?} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn bindings_round_trip_through_display() {
+        let bindings = [
+            Binding::new(K(Key::Space)),
+            Binding::new(A(GamepadAxis::AxisLeftY, true)).scaled(-1.0),
+            Binding::chord(&[K(Key::F4), K(Key::R)]),
+            Binding::chord(&[K(Key::LeftShift), A(GamepadAxis::AxisRightTrigger, true)]).scaled(0.25),
+        ];
+        for binding in bindings {
+            assert_eq!(Binding::parse(&binding.to_string()), Ok(binding));
+        }
+    }
+
+    #[test]
+    fn config_string_round_trips() {
+        let defaults = ActionMap::defaults();
+        let parsed = ActionMap::parse(&defaults.to_config_string(), ActionMap::new()).unwrap();
+        assert_eq!(parsed.bindings, defaults.bindings);
+    }
+
+    #[test]
+    fn config_overrides_only_listed_actions() {
+        let map = ActionMap::parse("# comment\n\njump = key:J | pad:X\nnew_action = mouse:Right\n", ActionMap::defaults()).unwrap();
+        let find = |action: &str| map.bindings.iter().find(|(name, _)| name == action).map(|(_, list)| list.clone());
+        assert_eq!(find(JUMP), Some(vec![Binding::new(K(Key::J)), Binding::new(InputSource::GamepadButton(GamepadButton::ButtonX))]));
+        assert_eq!(find("new_action"), Some(vec![Binding::new(InputSource::MouseButton(MouseButton::Button2))]));
+        assert_eq!(find(QUIT), Some(vec![Binding::new(K(Key::Escape))]));
+        assert!(matches!(ActionMap::parse("jump key:J", ActionMap::new()), Err(e) if e.starts_with("Line 1")));
+    }
+}
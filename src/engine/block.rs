@@ -1,3 +1,5 @@
+use crate::engine::constants::blocks;
+
 /// Represents a block in the voxel world.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Block {
@@ -20,4 +22,21 @@ impl Block {
             Block::Air => None,
         }
     }
+
+    /// Human-readable name, for debug output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Block::Air => "air",
+            Block::Solid(blocks::GRASS) => "grass",
+            Block::Solid(blocks::DIRT) => "dirt",
+            Block::Solid(blocks::STONE) => "stone",
+            Block::Solid(blocks::BEDROCK) => "bedrock",
+            Block::Solid(blocks::WATER) => "water",
+            Block::Solid(blocks::SAND) => "sand",
+            Block::Solid(blocks::GRAVEL) => "gravel",
+            Block::Solid(blocks::LAVA) => "lava",
+            Block::Solid(blocks::PORTAL) => "portal",
+            Block::Solid(_) => "unknown",
+        }
+    }
 }
@@ -0,0 +1,395 @@
+use std::collections::VecDeque;
+use std::mem;
+use std::ptr;
+use glam::Vec3;
+use crate::engine::constants::{CHUNK_POOL_PAGE_VERTICES, CHUNK_POOL_FRAMES_IN_FLIGHT};
+use crate::engine::gl_caps::GlCaps;
+use crate::engine::mesh::{setup_vertex_attributes, bind_quad_index_buffer, FLOATS_PER_VERTEX};
+
+/// Vertex attribute carrying the chunk's world offset (per draw).
+pub const CHUNK_OFFSET_ATTRIB: u32 = 4;
+
+/// Layout of one `glMultiDrawElementsIndirect` command.
+#[repr(C)]
+struct DrawElementsIndirectCommand {
+    count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    base_instance: u32,
+}
+
+/// A chunk mesh stored as a range of quads inside one of the pool's shared vertex buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkMesh {
+    pub page: usize,
+    pub first_vertex: u32,
+    vertex_count: u32,
+}
+
+impl ChunkMesh {
+    /// Returns the number of vertices in this mesh.
+    pub fn vertex_count(&self) -> i32 { self.vertex_count as i32 }
+
+    /// Returns the number of indices drawn (6 per quad).
+    pub fn index_count(&self) -> i32 { (self.vertex_count / 4 * 6) as i32 }
+}
+
+/// First-fit free-list allocator over a range of vertex slots.
+/// Free ranges are kept sorted by start and coalesced on release.
+struct FreeList {
+    free: Vec<(u32, u32)>, // (start, len)
+}
+
+impl FreeList {
+    fn new(capacity: u32) -> Self { Self { free: vec![(0, capacity)] } }
+
+    fn allocate(&mut self, len: u32) -> Option<u32> {
+        let slot = self.free.iter().position(|&(_, free_len)| free_len >= len)?;
+        let (start, free_len) = self.free[slot];
+        if free_len == len {
+            self.free.remove(slot);
+        } else {
+            self.free[slot] = (start + len, free_len - len);
+        }
+        Some(start)
+    }
+
+    fn release(&mut self, start: u32, len: u32) {
+        let slot = self.free.partition_point(|&(s, _)| s < start);
+        self.free.insert(slot, (start, len));
+        // Merge with the following range, then with the preceding one
+        if slot + 1 < self.free.len() && start + len == self.free[slot + 1].0 {
+            self.free[slot].1 += self.free[slot + 1].1;
+            self.free.remove(slot + 1);
+        }
+        if slot > 0 && self.free[slot - 1].0 + self.free[slot - 1].1 == start {
+            self.free[slot - 1].1 += self.free[slot].1;
+            self.free.remove(slot);
+        }
+    }
+}
+
+/// One large vertex buffer with its own VAO.
+struct PoolPage {
+    vao: u32,
+    vbo: u32,
+    capacity: u32,
+    /// Persistently mapped pointer into the buffer, or null when uploads go through BufferSubData
+    mapped: *mut f32,
+    allocator: FreeList,
+    /// Whether the per-draw chunk offset attribute is sourced from the offset buffer
+    offset_attrib_enabled: bool,
+}
+
+/// Per-frame buffers for indirect draws: the command list and one chunk offset per command.
+struct IndirectBuffers {
+    command_buffer: u32,
+    offset_buffer: u32,
+}
+
+/// Sub-allocates chunk geometry out of a few large vertex buffers instead of one VAO/VBO per mesh.
+/// Freed ranges are held back until a fence shows the GPU has finished the frames that drew them.
+/// When `GL_ARB_buffer_storage` is available the pages are persistently mapped and written directly.
+pub struct ChunkBufferPool {
+    pages: Vec<PoolPage>,
+    pending_frees: Vec<(u64, ChunkMesh)>,
+    /// One fence per submitted frame, oldest first, tagged with the frame it ends
+    fences: VecDeque<(u64, gl::types::GLsync)>,
+    frame: u64,
+    persistent: Option<bool>,
+    indirect: Option<IndirectBuffers>,
+    commands: Vec<DrawElementsIndirectCommand>,
+    offsets: Vec<f32>,
+}
+
+impl Default for ChunkBufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkBufferPool {
+    /// Creates an empty pool. GL objects are created lazily on the first allocation.
+    pub fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            pending_frees: Vec::new(),
+            fences: VecDeque::new(),
+            frame: 0,
+            persistent: None,
+            indirect: None,
+            commands: Vec::new(),
+            offsets: Vec::new(),
+        }
+    }
+
+    /// Total number of vertex slots across all pages.
+    #[allow(dead_code)]
+    pub fn capacity(&self) -> u64 {
+        self.pages.iter().map(|p| p.capacity as u64).sum()
+    }
+
+    /// Call once at the start of every frame, before any allocation or free.
+    /// Fences the previous frame's commands, then returns ranges freed in frames the GPU has
+    /// finished to the free lists. Blocks if the GPU falls more than
+    /// `CHUNK_POOL_FRAMES_IN_FLIGHT` frames behind.
+    pub fn begin_frame(&mut self) {
+        let mut completed = None;
+        unsafe {
+            self.fences.push_back((self.frame, gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0)));
+            while let Some(&(frame, fence)) = self.fences.front() {
+                let must_wait = self.fences.len() as u64 > CHUNK_POOL_FRAMES_IN_FLIGHT;
+                let timeout = if must_wait { u64::MAX } else { 0 };
+                let status = gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, timeout);
+                if status == gl::TIMEOUT_EXPIRED { break; }
+                // WAIT_FAILED means a lost context; nothing will be drawn from the ranges anymore
+                gl::DeleteSync(fence);
+                self.fences.pop_front();
+                completed = Some(frame);
+            }
+        }
+        self.frame += 1;
+
+        if let Some(completed) = completed {
+            let pages = &mut self.pages;
+            self.pending_frees.retain(|&(freed_at, mesh)| {
+                let done = freed_at <= completed;
+                if done { pages[mesh.page].allocator.release(mesh.first_vertex, mesh.vertex_count); }
+                !done
+            });
+        }
+    }
+
+    /// Uploads quad vertices (4 per quad) into the pool.
+    pub fn allocate(&mut self, vertices: &[f32]) -> ChunkMesh {
+        assert!(vertices.len().is_multiple_of(FLOATS_PER_VERTEX * 4), "quad vertex slice must hold 4 vertices per quad");
+        let vertex_count = (vertices.len() / FLOATS_PER_VERTEX) as u32;
+
+        let found = self.pages.iter_mut().enumerate()
+            .find_map(|(i, page)| page.allocator.allocate(vertex_count).map(|start| (i, start)));
+        let (page, first_vertex) = match found {
+            Some(slot) => slot,
+            None => {
+                let capacity = CHUNK_POOL_PAGE_VERTICES.max(vertex_count);
+                let new_page = unsafe { self.create_page(capacity) };
+                self.pages.push(new_page);
+                let index = self.pages.len() - 1;
+                let start = self.pages[index].allocator.allocate(vertex_count).expect("fresh page fits the mesh");
+                (index, start)
+            }
+        };
+
+        unsafe {
+            let target = &self.pages[page];
+            // Make sure the shared index pattern covers this mesh's quads
+            gl::BindVertexArray(target.vao);
+            bind_quad_index_buffer(vertex_count as usize / 4);
+            gl::BindVertexArray(0);
+
+            let offset = first_vertex as usize * FLOATS_PER_VERTEX;
+            if target.mapped.is_null() {
+                gl::BindBuffer(gl::ARRAY_BUFFER, target.vbo);
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    (offset * mem::size_of::<f32>()) as isize,
+                    mem::size_of_val(vertices) as isize,
+                    vertices.as_ptr() as *const _,
+                );
+            } else {
+                ptr::copy_nonoverlapping(vertices.as_ptr(), target.mapped.add(offset), vertices.len());
+            }
+        }
+
+        ChunkMesh { page, first_vertex, vertex_count }
+    }
+
+    /// Returns a mesh's range to the pool once the GPU can no longer be using it.
+    pub fn free(&mut self, mesh: ChunkMesh) {
+        self.pending_frees.push((self.frame, mesh));
+    }
+
+    /// Returns true if the context can draw the pool with `glMultiDrawElementsIndirect`
+    /// (GL 4.3, or the multi-draw-indirect and base-instance extensions).
+    pub fn supports_indirect() -> bool {
+        let caps = GlCaps::get();
+        let core = caps.supports_version(4, 3)
+            || (caps.has_extension("GL_ARB_multi_draw_indirect") && caps.has_extension("GL_ARB_base_instance"));
+        core && gl::MultiDrawElementsIndirect::is_loaded()
+    }
+
+    /// Draws a single mesh from the pool, translated by `offset`.
+    /// This is the GL 3.3 path: one draw call per mesh with the offset set as a constant attribute.
+    pub unsafe fn draw_at(&mut self, mesh: &ChunkMesh, offset: Vec3) {
+        let page = &mut self.pages[mesh.page];
+        gl::BindVertexArray(page.vao);
+        if page.offset_attrib_enabled {
+            gl::DisableVertexAttribArray(CHUNK_OFFSET_ATTRIB);
+            page.offset_attrib_enabled = false;
+        }
+        gl::VertexAttrib3f(CHUNK_OFFSET_ATTRIB, offset.x, offset.y, offset.z);
+        gl::DrawElementsBaseVertex(gl::TRIANGLES, mesh.index_count(), gl::UNSIGNED_INT, ptr::null(), mesh.first_vertex as i32);
+    }
+
+    /// Draws all meshes with one `glMultiDrawElementsIndirect` call per run of meshes sharing a page.
+    /// Draw order is preserved, so back-to-front sorted lists stay sorted.
+    /// Each command's base instance indexes its chunk offset in a per-frame instanced attribute buffer.
+    pub unsafe fn draw_indirect(&mut self, draws: &[(ChunkMesh, Vec3)]) {
+        if draws.is_empty() { return; }
+        let buffers = self.indirect.get_or_insert_with(|| {
+            let (mut command_buffer, mut offset_buffer) = (0, 0);
+            gl::GenBuffers(1, &mut command_buffer);
+            gl::GenBuffers(1, &mut offset_buffer);
+            IndirectBuffers { command_buffer, offset_buffer }
+        });
+
+        self.commands.clear();
+        self.offsets.clear();
+        for (i, (mesh, offset)) in draws.iter().enumerate() {
+            self.commands.push(DrawElementsIndirectCommand {
+                count: mesh.index_count() as u32,
+                instance_count: 1,
+                first_index: 0,
+                base_vertex: mesh.first_vertex as i32,
+                base_instance: i as u32,
+            });
+            self.offsets.extend_from_slice(&offset.to_array());
+        }
+
+        // Orphan and refill both buffers; the driver hands back fresh storage if the old one is in use
+        gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, buffers.command_buffer);
+        let command_bytes = mem::size_of_val(self.commands.as_slice()) as isize;
+        gl::BufferData(gl::DRAW_INDIRECT_BUFFER, command_bytes, ptr::null(), gl::STREAM_DRAW);
+        gl::BufferSubData(gl::DRAW_INDIRECT_BUFFER, 0, command_bytes, self.commands.as_ptr() as *const _);
+        gl::BindBuffer(gl::ARRAY_BUFFER, buffers.offset_buffer);
+        let offset_bytes = mem::size_of_val(self.offsets.as_slice()) as isize;
+        gl::BufferData(gl::ARRAY_BUFFER, offset_bytes, ptr::null(), gl::STREAM_DRAW);
+        gl::BufferSubData(gl::ARRAY_BUFFER, 0, offset_bytes, self.offsets.as_ptr() as *const _);
+
+        let mut start = 0;
+        while start < draws.len() {
+            let page_index = draws[start].0.page;
+            let run = draws[start..].iter().take_while(|(mesh, _)| mesh.page == page_index).count();
+
+            let page = &mut self.pages[page_index];
+            gl::BindVertexArray(page.vao);
+            if !page.offset_attrib_enabled {
+                gl::BindBuffer(gl::ARRAY_BUFFER, buffers.offset_buffer);
+                gl::VertexAttribPointer(CHUNK_OFFSET_ATTRIB, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
+                gl::VertexAttribDivisor(CHUNK_OFFSET_ATTRIB, 1);
+                gl::EnableVertexAttribArray(CHUNK_OFFSET_ATTRIB);
+                page.offset_attrib_enabled = true;
+            }
+            gl::MultiDrawElementsIndirect(
+                gl::TRIANGLES,
+                gl::UNSIGNED_INT,
+                (start * mem::size_of::<DrawElementsIndirectCommand>()) as *const _,
+                run as i32,
+                0,
+            );
+            start += run;
+        }
+        gl::BindVertexArray(0);
+        gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+    }
+
+    unsafe fn create_page(&mut self, capacity: u32) -> PoolPage {
+        let persistent = *self.persistent.get_or_insert_with(|| {
+            let caps = GlCaps::get();
+            (caps.supports_version(4, 4) || caps.has_extension("GL_ARB_buffer_storage")) && gl::BufferStorage::is_loaded()
+        });
+
+        let (mut vao, mut vbo) = (0, 0);
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        let bytes = capacity as usize * FLOATS_PER_VERTEX * mem::size_of::<f32>();
+
+        let mut mapped = ptr::null_mut();
+        if persistent {
+            let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+            gl::BufferStorage(gl::ARRAY_BUFFER, bytes as isize, ptr::null(), flags);
+            mapped = gl::MapBufferRange(gl::ARRAY_BUFFER, 0, bytes as isize, flags) as *mut f32;
+        } else {
+            gl::BufferData(gl::ARRAY_BUFFER, bytes as isize, ptr::null(), gl::DYNAMIC_DRAW);
+        }
+
+        setup_vertex_attributes();
+        bind_quad_index_buffer(1);
+        gl::BindVertexArray(0);
+
+        PoolPage { vao, vbo, capacity, mapped, allocator: FreeList::new(capacity), offset_attrib_enabled: false }
+    }
+}
+
+impl Drop for ChunkBufferPool {
+    fn drop(&mut self) {
+        unsafe {
+            for page in &self.pages {
+                if !page.mapped.is_null() {
+                    gl::BindBuffer(gl::ARRAY_BUFFER, page.vbo);
+                    gl::UnmapBuffer(gl::ARRAY_BUFFER);
+                }
+                gl::DeleteVertexArrays(1, &page.vao);
+                gl::DeleteBuffers(1, &page.vbo);
+            }
+            if let Some(buffers) = &self.indirect {
+                gl::DeleteBuffers(1, &buffers.command_buffer);
+                gl::DeleteBuffers(1, &buffers.offset_buffer);
+            }
+            for (_, fence) in self.fences.drain(..) {
+                gl::DeleteSync(fence);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_first_fit() {
+        let mut list = FreeList::new(100);
+        assert_eq!(list.allocate(40), Some(0));
+        assert_eq!(list.allocate(40), Some(40));
+        assert_eq!(list.allocate(40), None);
+        assert_eq!(list.allocate(20), Some(80));
+        assert!(list.free.is_empty());
+    }
+
+    #[test]
+    fn release_reuses_the_range() {
+        let mut list = FreeList::new(100);
+        let a = list.allocate(30).unwrap();
+        list.allocate(30).unwrap();
+        list.release(a, 30);
+        assert_eq!(list.free, vec![(0, 30), (60, 40)]);
+        assert_eq!(list.allocate(25), Some(0));
+        assert_eq!(list.free, vec![(25, 5), (60, 40)]);
+    }
+
+    #[test]
+    fn release_coalesces_with_both_neighbours() {
+        let mut list = FreeList::new(90);
+        let a = list.allocate(30).unwrap();
+        let b = list.allocate(30).unwrap();
+        let c = list.allocate(30).unwrap();
+        list.release(a, 30);
+        list.release(c, 30);
+        assert_eq!(list.free, vec![(0, 30), (60, 30)]);
+        // Filling the gap joins all three ranges back into one
+        list.release(b, 30);
+        assert_eq!(list.free, vec![(0, 90)]);
+        assert_eq!(list.allocate(90), Some(0));
+    }
+
+    #[test]
+    fn release_coalesces_out_of_order() {
+        let mut list = FreeList::new(40);
+        let starts: Vec<u32> = (0..4).map(|_| list.allocate(10).unwrap()).collect();
+        for &i in &[2, 0, 3, 1] { list.release(starts[i], 10); }
+        assert_eq!(list.free, vec![(0, 40)]);
+    }
+}
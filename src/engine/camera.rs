@@ -10,15 +10,61 @@ pub struct Camera {
     pub aspect: f32,
     pub z_near: f32,
     pub z_far: f32,
+    /// Renders only this part of the view, scaled up to fill the viewport (for tiled screenshots)
+    pub projection_tile: Option<ProjectionTile>,
+}
+
+/// One cell of a grid laid over the view; `x` counts from the left and `y` from the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectionTile {
+    pub columns: u32,
+    pub rows: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl ProjectionTile {
+    /// Maps the tile's part of clip space onto the whole of it.
+    pub fn matrix(&self) -> Mat4 {
+        let (columns, rows) = (self.columns as f32, self.rows as f32);
+        let center_x = -1.0 + (2.0 * self.x as f32 + 1.0) / columns;
+        let center_y = 1.0 - (2.0 * self.y as f32 + 1.0) / rows;
+        Mat4::from_scale(Vec3::new(columns, rows, 1.0)) * Mat4::from_translation(Vec3::new(-center_x, -center_y, 0.0))
+    }
 }
 
 /// View frustum planes for culling.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Frustum {
     pub planes: [Vec4; 6],
 }
 
 impl Frustum {
+    /// Extracts the six clip planes of a view-projection matrix (Gribb/Hartmann).
+    pub fn from_matrix(view_proj: Mat4) -> Self {
+        let m = view_proj.to_cols_array();
+        let left   = Vec4::new(m[3] + m[0], m[7] + m[4], m[11] + m[8], m[15] + m[12]).normalize();
+        let right  = Vec4::new(m[3] - m[0], m[7] - m[4], m[11] - m[8], m[15] - m[12]).normalize();
+        let bottom = Vec4::new(m[3] + m[1], m[7] + m[5], m[11] + m[9], m[15] + m[13]).normalize();
+        let top    = Vec4::new(m[3] - m[1], m[7] - m[5], m[11] - m[9], m[15] - m[13]).normalize();
+        let near   = Vec4::new(m[3] + m[2], m[7] + m[6], m[11] + m[10], m[15] + m[14]).normalize();
+        let far    = Vec4::new(m[3] - m[2], m[7] - m[6], m[11] - m[10], m[15] - m[14]).normalize();
+        Frustum { planes: [left, right, bottom, top, near, far] }
+    }
+
+    /// The frustum's corners: four on the near plane, then four on the far plane, each
+    /// in the order bottom left, bottom right, top right, top left.
+    pub fn corners(&self) -> [Vec3; 8] {
+        let [left, right, bottom, top, near, far] = self.planes;
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, &depth) in [near, far].iter().enumerate() {
+            for (j, &(side, height)) in [(left, bottom), (right, bottom), (right, top), (left, top)].iter().enumerate() {
+                corners[i * 4 + j] = intersect_planes(side, height, depth);
+            }
+        }
+        corners
+    }
+
     /// Checks if an axis-aligned bounding box is inside or intersects the frustum.
     pub fn contains_aabb(&self, min: Vec3, max: Vec3) -> bool {
         for plane in &self.planes {
@@ -37,6 +83,13 @@ impl Frustum {
     }
 }
 
+/// The point where three planes (`n·p + d = 0`) meet.
+fn intersect_planes(a: Vec4, b: Vec4, c: Vec4) -> Vec3 {
+    let (na, nb, nc) = (a.truncate(), b.truncate(), c.truncate());
+    let denom = na.dot(nb.cross(nc));
+    (nb.cross(nc) * -a.w + nc.cross(na) * -b.w + na.cross(nb) * -c.w) / denom
+}
+
 impl Camera {
     pub fn new(aspect: f32) -> Self {
         Self {
@@ -47,6 +100,7 @@ impl Camera {
             aspect,
             z_near: CAMERA_Z_NEAR,
             z_far: CAMERA_Z_FAR,
+            projection_tile: None,
         }
     }
 
@@ -56,7 +110,11 @@ impl Camera {
     }
 
     pub fn projection_matrix(&self) -> Mat4 {
-        Mat4::perspective_rh(self.fov_y, self.aspect, self.z_near, self.z_far)
+        let projection = Mat4::perspective_rh(self.fov_y, self.aspect, self.z_near, self.z_far);
+        match self.projection_tile {
+            Some(tile) => tile.matrix() * projection,
+            None => projection,
+        }
     }
 
     pub fn front(&self) -> Vec3 {
@@ -72,15 +130,25 @@ impl Camera {
     pub fn up(&self) -> Vec3 { self.right().cross(self.front()).normalize() }
 
     pub fn frustum(&self) -> Frustum {
-        let view_proj = self.projection_matrix() * self.view_matrix();
-        let m = view_proj.to_cols_array();
-        let left   = Vec4::new(m[3] + m[0], m[7] + m[4], m[11] + m[8], m[15] + m[12]).normalize();
-        let right  = Vec4::new(m[3] - m[0], m[7] - m[4], m[11] - m[8], m[15] - m[12]).normalize();
-        let bottom = Vec4::new(m[3] + m[1], m[7] + m[5], m[11] + m[9], m[15] + m[13]).normalize();
-        let top    = Vec4::new(m[3] - m[1], m[7] - m[5], m[11] - m[9], m[15] - m[13]).normalize();
-        let near   = Vec4::new(m[3] + m[2], m[7] + m[6], m[11] + m[10], m[15] + m[14]).normalize();
-        let far    = Vec4::new(m[3] - m[2], m[7] - m[6], m[11] - m[10], m[15] - m[14]).normalize();
-        Frustum { planes: [left, right, bottom, top, near, far] }
+        Frustum::from_matrix(self.projection_matrix() * self.view_matrix())
+    }
+
+    /// World-space corners of the slice of the view frustum between distances `near` and `far`:
+    /// the four near corners followed by the four far corners.
+    pub fn frustum_corners(&self, near: f32, far: f32) -> [Vec3; 8] {
+        let (front, right) = (self.front(), self.right());
+        let up = right.cross(front);
+        let tan_half = (self.fov_y * 0.5).tan();
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, &d) in [near, far].iter().enumerate() {
+            let center = self.position + front * d;
+            let (h, w) = (tan_half * d, tan_half * d * self.aspect);
+            corners[i * 4] = center - right * w - up * h;
+            corners[i * 4 + 1] = center + right * w - up * h;
+            corners[i * 4 + 2] = center + right * w + up * h;
+            corners[i * 4 + 3] = center - right * w + up * h;
+        }
+        corners
     }
 
     pub fn process_keyboard(&mut self, direction: CameraMove, delta: f32) {
@@ -95,25 +163,56 @@ impl Camera {
         }
     }
 
+    /// Turns by a mouse movement in pixels.
+    #[allow(dead_code)]
     pub fn process_mouse(&mut self, dx: f32, dy: f32) {
-        self.yaw += dx * CAMERA_MOUSE_SENSITIVITY;
-        self.pitch -= dy * CAMERA_MOUSE_SENSITIVITY;
+        self.rotate(dx * CAMERA_MOUSE_SENSITIVITY, -dy * CAMERA_MOUSE_SENSITIVITY);
+    }
+
+    /// Turns by yaw and pitch deltas in radians, clamping pitch short of straight up or down.
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch += pitch;
         let max_pitch = 89.0f32.to_radians();
         if self.pitch > max_pitch { self.pitch = max_pitch; }
         if self.pitch < -max_pitch { self.pitch = -max_pitch; }
     }
 }
 
+/// Direction to move the camera in; a negative `delta` moves the opposite way.
+#[allow(dead_code)]
 pub enum CameraMove { Forward, Backward, Left, Right, Up, Down }
 
-#[allow(dead_code)]
+/// Uniform buffer binding point of the `Camera` uniform block.
+pub const CAMERA_UNIFORM_BINDING: u32 = 0;
+
+/// Camera data shared by all shaders through the std140 `Camera` uniform block:
+/// ```glsl
+/// layout(std140) uniform Camera { mat4 uViewProj; mat4 uView; mat4 uProjection; vec4 uCameraPosition; };
+/// ```
 pub struct CameraUniform {
     pub view_proj: Mat4,
+    pub view: Mat4,
+    pub projection: Mat4,
+    pub position: Vec3,
 }
 
-#[allow(dead_code)]
 impl CameraUniform {
+    /// Size of the std140 block in floats: three mat4s and a vec4.
+    pub const STD140_FLOATS: usize = 16 * 3 + 4;
+
     pub fn new(cam: &Camera) -> Self {
-        Self { view_proj: cam.projection_matrix() * cam.view_matrix() }
+        let view = cam.view_matrix();
+        let projection = cam.projection_matrix();
+        Self { view_proj: projection * view, view, projection, position: cam.position }
+    }
+
+    pub fn to_std140(&self) -> [f32; Self::STD140_FLOATS] {
+        let mut data = [0.0; Self::STD140_FLOATS];
+        data[0..16].copy_from_slice(&self.view_proj.to_cols_array());
+        data[16..32].copy_from_slice(&self.view.to_cols_array());
+        data[32..48].copy_from_slice(&self.projection.to_cols_array());
+        data[48..51].copy_from_slice(&self.position.to_array());
+        data
     }
 }
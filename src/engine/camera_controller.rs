@@ -0,0 +1,243 @@
+use glam::{Vec2, Vec3};
+use crate::engine::actions::{self, ActionMap};
+use crate::engine::camera::{Camera, CameraMove};
+use crate::engine::constants::{CAMERA_MOUSE_SENSITIVITY, CAMERA_SPRINT_MULTIPLIER, controllers, gamepad};
+use crate::engine::input::InputState;
+
+/// Movement intent for one frame, gathered from actions so controllers don't care
+/// whether it came from a keyboard, mouse or gamepad.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ControlInput {
+    /// Right, up and forward, each -1..1
+    pub movement: Vec3,
+    /// Yaw and pitch change in radians (positive turns right and up)
+    pub look: Vec2,
+    /// Scroll wheel steps; positive zooms in
+    pub zoom: f32,
+    pub sprint: bool,
+    pub jump: bool,
+}
+
+impl ControlInput {
+    /// Reads the movement actions. Mouse look only counts while the cursor is captured.
+    pub fn from_actions(actions: &ActionMap, input: &InputState, mouse_captured: bool, dt: f32) -> Self {
+        let mouse = if mouse_captured { input.cursor_delta() * CAMERA_MOUSE_SENSITIVITY } else { Vec2::ZERO };
+        let stick = Vec2::new(actions.value(actions::LOOK_RIGHT), actions.value(actions::LOOK_UP)) * (gamepad::LOOK_SPEED * dt);
+        Self {
+            movement: Vec3::new(
+                actions.value(actions::MOVE_RIGHT),
+                actions.value(actions::MOVE_UP),
+                actions.value(actions::MOVE_FORWARD),
+            ),
+            // Screen y grows downwards
+            look: Vec2::new(mouse.x, -mouse.y) + stick,
+            zoom: input.scroll_delta().y,
+            sprint: actions.is_down(actions::SPRINT),
+            jump: actions.is_down(actions::JUMP),
+        }
+    }
+}
+
+/// Tells a controller which blocks it collides with.
+pub trait CollisionQuery {
+    fn is_solid(&self, x: i32, y: i32, z: i32) -> bool;
+
+    /// Whether the block's terrain has loaded; controllers with gravity wait for it.
+    fn is_loaded(&self, _x: i32, _y: i32, _z: i32) -> bool { true }
+}
+
+impl<F: Fn(i32, i32, i32) -> bool> CollisionQuery for F {
+    fn is_solid(&self, x: i32, y: i32, z: i32) -> bool { self(x, y, z) }
+}
+
+/// Moves the camera from player input. The `Game` owns one and calls `update` each frame,
+/// so games pick (or write) the controls they want.
+pub trait CameraController {
+    fn update(&mut self, camera: &mut Camera, input: &ControlInput, world: &dyn CollisionQuery, dt: f32);
+
+    /// Called when the game switches to this controller, to pick up from the current camera.
+    fn activate(&mut self, _camera: &Camera) {}
+
+    fn name(&self) -> &'static str;
+}
+
+/// Free flight through everything.
+pub struct FlyCamController {
+    pub sprint_multiplier: f32,
+}
+
+impl Default for FlyCamController {
+    fn default() -> Self { Self { sprint_multiplier: CAMERA_SPRINT_MULTIPLIER } }
+}
+
+impl CameraController for FlyCamController {
+    fn update(&mut self, camera: &mut Camera, input: &ControlInput, _world: &dyn CollisionQuery, dt: f32) {
+        camera.rotate(input.look.x, input.look.y);
+        // Axis values are signed, so a negative forward value moves backward
+        let step = dt * if input.sprint { self.sprint_multiplier } else { 1.0 };
+        camera.process_keyboard(CameraMove::Forward, input.movement.z * step);
+        camera.process_keyboard(CameraMove::Right, input.movement.x * step);
+        camera.process_keyboard(CameraMove::Up, input.movement.y * step);
+    }
+
+    fn name(&self) -> &'static str { "fly" }
+}
+
+/// First-person walking with gravity, jumping and collision against solid blocks.
+pub struct WalkController {
+    pub velocity: Vec3,
+    pub on_ground: bool,
+}
+
+impl Default for WalkController {
+    fn default() -> Self { Self { velocity: Vec3::ZERO, on_ground: false } }
+}
+
+impl WalkController {
+    /// Feet position for a camera at eye height.
+    fn feet(camera: &Camera) -> Vec3 { camera.position - Vec3::Y * controllers::EYE_HEIGHT }
+
+    /// Whether any block overlapped by the player's box, extended `below` blocks down, passes `test`.
+    fn any_block(feet: Vec3, below: i32, mut test: impl FnMut(i32, i32, i32) -> bool) -> bool {
+        let half = controllers::PLAYER_HALF_WIDTH;
+        let min = (feet - Vec3::new(half, 0.0, half)).floor().as_ivec3();
+        let max = (feet + Vec3::new(half, controllers::PLAYER_HEIGHT, half) - Vec3::splat(1e-4)).floor().as_ivec3();
+        for x in min.x..=max.x {
+            for y in min.y - below..=max.y {
+                for z in min.z..=max.z {
+                    if test(x, y, z) { return true; }
+                }
+            }
+        }
+        false
+    }
+
+    fn collides(world: &dyn CollisionQuery, feet: Vec3) -> bool {
+        Self::any_block(feet, 0, |x, y, z| world.is_solid(x, y, z))
+    }
+}
+
+impl CameraController for WalkController {
+    fn update(&mut self, camera: &mut Camera, input: &ControlInput, world: &dyn CollisionQuery, dt: f32) {
+        camera.rotate(input.look.x, input.look.y);
+        // Long frames would tunnel through floors
+        let dt = dt.min(controllers::MAX_STEP_SECS);
+
+        let forward = Vec3::new(camera.front().x, 0.0, camera.front().z).normalize_or_zero();
+        let right = camera.right();
+        let wish = (forward * input.movement.z + right * input.movement.x).clamp_length_max(1.0);
+        // Hang in place until the terrain around and under the player has loaded instead of
+        // falling through it
+        if Self::any_block(Self::feet(camera), 1, |x, y, z| !world.is_loaded(x, y, z)) {
+            self.velocity = Vec3::ZERO;
+            self.on_ground = false;
+            return;
+        }
+
+        let speed = controllers::WALK_SPEED * if input.sprint { controllers::RUN_MULTIPLIER } else { 1.0 };
+        self.velocity.x = wish.x * speed;
+        self.velocity.z = wish.z * speed;
+        if input.jump && self.on_ground { self.velocity.y = controllers::JUMP_SPEED; }
+        self.velocity.y = (self.velocity.y - controllers::GRAVITY * dt).max(-controllers::TERMINAL_SPEED);
+
+        // Resolve one axis at a time so walls slide instead of stopping all movement
+        let mut feet = Self::feet(camera);
+        // Climb out if we start inside terrain (e.g. switching from the flycam)
+        let mut climbed = 0;
+        while Self::collides(world, feet) && climbed < controllers::MAX_UNSTICK_BLOCKS {
+            feet.y = feet.y.floor() + 1.0;
+            climbed += 1;
+        }
+        self.on_ground = false;
+        for axis in 0..3 {
+            let mut step = Vec3::ZERO;
+            step[axis] = self.velocity[axis] * dt;
+            if Self::collides(world, feet + step) {
+                if axis == 1 && self.velocity.y < 0.0 { self.on_ground = true; }
+                self.velocity[axis] = 0.0;
+            } else {
+                feet += step;
+            }
+        }
+        camera.position = feet + Vec3::Y * controllers::EYE_HEIGHT;
+    }
+
+    fn activate(&mut self, _camera: &Camera) {
+        self.velocity = Vec3::ZERO;
+        self.on_ground = false;
+    }
+
+    fn name(&self) -> &'static str { "walk" }
+}
+
+/// Circles a target point: look input orbits, scrolling zooms and movement pans the target.
+pub struct OrbitController {
+    pub target: Vec3,
+    pub distance: f32,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self { Self { target: Vec3::ZERO, distance: controllers::ORBIT_DISTANCE } }
+}
+
+impl CameraController for OrbitController {
+    fn update(&mut self, camera: &mut Camera, input: &ControlInput, _world: &dyn CollisionQuery, dt: f32) {
+        camera.rotate(input.look.x, input.look.y);
+        self.distance = (self.distance * (1.0 - controllers::ORBIT_ZOOM_STEP).powf(input.zoom))
+            .clamp(controllers::ORBIT_MIN_DISTANCE, controllers::ORBIT_MAX_DISTANCE);
+
+        // Pan speed grows with distance so it feels the same at any zoom
+        let forward = Vec3::new(camera.front().x, 0.0, camera.front().z).normalize_or_zero();
+        let pan = forward * input.movement.z + camera.right() * input.movement.x + Vec3::Y * input.movement.y;
+        self.target += pan * (self.distance * controllers::ORBIT_PAN_SPEED * dt);
+
+        camera.position = self.target - camera.front() * self.distance;
+    }
+
+    /// Orbits whatever the camera is looking at, at the default distance.
+    fn activate(&mut self, camera: &Camera) {
+        self.distance = controllers::ORBIT_DISTANCE;
+        self.target = camera.position + camera.front() * self.distance;
+    }
+
+    fn name(&self) -> &'static str { "orbit" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flat ground filling everything below y = 0, optionally not loaded yet.
+    struct Ground { loaded: bool }
+
+    impl CollisionQuery for Ground {
+        fn is_solid(&self, _x: i32, y: i32, _z: i32) -> bool { !self.loaded || y < 0 }
+        fn is_loaded(&self, _x: i32, _y: i32, _z: i32) -> bool { self.loaded }
+    }
+
+    fn camera_at_height(feet_y: f32) -> Camera {
+        let mut camera = Camera::new(1.0);
+        camera.position = Vec3::new(0.5, feet_y + controllers::EYE_HEIGHT, 0.5);
+        camera
+    }
+
+    #[test]
+    fn walk_waits_for_unloaded_terrain() {
+        let mut camera = camera_at_height(5.0);
+        let start = camera.position;
+        let mut walk = WalkController::default();
+        let input = ControlInput { movement: Vec3::Z, ..ControlInput::default() };
+        for _ in 0..60 { walk.update(&mut camera, &input, &Ground { loaded: false }, 1.0 / 60.0); }
+        assert_eq!(camera.position, start);
+        assert_eq!(walk.velocity, Vec3::ZERO);
+    }
+
+    #[test]
+    fn walk_lands_on_loaded_ground() {
+        let mut camera = camera_at_height(5.0);
+        let mut walk = WalkController::default();
+        for _ in 0..120 { walk.update(&mut camera, &ControlInput::default(), &Ground { loaded: true }, 1.0 / 60.0); }
+        assert!(walk.on_ground);
+        assert!((camera.position.y - controllers::EYE_HEIGHT).abs() < 0.1, "feet at {}", camera.position.y - controllers::EYE_HEIGHT);
+    }
+}
@@ -2,22 +2,188 @@
 pub const DEFAULT_WINDOW_WIDTH: u32 = 1600; 
 pub const DEFAULT_WINDOW_HEIGHT: u32 = 1200;
 
-/// Sky blue clear color (RGBA)
+/// Sky blue clear color (RGBA), only seen where the sky dome isn't drawn
 pub const CLEAR_COLOR: (f32, f32, f32, f32) = (0.4, 0.6, 0.9, 1.0);
 
+/// Shader loading: directory watched for hot reloading in dev builds
+pub const SHADER_ASSET_DIR: &str = "assets/shaders";
+pub const SHADER_DIR_ENV: &str = "OXIDIZE_SHADER_DIR";
+pub const SHADER_RELOAD_INTERVAL_SECS: f32 = 0.5;
+
+/// Resource pack directory for block textures (optional)
+pub const RESOURCE_PACK_DIR: &str = "assets/resourcepack";
+/// Length of one `frametime` unit in resource pack animation metadata (a 20 Hz game tick)
+pub const TILE_ANIMATION_TICK_SECS: f32 = 0.05;
+
 /// Camera configuration
 pub const CAMERA_FOV_Y_DEGREES: f32 = 60.0;
-pub const CAMERA_Z_NEAR: f32 = 0.1;
-pub const CAMERA_Z_FAR: f32 = 500.0;
+// Near stays inside the player's half-width so walls don't clip; together with far it sets depth precision
+pub const CAMERA_Z_NEAR: f32 = 0.2;
+pub const CAMERA_Z_FAR: f32 = 2048.0; // Covers the coarsest LOD ring's corners
 pub const CAMERA_MOVE_SPEED: f32 = 8.0;
 pub const CAMERA_SPRINT_MULTIPLIER: f32 = 2.5;
 pub const CAMERA_MOUSE_SENSITIVITY: f32 = 0.0025;
 
+/// Input action bindings file, created with the defaults if missing
+pub const INPUT_CONFIG_PATH: &str = "assets/input.cfg";
+/// How far an analog input must be pushed for its action to count as down
+pub const ACTION_PRESS_THRESHOLD: f32 = 0.5;
+/// Where the record toggle writes input recordings (replay them with `--replay <file>`)
+pub const RECORDING_PATH: &str = "recording.replay";
+/// Where the profile capture toggle writes its Chrome trace (open it in chrome://tracing or Perfetto)
+pub const PROFILE_TRACE_PATH: &str = "profile.json";
+
+/// Debug overlay layout (see `DebugHud`)
+pub mod debug_hud {
+    /// Frames averaged for the FPS and frame time readout
+    pub const FRAME_HISTORY: usize = 60;
+    /// Distance from the window corner in pixels
+    pub const MARGIN: f32 = 6.0;
+}
+
+/// Screenshots and image sequences (see `screenshot`)
+pub mod screenshots {
+    /// Where screenshots and image sequence directories are written
+    pub const DIR: &str = "screenshots";
+    /// Size multiple of tiled screenshots, in each direction
+    pub const TILED_SCALE: u32 = 4;
+    /// Frames waiting to be encoded before capturing blocks
+    pub const WRITE_QUEUE: usize = 4;
+    /// Simulated frame rate while recording an image sequence, so the video plays at real speed
+    pub const SEQUENCE_FPS: f32 = 60.0;
+}
+
+/// Frame profiler history and graph layout (see `Profiler`)
+pub mod profiler {
+    /// Frames kept for the rolling graph and the per-scope averages
+    pub const HISTORY: usize = 120;
+    /// Height of the CPU and GPU graphs in pixels
+    pub const GRAPH_HEIGHT: f32 = 64.0;
+    /// Width of one frame's bar in pixels
+    pub const BAR_WIDTH: f32 = 2.0;
+    /// Frame time at the top of the graphs, in milliseconds
+    pub const GRAPH_MAX_MS: f32 = 33.3;
+    /// Frame time marked with a line across the graphs (60 fps)
+    pub const TARGET_FRAME_MS: f32 = 16.7;
+}
+
+/// Camera controller tuning (see `camera_controller`)
+pub mod controllers {
+    pub const WALK_SPEED: f32 = 4.3;
+    pub const RUN_MULTIPLIER: f32 = 1.3;
+    pub const JUMP_SPEED: f32 = 8.5;
+    pub const GRAVITY: f32 = 28.0;
+    pub const TERMINAL_SPEED: f32 = 60.0;
+    /// Longest physics step, so a slow frame can't carry the player through a block
+    pub const MAX_STEP_SECS: f32 = 0.05;
+    /// Player collision box: half its width and its full height, with the eye near the top
+    pub const PLAYER_HALF_WIDTH: f32 = 0.3;
+    pub const PLAYER_HEIGHT: f32 = 1.8;
+    pub const EYE_HEIGHT: f32 = 1.62;
+    /// How far the walk controller climbs per frame to escape from inside terrain
+    pub const MAX_UNSTICK_BLOCKS: i32 = 64;
+    pub const ORBIT_DISTANCE: f32 = 20.0;
+    pub const ORBIT_MIN_DISTANCE: f32 = 2.0;
+    pub const ORBIT_MAX_DISTANCE: f32 = 400.0;
+    /// Fraction of the distance each scroll step zooms in
+    pub const ORBIT_ZOOM_STEP: f32 = 0.1;
+    /// Target pan speed, in orbit distances per second
+    pub const ORBIT_PAN_SPEED: f32 = 0.5;
+}
+
+/// Gamepad stick shaping and look speed (see `Gamepads`)
+pub mod gamepad {
+    /// Optional SDL_GameControllerDB mappings loaded at startup for controllers GLFW doesn't know
+    pub const MAPPINGS_PATH: &str = "gamecontrollerdb.txt";
+    pub const STICK_DEADZONE: f32 = 0.15;
+    /// Response curve exponent for sticks
+    pub const STICK_CURVE: f32 = 2.0;
+    pub const TRIGGER_DEADZONE: f32 = 0.05;
+    /// Camera turn rate at full right stick deflection, in radians per second
+    pub const LOOK_SPEED: f32 = 3.0;
+}
+
 /// Chunk loading settings
 pub const MAX_NEW_CHUNKS_PER_FRAME: usize = 16;
 pub const DEFAULT_RENDER_DISTANCE: i32 = 6;
 pub const MAX_MESH_REBUILDS_PER_FRAME: usize = 4;
 pub const MAX_CHUNK_RECEIVES_PER_FRAME: usize = 8;
+/// Chunk layers generated in every column, from underground up to ~160 blocks high
+pub const MIN_CHUNK_Y: i32 = -1;
+pub const MAX_CHUNK_Y: i32 = 4;
+
+/// Chunk geometry pool settings
+pub const CHUNK_POOL_PAGE_VERTICES: u32 = 1 << 20; // 48 MiB per page
+pub const CHUNK_POOL_FRAMES_IN_FLIGHT: u64 = 3;
+
+/// Distant terrain level of detail (see `LodTerrain`)
+pub mod lod {
+    /// Number of LOD rings around the full-resolution chunks; ring n has cells of 2^n blocks
+    pub const LEVELS: u32 = 4;
+    /// Minimum half-width of each ring, in that ring's chunks
+    pub const RADIUS: i32 = 4;
+    /// Rings up to this cell size are downsampled from generated voxels (with caves);
+    /// coarser rings are built straight from the heightmap
+    pub const VOXEL_MAX_SCALE: i32 = 2;
+    /// Cells of wall hanging below the surface at ring and chunk borders to hide cracks
+    pub const SKIRT_CELLS: i32 = 3;
+    /// Top of the generated terrain covered by LOD chunks
+    pub const MAX_HEIGHT: i32 = 160;
+    pub const MAX_REQUESTS_PER_FRAME: usize = 8;
+    pub const MAX_RECEIVES_PER_FRAME: usize = 8;
+    pub const MAX_REBUILDS_PER_FRAME: usize = 4;
+}
+
+/// Fraction of the view distance at which the above-water haze starts; it is opaque at the
+/// view distance so terrain fades out before it ends
+pub const FOG_START_FRACTION: f32 = 0.3;
+
+/// Cascaded shadow map defaults (see `ShadowSettings`)
+pub mod shadows {
+    pub const CASCADE_COUNT: usize = 4;
+    pub const RESOLUTION: i32 = 2048;
+    /// View distance covered by the cascades; beyond it everything is lit
+    pub const DISTANCE: f32 = 160.0;
+    /// Blend between logarithmic (1.0) and uniform (0.0) cascade splits
+    pub const SPLIT_LAMBDA: f32 = 0.8;
+    /// Extra depth towards the sun so casters outside a cascade's slice still shadow it
+    pub const CASTER_MARGIN: f32 = 128.0;
+}
+
+/// Post-processing defaults (see `PostSettings`)
+pub mod post {
+    pub const EXPOSURE: f32 = 1.0;
+    pub const GAMMA: f32 = 2.2;
+    pub const BLOOM_THRESHOLD: f32 = 0.9;
+    pub const BLOOM_INTENSITY: f32 = 0.6;
+    /// Horizontal + vertical blur passes over the half-resolution bright image
+    pub const BLOOM_BLUR_PASSES: usize = 3;
+    pub const VIGNETTE_STRENGTH: f32 = 0.35;
+    pub const VIGNETTE_RADIUS: f32 = 0.6;
+}
+
+/// Water surface look (defaults for `WaterParams`)
+pub mod water {
+    pub const WAVE_AMPLITUDE: f32 = 0.06;
+    pub const WAVE_SPEED: f32 = 1.2;
+    pub const REFRACTION_STRENGTH: f32 = 0.03;
+    pub const ABSORPTION: [f32; 3] = [0.45, 0.12, 0.08];
+    pub const DEEP_COLOR: [f32; 3] = [0.02, 0.12, 0.2];
+}
+
+/// Day/night cycle and cloud layer (defaults for `Sky`)
+pub mod sky {
+    /// Real seconds for a full day/night cycle
+    pub const DAY_LENGTH_SECS: f32 = 600.0;
+    /// 0 = midnight, 0.25 = sunrise, 0.5 = noon
+    pub const START_TIME_OF_DAY: f32 = 0.3;
+    pub const CLOUD_ALTITUDE: f32 = 180.0;
+    pub const CLOUD_COVERAGE: f32 = 0.45;
+    /// Noise frequency per block of the cloud layer
+    pub const CLOUD_SCALE: f32 = 0.004;
+    /// Cloud drift in blocks per second (x, z)
+    pub const CLOUD_WIND: [f32; 2] = [4.0, 1.5];
+}
 
 /// Terrain generation noise parameters (Minecraft-style)
 pub mod noise {
@@ -65,4 +231,6 @@ pub mod blocks {
     pub const WATER: u8 = 5;
     pub const SAND: u8 = 6;
     pub const GRAVEL: u8 = 7;
+    pub const LAVA: u8 = 8;
+    pub const PORTAL: u8 = 9;
 }
@@ -1,10 +1,31 @@
-use glfw::{Context, Key, WindowEvent, GlfwReceiver, PWindow};
+use glfw::{Context, WindowEvent, GlfwReceiver, PWindow};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-use crate::engine::camera::Camera;
+use crate::engine::actions::{self, ActionMap};
+use crate::engine::camera::{Camera, ProjectionTile};
+use crate::engine::camera_controller::ControlInput;
 use crate::engine::input::InputState;
 use crate::engine::game::Game;
-use crate::engine::constants::{CLEAR_COLOR, CAMERA_SPRINT_MULTIPLIER};
+use crate::engine::chunk::CHUNK_SIZE;
+use crate::engine::constants::{screenshots, CLEAR_COLOR, INPUT_CONFIG_PATH, PROFILE_TRACE_PATH, RECORDING_PATH};
+use crate::engine::debug_draw::DebugDraw;
+use crate::engine::debug_hud::DebugHud;
+use crate::engine::gamepad::Gamepads;
+use crate::engine::framebuffer::{Framebuffer, FramebufferDesc};
+use crate::engine::profiler::Profiler;
+use crate::engine::post::{PostEffect, PostProcessor};
+use crate::engine::replay::{InputRecorder, InputReplay, ReplayStart};
+use crate::engine::screenshot::{self, FrameSequence, ImageWriter};
+use crate::engine::text::TextRenderer;
+
+/// Actions that toggle the post effects, in `PostEffect::ALL` order.
+const POST_TOGGLE_ACTIONS: [&str; 5] = [
+    actions::TOGGLE_BLOOM, actions::TOGGLE_TONEMAP, actions::TOGGLE_GAMMA, actions::TOGGLE_FXAA, actions::TOGGLE_VIGNETTE,
+];
+
+/// OpenGL context versions to try, most capable first.
+const GL_CONTEXT_VERSIONS: [(u32, u32); 2] = [(4, 3), (3, 3)];
 
 pub struct Engine {
     pub glfw: glfw::Glfw,
@@ -15,6 +36,34 @@ pub struct Engine {
     pub time: f32,
     pub frame: u64,
     pub input: InputState,
+    /// Feeds the connected controller into `input`
+    pub gamepads: Gamepads,
+    /// Named actions bound to `input`, updated each frame after events are polled
+    pub actions: ActionMap,
+    /// Writes each frame's input and `dt` to a file while recording
+    recorder: Option<InputRecorder>,
+    /// Feeds recorded input and `dt` instead of live input while replaying
+    replay: Option<InputReplay>,
+    /// Whether the cursor is hidden and locked to the window for mouse look
+    cursor_captured: bool,
+    /// Window framebuffer size in pixels
+    pub framebuffer_size: (i32, i32),
+    /// HDR target the game renders the scene into
+    pub scene_target: Framebuffer,
+    /// Post-processing chain that resolves `scene_target` to the window
+    pub post: PostProcessor,
+    /// Screen text drawn over the final image each frame
+    pub text: TextRenderer,
+    /// F3 overlay; games add their own lines with `hud.line`
+    pub hud: DebugHud,
+    /// World-space debug lines, drawn over the scene after the game renders
+    pub debug_draw: DebugDraw,
+    /// CPU and GPU frame timings; games time their own work with `cpu_begin` and `gpu_begin`
+    pub profiler: Profiler,
+    /// Screenshot taken at the end of this frame: where to save it and its size multiple
+    pending_screenshot: Option<(PathBuf, u32)>,
+    frame_sequence: Option<FrameSequence>,
+    image_writer: ImageWriter,
 }
 
 impl Engine {
@@ -22,30 +71,42 @@ impl Engine {
         let mut glfw = glfw::init(|err, desc| {
             eprintln!("GLFW Error {:?}: {}", err, desc);
         }).expect("Failed to initialize GLFW");
-        glfw.window_hint(glfw::WindowHint::ContextVersion(3, 3));
         glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
         #[cfg(target_os = "macos")] {
             glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
         }
-        let (mut window, events) = glfw
-            .create_window(width, height, title, glfw::WindowMode::Windowed)
+        // Prefer GL 4.3 for multi-draw indirect terrain rendering, falling back to 3.3 core
+        let (mut window, events) = GL_CONTEXT_VERSIONS.iter()
+            .find_map(|&(major, minor)| {
+                glfw.window_hint(glfw::WindowHint::ContextVersion(major, minor));
+                glfw.create_window(width, height, title, glfw::WindowMode::Windowed)
+            })
             .expect("Failed to create GLFW window.");
         window.set_key_polling(true);
         window.set_cursor_pos_polling(true);
         window.set_mouse_button_polling(true);
+        window.set_scroll_polling(true);
+        window.set_char_polling(true);
         window.set_framebuffer_size_polling(true);
         window.make_current();
-        window.set_cursor_mode(glfw::CursorMode::Disabled);
 
         gl::load_with(|s| window.get_proc_address(s) as *const _);
         let (fb_w, fb_h) = window.get_framebuffer_size();
-        unsafe {
+        let (scene_target, post, text, debug_draw) = unsafe {
             gl::Enable(gl::DEPTH_TEST);
             gl::Enable(gl::CULL_FACE);
             gl::CullFace(gl::BACK);
             gl::FrontFace(gl::CCW);
             gl::Viewport(0, 0, fb_w, fb_h);
-        }
+            (
+                Framebuffer::new(FramebufferDesc::hdr_scene(), fb_w, fb_h).expect("Failed to create scene framebuffer"),
+                PostProcessor::new(fb_w, fb_h).expect("Failed to create post-processing chain"),
+                TextRenderer::new().expect("Failed to create text renderer"),
+                DebugDraw::new().expect("Failed to create debug line renderer"),
+            )
+        };
+
+        let gamepads = Gamepads::new(&mut glfw);
 
         Engine {
             glfw,
@@ -56,59 +117,121 @@ impl Engine {
             time: 0.0,
             frame: 0,
             input: InputState::default(),
+            gamepads,
+            recorder: None,
+            replay: None,
+            cursor_captured: false,
+            actions: ActionMap::load_or_default(Path::new(INPUT_CONFIG_PATH)),
+            framebuffer_size: (fb_w, fb_h),
+            scene_target,
+            post,
+            text,
+            hud: DebugHud::new(),
+            debug_draw,
+            profiler: Profiler::new(),
+            pending_screenshot: None,
+            frame_sequence: None,
+            image_writer: ImageWriter::new(),
         }
     }
 
     pub fn run<G: Game>(&mut self, game: &mut G) {
         game.on_start(self);
         let mut last_frame = Instant::now();
-        let mut first_mouse = true;
-        let mut last_x = 0.0f64;
-        let mut last_y = 0.0f64;
 
     while !self.window.should_close() && !self.should_close {
             let now = Instant::now();
-            let dt = (now - last_frame).as_secs_f32();
+            let measured_dt = (now - last_frame).as_secs_f32();
             last_frame = now;
-            self.time += dt;
-            self.frame += 1;
 
+            unsafe { self.profiler.begin_frame(self.frame); }
+            game.begin_frame(self);
             self.update_input_begin();
-            self.process_input(dt);
-            self.poll_events(&mut first_mouse, &mut last_x, &mut last_y);
+            self.restore_replay_start(game);
+            self.poll_events();
+            let dt = self.gather_input(measured_dt);
+            self.record_input(game, dt);
+            self.time += dt;
+            self.frame += 1;
+            self.actions.update(&self.input);
+            self.process_input();
+            self.hud.record_frame(dt);
+            self.add_hud_lines();
 
             game.update(self, dt);
 
             unsafe {
-                gl::ClearColor(CLEAR_COLOR.0, CLEAR_COLOR.1, CLEAR_COLOR.2, CLEAR_COLOR.3);
-                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                self.post.poll_shaders();
+                self.text.poll_shader();
+                self.debug_draw.poll_shader();
+            }
+
+            let screenshot = self.pending_screenshot.take();
+            if let Some((path, scale)) = &screenshot {
+                if *scale > 1 { self.capture_tiled(game, path.clone(), *scale); }
             }
+            self.render_scene(game, true);
+            unsafe {
+                let pass = self.profiler.gpu_begin("overlay");
+                if self.hud.visible { self.profiler.render_graph(&mut self.text, self.framebuffer_size); }
+                self.hud.render(&mut self.text);
+                self.text.flush(self.framebuffer_size);
+                self.profiler.gpu_end(pass);
 
-            game.render(self);
+                if let Some((path, 1)) = screenshot {
+                    let image = self.capture_window();
+                    self.image_writer.save(path, image);
+                }
+                if self.frame_sequence.is_some() {
+                    let image = self.capture_window();
+                    if let Some(sequence) = &mut self.frame_sequence {
+                        self.image_writer.save(sequence.next_path(), image);
+                    }
+                }
+            }
             self.window.swap_buffers();
         }
 
         game.on_shutdown(self);
+        self.stop_recording();
+        self.stop_frame_sequence();
+        if self.profiler.is_capturing() { self.finish_profile_capture(); }
     }
 
-    fn poll_events(&mut self, first_mouse: &mut bool, last_x: &mut f64, last_y: &mut f64) {
+    fn poll_events(&mut self) {
+        // While replaying, recorded events stand in for the user's
+        let live = self.replay.is_none();
         self.glfw.poll_events();
         for (_, event) in glfw::flush_messages(&self.events) {
             match event {
-                WindowEvent::CursorPos(x, y) => {
-                    if *first_mouse { *last_x = x; *last_y = y; *first_mouse = false; }
-                    let dx = (x - *last_x) as f32;
-                    let dy = (y - *last_y) as f32;
-                    *last_x = x; *last_y = y;
-                    self.camera.process_mouse(dx, dy);
+                WindowEvent::CursorPos(x, y) if live => {
+                    self.input.cursor_event(x, y);
                 }
                 WindowEvent::FramebufferSize(w, h) => unsafe {
-                    gl::Viewport(0, 0, w, h);
-                    if h > 0 { self.camera.aspect = w as f32 / h as f32; }
+                    // Minimized windows report 0x0; keep the old targets until restored
+                    if w > 0 && h > 0 {
+                        self.framebuffer_size = (w, h);
+                        self.camera.aspect = w as f32 / h as f32;
+                        if let Err(e) = self.scene_target.resize(w, h) {
+                            eprintln!("Failed to resize scene framebuffer: {}", e);
+                        }
+                        if let Err(e) = self.post.resize(w, h) {
+                            eprintln!("Failed to resize post-processing targets: {}", e);
+                        }
+                    }
                 },
-                WindowEvent::Key(key, _, action, _) => {
+                WindowEvent::Key(key, _, action, _) if live => {
                     self.input.key_event(key, action);
                 }
+                WindowEvent::MouseButton(button, action, _) if live => {
+                    self.input.mouse_button_event(button, action);
+                }
+                WindowEvent::Scroll(x, y) if live => {
+                    self.input.scroll_event(x, y);
+                }
+                WindowEvent::Char(c) if live => {
+                    self.input.char_event(c);
+                }
                 _ => {}
             }
         }
@@ -116,14 +239,255 @@ impl Engine {
 
     fn update_input_begin(&mut self) { self.input.begin_frame(); }
 
-    fn process_input(&mut self, dt: f32) {
-        let cam_speed_scale = if self.input.is_key_down(Key::LeftShift) { CAMERA_SPRINT_MULTIPLIER } else { 1.0 };
-        if self.input.is_key_down(Key::W) { self.camera.process_keyboard(crate::engine::camera::CameraMove::Forward, dt * cam_speed_scale); }
-        if self.input.is_key_down(Key::S) { self.camera.process_keyboard(crate::engine::camera::CameraMove::Backward, dt * cam_speed_scale); }
-        if self.input.is_key_down(Key::A) { self.camera.process_keyboard(crate::engine::camera::CameraMove::Left, dt * cam_speed_scale); }
-        if self.input.is_key_down(Key::D) { self.camera.process_keyboard(crate::engine::camera::CameraMove::Right, dt * cam_speed_scale); }
-        if self.input.is_key_down(Key::Space) { self.camera.process_keyboard(crate::engine::camera::CameraMove::Up, dt * cam_speed_scale); }
-        if self.input.is_key_down(Key::LeftControl) { self.camera.process_keyboard(crate::engine::camera::CameraMove::Down, dt * cam_speed_scale); }
-        if self.input.was_key_pressed(Key::Escape) { self.window.set_should_close(true); }
+    /// Applies this frame's gamepad or replayed input and returns the `dt` to simulate with.
+    fn gather_input(&mut self, measured_dt: f32) -> f32 {
+        if let Some(replay) = &mut self.replay {
+            if let Some(frame) = replay.next_frame().cloned() {
+                for event in frame.events { self.input.apply(event); }
+                return frame.dt;
+            }
+            println!("Replay finished");
+            if replay.quit_when_done { self.should_close = true; }
+            self.replay = None;
+        }
+        self.gamepads.poll(&self.glfw, &mut self.input);
+        // Image sequences play back at a fixed rate, however long each frame takes to capture
+        if self.frame_sequence.is_some() { 1.0 / screenshots::SEQUENCE_FPS } else { measured_dt }
+    }
+
+    fn record_input<G: Game>(&mut self, game: &mut G, dt: f32) {
+        let Some(mut recorder) = self.recorder.take() else { return };
+        let mut result = Ok(());
+        if !recorder.has_started() {
+            // Saved as the first frame is recorded, when the game is at hand
+            let mut start = ReplayStart {
+                camera_position: self.camera.position,
+                yaw: self.camera.yaw,
+                pitch: self.camera.pitch,
+                time: self.time,
+                cursor_captured: self.cursor_captured,
+                values: Vec::new(),
+            };
+            game.save_replay_state(self, &mut start);
+            result = recorder.start(&start);
+        }
+        match result.and_then(|_| recorder.record(dt, self.input.frame_events())) {
+            Ok(()) => self.recorder = Some(recorder),
+            Err(e) => eprintln!("{}; recording stopped", e),
+        }
+    }
+
+    /// Puts the engine and game back in the state the replay was recorded from, once it starts.
+    fn restore_replay_start<G: Game>(&mut self, game: &mut G) {
+        let Some(start) = self.replay.as_mut().and_then(|r| r.take_start()) else { return };
+        self.camera.position = start.camera_position;
+        self.camera.yaw = start.yaw;
+        self.camera.pitch = start.pitch;
+        self.time = start.time;
+        self.set_cursor_captured(start.cursor_captured);
+        game.restore_replay_state(self, &start);
+    }
+
+    /// Starts writing every frame's input and `dt` to `path`, replacing any recording in progress.
+    pub fn start_recording(&mut self, path: &Path) -> Result<(), String> {
+        self.stop_recording();
+        self.recorder = Some(InputRecorder::create(path, self.input.held_state_events())?);
+        println!("Recording input to {}", path.display());
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            match recorder.finish() {
+                Ok(frames) => println!("Recorded {} frames", frames),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    }
+
+    pub fn is_recording(&self) -> bool { self.recorder.is_some() }
+
+    /// Replays a recording from the next frame on: the camera, time and game state are reset
+    /// to where the recording started, live input is ignored and each frame runs with its
+    /// recorded `dt`, so the camera and world follow the same path.
+    pub fn start_replay(&mut self, path: &Path, quit_when_done: bool) -> Result<(), String> {
+        let mut replay = InputReplay::load(path)?;
+        replay.quit_when_done = quit_when_done;
+        println!("Replaying {} frames ({:.1}s) from {}", replay.frame_count(), replay.duration(), path.display());
+        self.replay = Some(replay);
+        Ok(())
+    }
+
+    pub fn is_replaying(&self) -> bool { self.replay.is_some() }
+
+    /// Draws the game's scene into the window through the post-processing chain, without the overlay.
+    fn render_scene<G: Game>(&mut self, game: &mut G, debug_lines: bool) {
+        unsafe {
+            self.scene_target.bind();
+            gl::ClearColor(CLEAR_COLOR.0, CLEAR_COLOR.1, CLEAR_COLOR.2, CLEAR_COLOR.3);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        game.render(self);
+        unsafe {
+            if debug_lines {
+                let pass = self.profiler.gpu_begin("debug lines");
+                if self.debug_draw.settings.chunk_borders {
+                    self.debug_draw.chunk_borders(self.camera.position);
+                }
+                self.debug_draw.flush(self.camera.projection_matrix() * self.camera.view_matrix());
+                self.profiler.gpu_end(pass);
+            }
+
+            let pass = self.profiler.gpu_begin("post");
+            self.post.run(&self.scene_target, self.framebuffer_size);
+            self.profiler.gpu_end(pass);
+        }
+    }
+
+    /// Reads what has been drawn to the window so far this frame.
+    pub unsafe fn capture_window(&self) -> image::RgbaImage {
+        screenshot::read_framebuffer(0, self.framebuffer_size.0, self.framebuffer_size.1)
+    }
+
+    /// Reads the offscreen scene as the game rendered it, before post-processing, clamped to 0..1.
+    #[allow(dead_code)]
+    pub unsafe fn capture_scene(&self) -> image::RgbaImage {
+        screenshot::read_framebuffer(self.scene_target.id, self.scene_target.width(), self.scene_target.height())
+    }
+
+    /// Saves a screenshot of this frame to `path` once it has been drawn. A `scale` above 1
+    /// renders the scene again in `scale` x `scale` tiles for an image that many times larger,
+    /// without the debug overlay and lines.
+    pub fn request_screenshot(&mut self, path: PathBuf, scale: u32) {
+        self.pending_screenshot = Some((path, scale.max(1)));
+    }
+
+    /// Renders the scene in tiles through a zoomed projection and stitches them into one image.
+    fn capture_tiled<G: Game>(&mut self, game: &mut G, path: PathBuf, scale: u32) {
+        let (width, height) = (self.framebuffer_size.0 as u32, self.framebuffer_size.1 as u32);
+        let mut image = image::RgbaImage::new(width * scale, height * scale);
+        // Screen-space effects would show seams: bloom and FXAA can't see across tile edges,
+        // and the vignette would darken every tile's corners
+        let effects = [PostEffect::Bloom, PostEffect::Fxaa, PostEffect::Vignette];
+        let enabled = effects.map(|effect| self.post.settings.is_enabled(effect));
+        for effect in effects { self.post.settings.set_enabled(effect, false); }
+        for y in 0..scale {
+            for x in 0..scale {
+                self.camera.projection_tile = Some(ProjectionTile { columns: scale, rows: scale, x, y });
+                self.render_scene(game, false);
+                let tile = unsafe { self.capture_window() };
+                image::imageops::replace(&mut image, &tile, (x * width) as i64, (y * height) as i64);
+            }
+        }
+        self.camera.projection_tile = None;
+        for (&effect, &enabled) in effects.iter().zip(&enabled) { self.post.settings.set_enabled(effect, enabled); }
+        self.image_writer.save(path, image);
+    }
+
+    /// Saves every frame from the next one on as `dir/frame_NNNNNN.png`, simulating each
+    /// with a fixed `dt` so the sequence plays back at `SEQUENCE_FPS`.
+    pub fn start_frame_sequence(&mut self, dir: PathBuf) {
+        self.stop_frame_sequence();
+        println!("Recording frames to {}", dir.display());
+        self.frame_sequence = Some(FrameSequence::new(dir));
+    }
+
+    pub fn stop_frame_sequence(&mut self) {
+        if let Some(sequence) = self.frame_sequence.take() {
+            println!("Recorded {} frames to {}", sequence.frames, sequence.dir.display());
+        }
+    }
+
+    pub fn is_recording_frames(&self) -> bool { self.frame_sequence.is_some() }
+
+    fn finish_profile_capture(&mut self) {
+        match self.profiler.finish_capture(Path::new(PROFILE_TRACE_PATH)) {
+            Ok(frames) => println!("Wrote {} profiled frames to {}", frames, PROFILE_TRACE_PATH),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    /// Handles engine-level actions (post effect toggles, debug views, recording). Camera movement and quitting are up to the game.
+    fn process_input(&mut self) {
+        // A replay ends with the key that stopped its recording; don't let it start another
+        if self.actions.was_pressed(actions::TOGGLE_RECORDING) && self.replay.is_none() {
+            if self.is_recording() {
+                self.stop_recording();
+            } else if let Err(e) = self.start_recording(Path::new(RECORDING_PATH)) {
+                eprintln!("{}", e);
+            }
+        }
+        if self.actions.was_pressed(actions::TOGGLE_DEBUG_HUD) { self.hud.visible = !self.hud.visible; }
+        let dir = Path::new(screenshots::DIR);
+        if self.actions.was_pressed(actions::SCREENSHOT) {
+            let path = screenshot::next_free_path(dir, "screenshot", ".png");
+            println!("Saving screenshot to {}", path.display());
+            self.request_screenshot(path, 1);
+        }
+        if self.actions.was_pressed(actions::TILED_SCREENSHOT) {
+            let path = screenshot::next_free_path(dir, "screenshot", ".png");
+            println!("Saving {}x screenshot to {}", screenshots::TILED_SCALE, path.display());
+            self.request_screenshot(path, screenshots::TILED_SCALE);
+        }
+        if self.actions.was_pressed(actions::TOGGLE_FRAME_SEQUENCE) {
+            if self.is_recording_frames() {
+                self.stop_frame_sequence();
+            } else {
+                self.start_frame_sequence(screenshot::next_free_path(dir, "sequence", ""));
+            }
+        }
+        if self.actions.was_pressed(actions::TOGGLE_PROFILE_CAPTURE) {
+            if self.profiler.is_capturing() {
+                self.finish_profile_capture();
+            } else {
+                self.profiler.start_capture();
+                println!("Capturing a profile");
+            }
+        }
+        let debug = &mut self.debug_draw.settings;
+        if self.actions.was_pressed(actions::TOGGLE_CHUNK_BORDERS) { debug.chunk_borders = !debug.chunk_borders; }
+        if self.actions.was_pressed(actions::TOGGLE_CHUNK_STATES) { debug.chunk_states = !debug.chunk_states; }
+        if self.actions.was_pressed(actions::FREEZE_FRUSTUM) {
+            debug.frozen_frustum = match debug.frozen_frustum {
+                Some(_) => None,
+                None => Some(self.camera.frustum()),
+            };
+        }
+        for (&action, &effect) in POST_TOGGLE_ACTIONS.iter().zip(PostEffect::ALL.iter()) {
+            if self.actions.was_pressed(action) {
+                let enabled = self.post.settings.toggle(effect);
+                println!("Post effect {}: {}", effect.name(), if enabled { "on" } else { "off" });
+            }
+        }
+    }
+
+    /// Engine lines at the top of the debug HUD: frame timing and where the camera is.
+    fn add_hud_lines(&mut self) {
+        if !self.hud.visible { return; }
+        let (average, worst) = self.hud.frame_time_stats();
+        let fps = if average > 0.0 { 1.0 / average } else { 0.0 };
+        self.hud.line(format!("{:.0} fps  {:.2} ms (worst {:.2} ms)", fps, average * 1000.0, worst * 1000.0));
+        let p = self.camera.position;
+        self.hud.line(format!("XYZ: {:.2} / {:.2} / {:.2}", p.x, p.y, p.z));
+        let chunk = (p / CHUNK_SIZE as f32).floor();
+        let block = p.floor();
+        self.hud.line(format!("Chunk: {} {} {}  Block: {} {} {}", chunk.x, chunk.y, chunk.z, block.x, block.y, block.z));
+    }
+
+    /// Hides the cursor and locks it to the window for mouse look, or releases it (e.g. for a menu).
+    pub fn set_cursor_captured(&mut self, captured: bool) {
+        if captured == self.cursor_captured { return; }
+        self.cursor_captured = captured;
+        self.window.set_cursor_mode(if captured { glfw::CursorMode::Disabled } else { glfw::CursorMode::Normal });
+        // The cursor jumps when the mode changes; don't turn that into a look
+        self.input.reset_cursor();
+    }
+
+    pub fn is_cursor_captured(&self) -> bool { self.cursor_captured }
+
+    /// This frame's movement intent for a `CameraController`.
+    pub fn control_input(&self, dt: f32) -> ControlInput {
+        ControlInput::from_actions(&self.actions, &self.input, self.cursor_captured, dt)
     }
 }
@@ -0,0 +1,156 @@
+use std::mem;
+use std::ptr;
+use glam::{Mat4, Vec3, Vec4};
+use crate::engine::camera::Frustum;
+use crate::engine::chunk::CHUNK_SIZE;
+use crate::engine::shader_loader::ReloadableShader;
+use crate::engine::shader_sources::{DEBUG_LINE_VERT, DEBUG_LINE_FRAG};
+
+/// Floats per vertex: position(3) + color(4).
+const LINE_VERTEX_FLOATS: usize = 7;
+
+pub const GREEN: Vec4 = Vec4::new(0.2, 1.0, 0.2, 1.0);
+pub const YELLOW: Vec4 = Vec4::new(1.0, 0.9, 0.1, 1.0);
+pub const RED: Vec4 = Vec4::new(1.0, 0.2, 0.2, 1.0);
+pub const CYAN: Vec4 = Vec4::new(0.2, 0.9, 1.0, 1.0);
+
+/// The 12 edges of a box, as index pairs into the corner order of `Frustum::corners`
+/// (four near corners, then the four far ones).
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1), (1, 2), (2, 3), (3, 0),
+    (4, 5), (5, 6), (6, 7), (7, 4),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+];
+
+/// Which built-in debug visualizations are on.
+#[derive(Debug, Default)]
+pub struct DebugDrawSettings {
+    /// Outline the chunk the camera is in, with a grid on its walls
+    pub chunk_borders: bool,
+    /// Box every loaded chunk, colored by state (meshed, dirty, pending)
+    pub chunk_states: bool,
+    /// A frustum captured from the camera, drawn where it was frozen
+    pub frozen_frustum: Option<Frustum>,
+}
+
+/// Immediate-mode world-space lines: queue lines, boxes, frustums and rays any time during
+/// the frame, and `flush` draws them in one batch (depth tested) after the scene.
+pub struct DebugDraw {
+    pub settings: DebugDrawSettings,
+    shader: ReloadableShader,
+    vao: u32,
+    vbo: u32,
+    /// Capacity of `vbo` in floats
+    vbo_capacity: usize,
+    vertices: Vec<f32>,
+}
+
+impl DebugDraw {
+    pub unsafe fn new() -> Result<Self, String> {
+        let shader = ReloadableShader::load(DEBUG_LINE_VERT, DEBUG_LINE_FRAG, &[])?;
+
+        let (mut vao, mut vbo) = (0, 0);
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        let stride = (LINE_VERTEX_FLOATS * mem::size_of::<f32>()) as i32;
+        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
+        gl::EnableVertexAttribArray(0);
+        gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, stride, (3 * mem::size_of::<f32>()) as *const _);
+        gl::EnableVertexAttribArray(1);
+        gl::BindVertexArray(0);
+
+        Ok(Self { settings: DebugDrawSettings::default(), shader, vao, vbo, vbo_capacity: 0, vertices: Vec::new() })
+    }
+
+    pub fn line(&mut self, a: Vec3, b: Vec3, color: Vec4) {
+        for p in [a, b] {
+            self.vertices.extend_from_slice(&[p.x, p.y, p.z, color.x, color.y, color.z, color.w]);
+        }
+    }
+
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, color: Vec4) {
+        let corners = [
+            Vec3::new(min.x, min.y, min.z), Vec3::new(max.x, min.y, min.z), Vec3::new(max.x, max.y, min.z), Vec3::new(min.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z), Vec3::new(max.x, min.y, max.z), Vec3::new(max.x, max.y, max.z), Vec3::new(min.x, max.y, max.z),
+        ];
+        self.box_edges(&corners, color);
+    }
+
+    pub fn frustum(&mut self, frustum: &Frustum, color: Vec4) {
+        self.box_edges(&frustum.corners(), color);
+    }
+
+    #[allow(dead_code)]
+    pub fn ray(&mut self, origin: Vec3, direction: Vec3, length: f32, color: Vec4) {
+        self.line(origin, origin + direction.normalize_or_zero() * length, color);
+    }
+
+    fn box_edges(&mut self, corners: &[Vec3; 8], color: Vec4) {
+        for &(a, b) in &BOX_EDGES {
+            self.line(corners[a], corners[b], color);
+        }
+    }
+
+    /// Outlines the chunk containing `position`, with a grid every quarter chunk on its walls.
+    pub fn chunk_borders(&mut self, position: Vec3) {
+        let size = CHUNK_SIZE as f32;
+        let min = (position / size).floor() * size;
+        let max = min + Vec3::splat(size);
+        self.aabb(min, max, YELLOW);
+        let step = size / 4.0;
+        let mut t = step;
+        while t < size {
+            // Rings around the chunk at each height, and verticals along each wall
+            let y = min.y + t;
+            let corners = [Vec3::new(min.x, y, min.z), Vec3::new(max.x, y, min.z), Vec3::new(max.x, y, max.z), Vec3::new(min.x, y, max.z)];
+            for i in 0..4 { self.line(corners[i], corners[(i + 1) % 4], CYAN); }
+            for (x, z) in [(min.x + t, min.z), (min.x + t, max.z), (min.x, min.z + t), (max.x, min.z + t)] {
+                self.line(Vec3::new(x, min.y, z), Vec3::new(x, max.y, z), CYAN);
+            }
+            t += step;
+        }
+    }
+
+    /// Picks up shader edits from disk (dev builds only).
+    pub unsafe fn poll_shader(&mut self) {
+        self.shader.poll();
+    }
+
+    /// Draws everything queued this frame into the bound framebuffer and clears the queue.
+    pub unsafe fn flush(&mut self, view_proj: Mat4) {
+        if let Some(frustum) = self.settings.frozen_frustum {
+            self.frustum(&frustum, RED);
+        }
+        if self.vertices.is_empty() { return; }
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+        let bytes = (self.vertices.len() * mem::size_of::<f32>()) as isize;
+        if self.vertices.len() > self.vbo_capacity {
+            gl::BufferData(gl::ARRAY_BUFFER, bytes, self.vertices.as_ptr() as *const _, gl::STREAM_DRAW);
+            self.vbo_capacity = self.vertices.len();
+        } else {
+            // Orphan the old storage so we don't wait on last frame's draw
+            gl::BufferData(gl::ARRAY_BUFFER, (self.vbo_capacity * mem::size_of::<f32>()) as isize, ptr::null(), gl::STREAM_DRAW);
+            gl::BufferSubData(gl::ARRAY_BUFFER, 0, bytes, self.vertices.as_ptr() as *const _);
+        }
+
+        self.shader.use_program();
+        self.shader.set_mat4("uViewProj", &view_proj);
+        gl::BindVertexArray(self.vao);
+        gl::DrawArrays(gl::LINES, 0, (self.vertices.len() / LINE_VERTEX_FLOATS) as i32);
+        gl::BindVertexArray(0);
+
+        self.vertices.clear();
+    }
+}
+
+impl Drop for DebugDraw {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+use glam::Vec4;
+use crate::engine::constants::debug_hud;
+use crate::engine::text::TextRenderer;
+
+const TEXT_COLOR: Vec4 = Vec4::new(1.0, 1.0, 1.0, 1.0);
+const BACKGROUND_COLOR: Vec4 = Vec4::new(0.0, 0.0, 0.0, 0.5);
+
+/// F3-style overlay. Immediate mode: while it's visible, anyone can add lines during the
+/// frame with `line`; they are drawn in the top left corner and forgotten after rendering.
+pub struct DebugHud {
+    pub visible: bool,
+    lines: Vec<String>,
+    /// Recent frame times in seconds, newest last
+    frame_times: VecDeque<f32>,
+}
+
+impl Default for DebugHud {
+    fn default() -> Self { Self::new() }
+}
+
+impl DebugHud {
+    pub fn new() -> Self {
+        Self { visible: false, lines: Vec::new(), frame_times: VecDeque::with_capacity(debug_hud::FRAME_HISTORY) }
+    }
+
+    pub fn record_frame(&mut self, dt: f32) {
+        if self.frame_times.len() == debug_hud::FRAME_HISTORY { self.frame_times.pop_front(); }
+        self.frame_times.push_back(dt);
+    }
+
+    /// Average frame time and the worst one over the recent history, in seconds.
+    pub fn frame_time_stats(&self) -> (f32, f32) {
+        if self.frame_times.is_empty() { return (0.0, 0.0); }
+        let sum: f32 = self.frame_times.iter().sum();
+        let worst = self.frame_times.iter().copied().fold(0.0, f32::max);
+        (sum / self.frame_times.len() as f32, worst)
+    }
+
+    /// Adds a line this frame. Build the text only when `visible` if it's expensive.
+    pub fn line(&mut self, text: impl Into<String>) {
+        if self.visible { self.lines.push(text.into()); }
+    }
+
+    /// Queues this frame's lines on `text` and clears them.
+    pub fn render(&mut self, text: &mut TextRenderer) {
+        if self.visible && !self.lines.is_empty() {
+            let margin = debug_hud::MARGIN;
+            let mut y = margin;
+            for line in &self.lines {
+                let size = text.measure(line);
+                // Per-line backgrounds keep text readable without covering more than needed
+                text.rect(margin - 2.0, y, size.x + 4.0, size.y, BACKGROUND_COLOR);
+                text.text(margin, y + text.scale, line, TEXT_COLOR);
+                y += size.y;
+            }
+        }
+        self.lines.clear();
+    }
+}
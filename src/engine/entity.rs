@@ -1,24 +1,29 @@
-use glam::{Mat4, Vec3};
+use std::rc::Rc;
+use glam::{Mat4, Vec3, Vec4};
 use crate::engine::mesh::Mesh;
 
 /// An entity with a mesh, position, rotation, and scale.
 /// This is a general-purpose game object that can be rendered.
+/// The mesh is shared so entities using the same model can be drawn in one instanced call.
 #[allow(dead_code)]
 pub struct Entity {
-    pub mesh: Mesh,
+    pub mesh: Rc<Mesh>,
     pub position: Vec3,
     pub rotation: Vec3,
     pub scale: Vec3,
+    /// Per-instance color multiplier (RGBA)
+    pub tint: Vec4,
 }
 
 #[allow(dead_code)]
 impl Entity {
-    pub fn new(mesh: Mesh) -> Self {
+    pub fn new(mesh: Rc<Mesh>) -> Self {
         Self {
             mesh,
             position: Vec3::ZERO,
             rotation: Vec3::ZERO,
             scale: Vec3::ONE,
+            tint: Vec4::ONE,
         }
     }
 
@@ -29,4 +34,23 @@ impl Entity {
             self.position,
         )
     }
+
+    /// World-space bounding box of the transformed mesh bounds.
+    pub fn world_aabb(&self) -> (Vec3, Vec3) {
+        let model = self.model_matrix();
+        let (lmin, lmax) = self.mesh.bounds();
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for i in 0..8 {
+            let corner = Vec3::new(
+                if i & 1 == 0 { lmin.x } else { lmax.x },
+                if i & 2 == 0 { lmin.y } else { lmax.y },
+                if i & 4 == 0 { lmin.z } else { lmax.z },
+            );
+            let p = model.transform_point3(corner);
+            min = min.min(p);
+            max = max.max(p);
+        }
+        (min, max)
+    }
 }
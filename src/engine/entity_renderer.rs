@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::mem;
+use gl::types::*;
+use crate::engine::camera::Camera;
+use crate::engine::entity::Entity;
+use crate::engine::mesh::Mesh;
+
+/// Per-instance data: model matrix(16) + tint(4) = 20 floats
+const FLOATS_PER_INSTANCE: usize = 20;
+/// First attribute location used for instance data (model matrix takes 4 slots, tint 1).
+/// Starts past the mesh vertex layout, which ends with the chunk offset (4) and layer (5),
+/// since the instance pointers stay set on the mesh's vertex array after drawing.
+const INSTANCE_ATTRIB_BASE: u32 = 6;
+
+/// Draws entities grouped by mesh using instanced rendering.
+/// Model matrices and tints for all visible entities are streamed into a single instance buffer each frame.
+pub struct EntityRenderer {
+    instance_vbo: u32,
+    capacity: usize,
+    instance_data: Vec<f32>,
+}
+
+impl Default for EntityRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntityRenderer {
+    pub fn new() -> Self {
+        let mut instance_vbo = 0;
+        unsafe { gl::GenBuffers(1, &mut instance_vbo); }
+        Self { instance_vbo, capacity: 0, instance_data: Vec::new() }
+    }
+
+    /// Frustum culls the entities, groups the survivors by mesh and issues one instanced draw per mesh.
+    /// The shader must already be bound with its view-projection uniforms set.
+    pub unsafe fn render(&mut self, entities: &[Entity], camera: &Camera) {
+        let frustum = camera.frustum();
+
+        // Group visible entities by mesh (keyed on VAO, which is unique per mesh)
+        let mut groups: HashMap<u32, (&Mesh, Vec<&Entity>)> = HashMap::new();
+        for entity in entities {
+            let (min, max) = entity.world_aabb();
+            if !frustum.contains_aabb(min, max) { continue; }
+            groups.entry(entity.mesh.vao)
+                .or_insert_with(|| (&*entity.mesh, Vec::new()))
+                .1
+                .push(entity);
+        }
+        if groups.is_empty() { return; }
+
+        // Pack all instances into one contiguous buffer, remembering each group's range
+        self.instance_data.clear();
+        let mut batches: Vec<(&Mesh, usize, i32)> = Vec::with_capacity(groups.len());
+        for (mesh, members) in groups.values() {
+            let first = self.instance_data.len() / FLOATS_PER_INSTANCE;
+            for entity in members {
+                self.instance_data.extend_from_slice(&entity.model_matrix().to_cols_array());
+                self.instance_data.extend_from_slice(&entity.tint.to_array());
+            }
+            batches.push((mesh, first, members.len() as i32));
+        }
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+        let bytes = mem::size_of_val(self.instance_data.as_slice());
+        if bytes > self.capacity {
+            // Grow geometrically so steady-state frames only need sub-data uploads
+            self.capacity = bytes.next_power_of_two();
+            gl::BufferData(gl::ARRAY_BUFFER, self.capacity as isize, std::ptr::null(), gl::STREAM_DRAW);
+        }
+        gl::BufferSubData(gl::ARRAY_BUFFER, 0, bytes as isize, self.instance_data.as_ptr() as *const _);
+
+        let stride = (FLOATS_PER_INSTANCE * mem::size_of::<f32>()) as GLsizei;
+        for (mesh, first, count) in batches {
+            gl::BindVertexArray(mesh.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+            let base = first * FLOATS_PER_INSTANCE * mem::size_of::<f32>();
+            // Model matrix: one vec4 column per attribute slot, then the tint
+            for i in 0..5u32 {
+                let loc = INSTANCE_ATTRIB_BASE + i;
+                let offset = base + i as usize * 4 * mem::size_of::<f32>();
+                gl::VertexAttribPointer(loc, 4, gl::FLOAT, gl::FALSE, stride, offset as *const _);
+                gl::EnableVertexAttribArray(loc);
+                gl::VertexAttribDivisor(loc, 1);
+            }
+            mesh.draw_instanced(count);
+        }
+        gl::BindVertexArray(0);
+    }
+}
+
+impl Drop for EntityRenderer {
+    fn drop(&mut self) { unsafe { gl::DeleteBuffers(1, &self.instance_vbo); } }
+}
@@ -0,0 +1,48 @@
+use glam::Vec3;
+use crate::engine::constants::{lod, FOG_START_FRACTION};
+use crate::engine::lod::ring_distance;
+
+/// Uniform block binding point for `FogUniform`.
+pub const FOG_UNIFORM_BINDING: u32 = 1;
+
+/// Distance fog shared by all lit shaders through the std140 `Fog` uniform block:
+/// ```glsl
+/// layout(std140) uniform Fog { vec4 uFogColor; vec4 uFogParams; };
+/// ```
+/// `uFogColor.rgb` is the color; `uFogParams` is (start, end, max factor, unused).
+#[derive(Debug, Clone)]
+pub struct FogUniform {
+    pub color: Vec3,
+    pub start: f32,
+    pub end: f32,
+    pub max_factor: f32,
+}
+
+impl Default for FogUniform {
+    /// The regular above-water haze, out to the default outermost LOD ring.
+    fn default() -> Self {
+        Self::haze(Vec3::new(0.6, 0.75, 0.95), ring_distance(lod::LEVELS, lod::RADIUS))
+    }
+}
+
+impl FogUniform {
+    /// Size of the std140 block in floats: two vec4s.
+    pub const STD140_FLOATS: usize = 8;
+
+    /// Above-water haze that turns opaque at `view_distance`, where the terrain ends.
+    pub fn haze(color: Vec3, view_distance: f32) -> Self {
+        Self { color, start: view_distance * FOG_START_FRACTION, end: view_distance, max_factor: 1.0 }
+    }
+
+    /// Dense blue-green fog used while the camera is inside water.
+    pub fn underwater() -> Self {
+        Self { color: Vec3::new(0.05, 0.22, 0.32), start: 0.0, end: 24.0, max_factor: 1.0 }
+    }
+
+    pub fn to_std140(&self) -> [f32; Self::STD140_FLOATS] {
+        [
+            self.color.x, self.color.y, self.color.z, 1.0,
+            self.start, self.end, self.max_factor, 0.0,
+        ]
+    }
+}
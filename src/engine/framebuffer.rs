@@ -0,0 +1,175 @@
+use std::ptr;
+
+/// Storage format of a framebuffer attachment texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttachmentFormat {
+    pub internal: u32,
+    pub format: u32,
+    pub ty: u32,
+}
+
+impl AttachmentFormat {
+    #[allow(dead_code)]
+    pub const RGBA8: Self = Self { internal: gl::RGBA8, format: gl::RGBA, ty: gl::UNSIGNED_BYTE };
+    /// Half-float color for HDR rendering
+    pub const RGBA16F: Self = Self { internal: gl::RGBA16F, format: gl::RGBA, ty: gl::HALF_FLOAT };
+    /// Same depth/stencil layout as the usual default framebuffer, so depth can be blitted between them
+    pub const DEPTH24_STENCIL8: Self = Self { internal: gl::DEPTH24_STENCIL8, format: gl::DEPTH_STENCIL, ty: gl::UNSIGNED_INT_24_8 };
+
+    fn is_depth_stencil(&self) -> bool { self.format == gl::DEPTH_STENCIL }
+}
+
+/// Attachments of a `Framebuffer`.
+#[derive(Debug, Clone)]
+pub struct FramebufferDesc {
+    pub color: Vec<AttachmentFormat>,
+    pub depth: Option<AttachmentFormat>,
+    /// Min/mag filter of the attachment textures when sampled
+    pub filter: u32,
+}
+
+impl FramebufferDesc {
+    /// A single HDR color target with depth, for rendering the 3D scene.
+    pub fn hdr_scene() -> Self {
+        Self { color: vec![AttachmentFormat::RGBA16F], depth: Some(AttachmentFormat::DEPTH24_STENCIL8), filter: gl::LINEAR }
+    }
+
+    /// A single color target without depth, for fullscreen passes.
+    pub fn color_only(format: AttachmentFormat) -> Self {
+        Self { color: vec![format], depth: None, filter: gl::LINEAR }
+    }
+}
+
+/// Off-screen render target with texture attachments that can be sampled afterwards.
+pub struct Framebuffer {
+    pub id: u32,
+    color: Vec<u32>,
+    depth: u32,
+    width: i32,
+    height: i32,
+    desc: FramebufferDesc,
+}
+
+impl Framebuffer {
+    pub unsafe fn new(desc: FramebufferDesc, width: i32, height: i32) -> Result<Self, String> {
+        let mut id = 0;
+        gl::GenFramebuffers(1, &mut id);
+        let mut fb = Self { id, color: Vec::new(), depth: 0, width: 0, height: 0, desc };
+        fb.resize(width, height)?;
+        Ok(fb)
+    }
+
+    pub fn width(&self) -> i32 { self.width }
+    pub fn height(&self) -> i32 { self.height }
+    #[allow(dead_code)]
+    pub fn size(&self) -> (i32, i32) { (self.width, self.height) }
+
+    /// Texture of a color attachment.
+    pub fn color_texture(&self, index: usize) -> u32 { self.color[index] }
+
+    /// Texture of the depth attachment, or 0 if there is none.
+    #[allow(dead_code)]
+    pub fn depth_texture(&self) -> u32 { self.depth }
+
+    /// Recreates the attachments at a new size. Does nothing if the size is unchanged.
+    pub unsafe fn resize(&mut self, width: i32, height: i32) -> Result<(), String> {
+        let (width, height) = (width.max(1), height.max(1));
+        if (width, height) == (self.width, self.height) { return Ok(()); }
+        self.delete_attachments();
+        self.width = width;
+        self.height = height;
+
+        let mut previous = 0;
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        for (i, format) in self.desc.color.iter().enumerate() {
+            let texture = create_texture(*format, width, height, self.desc.filter);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0 + i as u32, gl::TEXTURE_2D, texture, 0);
+            self.color.push(texture);
+        }
+        let draw_buffers: Vec<u32> = (0..self.color.len() as u32).map(|i| gl::COLOR_ATTACHMENT0 + i).collect();
+        if draw_buffers.is_empty() {
+            gl::DrawBuffer(gl::NONE);
+        } else {
+            gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+        }
+        if let Some(format) = self.desc.depth {
+            self.depth = create_texture(format, width, height, gl::NEAREST);
+            let attachment = if format.is_depth_stencil() { gl::DEPTH_STENCIL_ATTACHMENT } else { gl::DEPTH_ATTACHMENT };
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, self.depth, 0);
+        }
+        let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format!("Framebuffer {}x{} incomplete (0x{:X})", width, height, status));
+        }
+        Ok(())
+    }
+
+    /// Binds for drawing and sets the viewport to cover it.
+    pub unsafe fn bind(&self) {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        gl::Viewport(0, 0, self.width, self.height);
+    }
+
+    /// Binds the window's default framebuffer with a `width` x `height` viewport.
+    pub unsafe fn bind_default(width: i32, height: i32) {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl::Viewport(0, 0, width, height);
+    }
+
+    /// Binds a color attachment to a texture unit for sampling.
+    pub unsafe fn bind_color(&self, index: usize, unit: u32) {
+        gl::ActiveTexture(gl::TEXTURE0 + unit);
+        gl::BindTexture(gl::TEXTURE_2D, self.color[index]);
+    }
+
+    /// Binds the depth attachment to a texture unit for sampling.
+    pub unsafe fn bind_depth(&self, unit: u32) {
+        gl::ActiveTexture(gl::TEXTURE0 + unit);
+        gl::BindTexture(gl::TEXTURE_2D, self.depth);
+    }
+
+    /// Copies the buffers in `mask` from framebuffer `source` (same size) into this one.
+    pub unsafe fn blit_from(&self, source: u32, mask: u32) {
+        let mut previous = 0;
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, source);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.id);
+        gl::BlitFramebuffer(0, 0, self.width, self.height, 0, 0, self.width, self.height, mask, gl::NEAREST);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+    }
+
+    unsafe fn delete_attachments(&mut self) {
+        if !self.color.is_empty() {
+            gl::DeleteTextures(self.color.len() as i32, self.color.as_ptr());
+            self.color.clear();
+        }
+        if self.depth != 0 {
+            gl::DeleteTextures(1, &self.depth);
+            self.depth = 0;
+        }
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.delete_attachments();
+            gl::DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
+
+unsafe fn create_texture(format: AttachmentFormat, width: i32, height: i32, filter: u32) -> u32 {
+    let mut texture = 0;
+    gl::GenTextures(1, &mut texture);
+    gl::BindTexture(gl::TEXTURE_2D, texture);
+    gl::TexImage2D(gl::TEXTURE_2D, 0, format.internal as i32, width, height, 0, format.format, format.ty, ptr::null());
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+    texture
+}
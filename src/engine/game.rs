@@ -1,8 +1,16 @@
 use crate::engine::core::Engine;
+use crate::engine::replay::ReplayStart;
 
 pub trait Game {
     fn on_start(&mut self, _engine: &mut Engine) {}
+    /// Called first thing every frame, before input is processed.
+    fn begin_frame(&mut self, _engine: &mut Engine) {}
     fn update(&mut self, _engine: &mut Engine, _dt: f32) {}
     fn render(&mut self, _engine: &mut Engine) {}
     fn on_shutdown(&mut self, _engine: &mut Engine) {}
+    /// Called when an input recording starts, to add the game state its replay has to start
+    /// from (anything input changes over time that the engine doesn't already save).
+    fn save_replay_state(&mut self, _engine: &Engine, _start: &mut ReplayStart) {}
+    /// Called before the first frame of a replay, after the engine restored its own state.
+    fn restore_replay_state(&mut self, _engine: &mut Engine, _start: &ReplayStart) {}
 }
@@ -0,0 +1,202 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use glam::Vec2;
+use glfw::{Action, GamepadAxis, GamepadButton, Glfw, JoystickEvent, JoystickId};
+use crate::engine::constants::gamepad;
+use crate::engine::input::InputState;
+
+const BUTTONS: [GamepadButton; 15] = [
+    GamepadButton::ButtonA, GamepadButton::ButtonB, GamepadButton::ButtonX, GamepadButton::ButtonY,
+    GamepadButton::ButtonLeftBumper, GamepadButton::ButtonRightBumper,
+    GamepadButton::ButtonBack, GamepadButton::ButtonStart, GamepadButton::ButtonGuide,
+    GamepadButton::ButtonLeftThumb, GamepadButton::ButtonRightThumb,
+    GamepadButton::ButtonDpadUp, GamepadButton::ButtonDpadRight, GamepadButton::ButtonDpadDown, GamepadButton::ButtonDpadLeft,
+];
+
+const JOYSTICKS: [JoystickId; 16] = [
+    JoystickId::Joystick1, JoystickId::Joystick2, JoystickId::Joystick3, JoystickId::Joystick4,
+    JoystickId::Joystick5, JoystickId::Joystick6, JoystickId::Joystick7, JoystickId::Joystick8,
+    JoystickId::Joystick9, JoystickId::Joystick10, JoystickId::Joystick11, JoystickId::Joystick12,
+    JoystickId::Joystick13, JoystickId::Joystick14, JoystickId::Joystick15, JoystickId::Joystick16,
+];
+
+/// How raw stick and trigger positions are shaped before they reach `InputState`.
+#[derive(Debug, Clone, Copy)]
+pub struct StickResponse {
+    /// Fraction of the range ignored around rest
+    pub deadzone: f32,
+    /// Exponent applied after the deadzone; above 1 gives finer control near the center
+    pub curve: f32,
+}
+
+impl StickResponse {
+    /// Remaps a magnitude in 0..1 so the deadzone reads as 0 and the curve starts from there.
+    pub fn apply(&self, magnitude: f32) -> f32 {
+        let live = ((magnitude - self.deadzone) / (1.0 - self.deadzone)).clamp(0.0, 1.0);
+        live.powf(self.curve)
+    }
+
+    /// Radial deadzone for a stick, so diagonals aren't clipped like a per-axis deadzone would.
+    pub fn apply_stick(&self, stick: Vec2) -> Vec2 {
+        let magnitude = stick.length();
+        if magnitude <= self.deadzone { return Vec2::ZERO; }
+        stick / magnitude * self.apply(magnitude.min(1.0))
+    }
+}
+
+/// Polls the first connected gamepad each frame and feeds its buttons and shaped
+/// axes into `InputState`, where actions pick them up like any other input.
+/// Controllers can be plugged in and out at any time; the first one found is used.
+pub struct Gamepads {
+    pub stick_response: StickResponse,
+    pub trigger_response: StickResponse,
+    active: Option<JoystickId>,
+    events: Receiver<(JoystickId, JoystickEvent)>,
+}
+
+impl Gamepads {
+    /// Loads extra SDL-style controller mappings if the mappings file exists, and starts
+    /// listening for hot-plug events.
+    pub fn new(glfw: &mut Glfw) -> Self {
+        let mappings = Path::new(gamepad::MAPPINGS_PATH);
+        if mappings.exists() {
+            match std::fs::read_to_string(mappings) {
+                Ok(text) if glfw.update_gamepad_mappings(&text) => {}
+                Ok(_) => eprintln!("Warning: some gamepad mappings in {} were rejected", mappings.display()),
+                Err(e) => eprintln!("Warning: failed to read {}: {}", mappings.display(), e),
+            }
+        }
+
+        let (sender, events) = mpsc::channel();
+        glfw.set_joystick_callback(move |id, event| { let _ = sender.send((id, event)); });
+
+        let mut pads = Self {
+            stick_response: StickResponse { deadzone: gamepad::STICK_DEADZONE, curve: gamepad::STICK_CURVE },
+            trigger_response: StickResponse { deadzone: gamepad::TRIGGER_DEADZONE, curve: 1.0 },
+            active: None,
+            events,
+        };
+        pads.active = pads.find_gamepad(glfw);
+        if let Some(id) = pads.active { pads.announce(glfw, id, "connected"); }
+        pads
+    }
+
+    /// Name of the gamepad in use, if any.
+    #[allow(dead_code)]
+    pub fn active_name(&self, glfw: &Glfw) -> Option<String> {
+        self.active.and_then(|id| glfw.get_joystick(id).get_gamepad_name())
+    }
+
+    /// Handles hot-plug events and copies the active gamepad's state into `input`.
+    /// Call once per frame after GLFW events are polled.
+    pub fn poll(&mut self, glfw: &Glfw, input: &mut InputState) {
+        let mut rescan = false;
+        for (id, event) in self.events.try_iter() {
+            match event {
+                JoystickEvent::Connected => rescan |= self.active.is_none(),
+                JoystickEvent::Disconnected if self.active == Some(id) => {
+                    self.announce(glfw, id, "disconnected");
+                    self.active = None;
+                    Self::release_all(input);
+                    rescan = true;
+                }
+                JoystickEvent::Disconnected => {}
+            }
+        }
+        if rescan {
+            self.active = self.find_gamepad(glfw);
+            if let Some(id) = self.active { self.announce(glfw, id, "connected"); }
+        }
+
+        let Some(state) = self.active.and_then(|id| glfw.get_joystick(id).get_gamepad_state()) else { return };
+        for button in BUTTONS {
+            let action = state.get_button_state(button);
+            // Only forward changes so pressed/released edges work like keyboard events
+            if (action == Action::Press) != input.gamepad_button(button).down {
+                input.gamepad_button_event(button, action);
+            }
+        }
+
+        let left = self.stick_response.apply_stick(Vec2::new(
+            state.get_axis(GamepadAxis::AxisLeftX), state.get_axis(GamepadAxis::AxisLeftY)));
+        let right = self.stick_response.apply_stick(Vec2::new(
+            state.get_axis(GamepadAxis::AxisRightX), state.get_axis(GamepadAxis::AxisRightY)));
+        input.set_gamepad_axis(GamepadAxis::AxisLeftX, left.x);
+        input.set_gamepad_axis(GamepadAxis::AxisLeftY, left.y);
+        input.set_gamepad_axis(GamepadAxis::AxisRightX, right.x);
+        input.set_gamepad_axis(GamepadAxis::AxisRightY, right.y);
+        // GLFW reports triggers as -1 at rest to 1 fully pulled
+        for trigger in [GamepadAxis::AxisLeftTrigger, GamepadAxis::AxisRightTrigger] {
+            let pulled = (state.get_axis(trigger) + 1.0) * 0.5;
+            input.set_gamepad_axis(trigger, self.trigger_response.apply(pulled));
+        }
+    }
+
+    fn find_gamepad(&self, glfw: &Glfw) -> Option<JoystickId> {
+        JOYSTICKS.iter().copied().find(|&id| {
+            let joystick = glfw.get_joystick(id);
+            joystick.is_present() && joystick.is_gamepad()
+        })
+    }
+
+    fn announce(&self, glfw: &Glfw, id: JoystickId, what: &str) {
+        let name = glfw.get_joystick(id).get_gamepad_name().unwrap_or_else(|| format!("{:?}", id));
+        eprintln!("Gamepad {}: {}", what, name);
+    }
+
+    /// Releases everything so an unplugged controller doesn't leave inputs stuck.
+    fn release_all(input: &mut InputState) {
+        for button in BUTTONS {
+            if input.gamepad_button(button).down { input.gamepad_button_event(button, Action::Release); }
+        }
+        for axis in [GamepadAxis::AxisLeftX, GamepadAxis::AxisLeftY, GamepadAxis::AxisRightX,
+                     GamepadAxis::AxisRightY, GamepadAxis::AxisLeftTrigger, GamepadAxis::AxisRightTrigger] {
+            input.set_gamepad_axis(axis, 0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: StickResponse = StickResponse { deadzone: 0.2, curve: 2.0 };
+
+    #[test]
+    fn deadzone_reads_as_rest() {
+        for magnitude in [0.0, 0.1, 0.2] {
+            assert_eq!(RESPONSE.apply(magnitude), 0.0);
+        }
+        assert_eq!(RESPONSE.apply_stick(Vec2::new(0.1, -0.1)), Vec2::ZERO);
+        assert_eq!(RESPONSE.apply_stick(Vec2::new(0.0, 0.2)), Vec2::ZERO);
+    }
+
+    #[test]
+    fn range_starts_at_deadzone_edge_and_reaches_one() {
+        assert_eq!(RESPONSE.apply(0.2), 0.0);
+        assert!(RESPONSE.apply(0.21) < 0.01);
+        assert_eq!(RESPONSE.apply(1.0), 1.0);
+        assert_eq!(RESPONSE.apply(1.5), 1.0);
+        // Halfway through the live range, squared by the curve
+        assert!((RESPONSE.apply(0.6) - 0.25).abs() < 1e-6);
+        let linear = StickResponse { deadzone: 0.2, curve: 1.0 };
+        assert!((linear.apply(0.6) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stick_keeps_direction() {
+        let full = RESPONSE.apply_stick(Vec2::new(1.0, 0.0));
+        assert!((full - Vec2::X).length() < 1e-6);
+
+        // A diagonal with both axes inside a per-axis deadzone still registers
+        let diagonal = Vec2::new(0.18, 0.18);
+        let shaped = RESPONSE.apply_stick(diagonal);
+        assert!(shaped.x > 0.0 && (shaped.x - shaped.y).abs() < 1e-6);
+        assert!((shaped.normalize() - diagonal.normalize()).length() < 1e-6);
+
+        // Corners of a square gate are clamped to full deflection along the same angle
+        let corner = RESPONSE.apply_stick(Vec2::new(-1.0, 1.0));
+        assert!((corner.length() - 1.0).abs() < 1e-6);
+        assert!((corner.normalize() - Vec2::new(-1.0, 1.0).normalize()).length() < 1e-6);
+    }
+}
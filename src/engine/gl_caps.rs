@@ -0,0 +1,43 @@
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::sync::OnceLock;
+
+/// OpenGL version and extension support of the current context.
+pub struct GlCaps {
+    pub major: i32,
+    pub minor: i32,
+    extensions: HashSet<String>,
+}
+
+static CAPS: OnceLock<GlCaps> = OnceLock::new();
+
+impl GlCaps {
+    /// Returns the capabilities of the current context, querying them on first use.
+    /// Must be called on the thread that owns the GL context.
+    pub fn get() -> &'static GlCaps {
+        CAPS.get_or_init(|| unsafe { Self::query() })
+    }
+
+    unsafe fn query() -> GlCaps {
+        let (mut major, mut minor, mut count) = (0, 0, 0);
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        let mut extensions = HashSet::with_capacity(count as usize);
+        for i in 0..count as u32 {
+            let name = gl::GetStringi(gl::EXTENSIONS, i);
+            if !name.is_null() {
+                extensions.insert(CStr::from_ptr(name as *const _).to_string_lossy().into_owned());
+            }
+        }
+        GlCaps { major, minor, extensions }
+    }
+
+    pub fn supports_version(&self, major: i32, minor: i32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.contains(name)
+    }
+}
@@ -1,9 +1,39 @@
 use std::collections::HashMap;
-use glfw::{Action, Key};
+use std::hash::Hash;
+use glam::Vec2;
+use glfw::{Action, GamepadAxis, GamepadButton, Key, MouseButton};
 
 #[derive(Default)]
 pub struct InputState {
     keys: HashMap<Key, KeyState>,
+    mouse_buttons: HashMap<MouseButton, KeyState>,
+    gamepad_buttons: HashMap<GamepadButton, KeyState>,
+    /// Latest gamepad axis positions, -1..1
+    gamepad_axes: HashMap<GamepadAxis, f32>,
+    /// Cursor position in window coordinates, `None` until the first cursor event
+    cursor: Option<Vec2>,
+    /// Cursor movement since the start of the frame
+    cursor_delta: Vec2,
+    /// Scroll wheel movement since the start of the frame
+    scroll_delta: Vec2,
+    /// Characters typed since the start of the frame
+    text: String,
+    /// Every event applied since the start of the frame, in order, for recording
+    events: Vec<InputEvent>,
+}
+
+/// One change to the input state. Everything that reaches `InputState` goes through
+/// these, so a frame's events can be recorded and applied again to reproduce it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    Key(Key, Action),
+    MouseButton(MouseButton, Action),
+    Cursor(f64, f64),
+    Scroll(f64, f64),
+    Char(char),
+    GamepadButton(GamepadButton, Action),
+    /// Shaped gamepad axis position, -1..1
+    GamepadAxis(GamepadAxis, f32),
 }
 
 #[derive(Copy, Clone, Default)]
@@ -13,27 +43,117 @@ pub struct KeyState {
     pub released: bool,
 }
 
-impl InputState {
-    pub fn begin_frame(&mut self) {
-        for state in self.keys.values_mut() { state.pressed = false; state.released = false; }
-    }
-    pub fn key_event(&mut self, key: Key, action: Action) {
-        let entry = self.keys.entry(key).or_default();
+impl KeyState {
+    fn apply(&mut self, action: Action) {
         match action {
             Action::Press => {
-                if !entry.down { entry.pressed = true; }
-                entry.down = true;
+                if !self.down { self.pressed = true; }
+                self.down = true;
             }
             Action::Release => {
-                if entry.down { entry.released = true; }
-                entry.down = false;
+                if self.down { self.released = true; }
+                self.down = false;
             }
             Action::Repeat => {}
         }
     }
-    pub fn is_key_down(&self, key: Key) -> bool { self.keys.get(&key).map(|s| s.down).unwrap_or(false) }
-    pub fn was_key_pressed(&self, key: Key) -> bool { self.keys.get(&key).map(|s| s.pressed).unwrap_or(false) }
+}
+
+fn state_of<T: Eq + Hash>(map: &HashMap<T, KeyState>, button: T) -> KeyState {
+    map.get(&button).copied().unwrap_or_default()
+}
+
+impl InputState {
+    pub fn begin_frame(&mut self) {
+        let states = self.keys.values_mut()
+            .chain(self.mouse_buttons.values_mut())
+            .chain(self.gamepad_buttons.values_mut());
+        for state in states { state.pressed = false; state.released = false; }
+        self.cursor_delta = Vec2::ZERO;
+        self.scroll_delta = Vec2::ZERO;
+        self.text.clear();
+        self.events.clear();
+    }
+    pub fn key_event(&mut self, key: Key, action: Action) { self.apply(InputEvent::Key(key, action)); }
+    pub fn mouse_button_event(&mut self, button: MouseButton, action: Action) { self.apply(InputEvent::MouseButton(button, action)); }
+    pub fn cursor_event(&mut self, x: f64, y: f64) { self.apply(InputEvent::Cursor(x, y)); }
+    pub fn scroll_event(&mut self, x: f64, y: f64) { self.apply(InputEvent::Scroll(x, y)); }
+    pub fn char_event(&mut self, c: char) { self.apply(InputEvent::Char(c)); }
+    pub fn gamepad_button_event(&mut self, button: GamepadButton, action: Action) { self.apply(InputEvent::GamepadButton(button, action)); }
+    pub fn set_gamepad_axis(&mut self, axis: GamepadAxis, value: f32) {
+        // Gamepads are polled every frame; only changes are worth an event
+        if self.gamepad_axis(axis) != value { self.apply(InputEvent::GamepadAxis(axis, value)); }
+    }
+    /// Events that bring a fresh `InputState` to this one's held buttons, axes and cursor position,
+    /// so a recording started mid-session replays from the same input state.
+    pub fn held_state_events(&self) -> Vec<InputEvent> {
+        fn held<T: Copy + Ord>(map: &HashMap<T, KeyState>) -> Vec<T> {
+            let mut held: Vec<T> = map.iter().filter(|(_, s)| s.down).map(|(&b, _)| b).collect();
+            held.sort();
+            held
+        }
+        let mut events: Vec<InputEvent> = Vec::new();
+        events.extend(held(&self.keys).into_iter().map(|k| InputEvent::Key(k, Action::Press)));
+        events.extend(held(&self.mouse_buttons).into_iter().map(|b| InputEvent::MouseButton(b, Action::Press)));
+        events.extend(held(&self.gamepad_buttons).into_iter().map(|b| InputEvent::GamepadButton(b, Action::Press)));
+        let mut axes: Vec<(GamepadAxis, f32)> = self.gamepad_axes.iter().filter(|(_, &v)| v != 0.0).map(|(&a, &v)| (a, v)).collect();
+        axes.sort_by_key(|&(axis, _)| axis);
+        events.extend(axes.into_iter().map(|(axis, value)| InputEvent::GamepadAxis(axis, value)));
+        if let Some(cursor) = self.cursor { events.push(InputEvent::Cursor(cursor.x as f64, cursor.y as f64)); }
+        events
+    }
+
+    /// Forgets the cursor position so the next cursor event doesn't report a jump (e.g. after recapturing it).
+    pub fn reset_cursor(&mut self) { self.cursor = None; }
 
+    pub fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Key(key, action) => self.keys.entry(key).or_default().apply(action),
+            InputEvent::MouseButton(button, action) => self.mouse_buttons.entry(button).or_default().apply(action),
+            InputEvent::Cursor(x, y) => {
+                let position = Vec2::new(x as f32, y as f32);
+                // The first event only establishes where the cursor is
+                if let Some(last) = self.cursor { self.cursor_delta += position - last; }
+                self.cursor = Some(position);
+            }
+            InputEvent::Scroll(x, y) => self.scroll_delta += Vec2::new(x as f32, y as f32),
+            InputEvent::Char(c) => self.text.push(c),
+            InputEvent::GamepadButton(button, action) => self.gamepad_buttons.entry(button).or_default().apply(action),
+            InputEvent::GamepadAxis(axis, value) => { self.gamepad_axes.insert(axis, value); }
+        }
+        self.events.push(event);
+    }
+
+    /// Events applied since `begin_frame`.
+    pub fn frame_events(&self) -> &[InputEvent] { &self.events }
+
+    #[allow(dead_code)]
+    pub fn is_key_down(&self, key: Key) -> bool { self.key(key).down }
     #[allow(dead_code)]
-    pub fn was_key_released(&self, key: Key) -> bool { self.keys.get(&key).map(|s| s.released).unwrap_or(false) }
+    pub fn was_key_pressed(&self, key: Key) -> bool { self.key(key).pressed }
+    #[allow(dead_code)]
+    pub fn was_key_released(&self, key: Key) -> bool { self.key(key).released }
+
+    #[allow(dead_code)]
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool { self.mouse_button(button).down }
+    #[allow(dead_code)]
+    pub fn was_mouse_pressed(&self, button: MouseButton) -> bool { self.mouse_button(button).pressed }
+    #[allow(dead_code)]
+    pub fn was_mouse_released(&self, button: MouseButton) -> bool { self.mouse_button(button).released }
+
+    /// Cursor position in window coordinates (origin top left).
+    #[allow(dead_code)]
+    pub fn cursor_position(&self) -> Vec2 { self.cursor.unwrap_or(Vec2::ZERO) }
+    /// Cursor movement this frame, in pixels.
+    pub fn cursor_delta(&self) -> Vec2 { self.cursor_delta }
+    /// Scroll wheel movement this frame; `y` is the usual vertical wheel.
+    pub fn scroll_delta(&self) -> Vec2 { self.scroll_delta }
+    /// Text typed this frame, with the keyboard layout and modifiers applied.
+    #[allow(dead_code)]
+    pub fn text(&self) -> &str { &self.text }
+
+    pub fn key(&self, key: Key) -> KeyState { state_of(&self.keys, key) }
+    pub fn mouse_button(&self, button: MouseButton) -> KeyState { state_of(&self.mouse_buttons, button) }
+    pub fn gamepad_button(&self, button: GamepadButton) -> KeyState { state_of(&self.gamepad_buttons, button) }
+    pub fn gamepad_axis(&self, axis: GamepadAxis) -> f32 { self.gamepad_axes.get(&axis).copied().unwrap_or(0.0) }
 }
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+/// Minimal JSON document model, enough for reading asset manifests such as glTF.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(HashMap<String, Json>),
+}
+
+static NULL: Json = Json::Null;
+
+/// Deepest array/object nesting accepted, so hostile input can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser { bytes: text.as_bytes(), pos: 0, depth: 0 };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(format!("Trailing characters at byte {}", parser.pos));
+        }
+        Ok(value)
+    }
+
+    /// Looks up a key in an object. Returns `Json::Null` for missing keys or non-objects.
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(map) => map.get(key).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self { Json::Number(n) => Some(*n), _ => None }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_f64().filter(|n| *n >= 0.0).map(|n| n as usize)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self { Json::Bool(b) => Some(*b), _ => None }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Json::String(s) => Some(s), _ => None }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        match self { Json::Array(items) => items, _ => &[] }
+    }
+
+    /// Reads an array of numbers as f32s, e.g. a glTF color factor or matrix.
+    pub fn as_f32_vec(&self) -> Option<Vec<f32>> {
+        match self {
+            Json::Array(items) => items.iter().map(|v| v.as_f64().map(|n| n as f32)).collect(),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Arrays and objects currently open
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.bytes.get(self.pos) {
+            if c == b' ' || c == b'\n' || c == b'\r' || c == b'\t' { self.pos += 1; } else { break; }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> { self.bytes.get(self.pos).copied() }
+
+    fn expect(&mut self, c: u8) -> Result<(), String> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at byte {}", c as char, self.pos))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.nested(Self::parse_object),
+            Some(b'[') => self.nested(Self::parse_array),
+            Some(b'"') => self.parse_string().map(Json::String),
+            Some(b't') => self.parse_literal("true", Json::Bool(true)),
+            Some(b'f') => self.parse_literal("false", Json::Bool(false)),
+            Some(b'n') => self.parse_literal("null", Json::Null),
+            Some(c) if c == b'-' || c.is_ascii_digit() => self.parse_number(),
+            Some(c) => Err(format!("Unexpected character '{}' at byte {}", c as char, self.pos)),
+            None => Err("Unexpected end of input".to_string()),
+        }
+    }
+
+    /// Runs `parse` one nesting level deeper, failing past `MAX_DEPTH`.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json, String>) -> Result<Json, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("JSON nested deeper than {} levels at byte {}", MAX_DEPTH, self.pos));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_literal(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(format!("Invalid literal at byte {}", self.pos))
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.' | b'e' | b'E') { self.pos += 1; } else { break; }
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or("");
+        text.parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| format!("Invalid number '{}' at byte {}", text, start))
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            // Copy runs of plain characters in one go
+            while let Some(c) = self.peek() {
+                if c == b'"' || c == b'\\' { break; }
+                self.pos += 1;
+            }
+            out.push_str(std::str::from_utf8(&self.bytes[start..self.pos]).map_err(|e| e.to_string())?);
+            match self.peek() {
+                Some(b'"') => { self.pos += 1; return Ok(out); }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let esc = self.peek().ok_or("Unterminated escape")?;
+                    self.pos += 1;
+                    match esc {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'u' => {
+                            let hex = self.bytes.get(self.pos..self.pos + 4).ok_or("Truncated unicode escape")?;
+                            let code = u32::from_str_radix(std::str::from_utf8(hex).unwrap_or(""), 16)
+                                .map_err(|_| format!("Invalid unicode escape at byte {}", self.pos))?;
+                            self.pos += 4;
+                            out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                        }
+                        _ => return Err(format!("Invalid escape at byte {}", self.pos - 1)),
+                    }
+                }
+                _ => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') { self.pos += 1; return Ok(Json::Array(items)); }
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => { self.pos += 1; return Ok(Json::Array(items)); }
+                _ => return Err(format!("Expected ',' or ']' at byte {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        self.expect(b'{')?;
+        let mut map = HashMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') { self.pos += 1; return Ok(Json::Object(map)); }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            map.insert(key, value);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => { self.pos += 1; return Ok(Json::Object(map)); }
+                _ => return Err(format!("Expected ',' or '}}' at byte {}", self.pos)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scalars() {
+        assert_eq!(Json::parse("null"), Ok(Json::Null));
+        assert_eq!(Json::parse(" true "), Ok(Json::Bool(true)));
+        assert_eq!(Json::parse("false"), Ok(Json::Bool(false)));
+        assert_eq!(Json::parse("-12.5e1"), Ok(Json::Number(-125.0)));
+    }
+
+    #[test]
+    fn parses_string_escapes() {
+        let parsed = Json::parse(r#""a\"b\\c\/\n\t\u00e9""#).unwrap();
+        assert_eq!(parsed.as_str(), Some("a\"b\\c/\n\té"));
+    }
+
+    #[test]
+    fn parses_nested_containers() {
+        let doc = Json::parse(r#"{ "a": [1, 2, {"b": null}], "c": {}, "d": [] }"#).unwrap();
+        let a = doc.get("a").as_array();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a[1].as_usize(), Some(2));
+        assert_eq!(a[2].get("b"), &Json::Null);
+        assert_eq!(doc.get("c"), &Json::Object(HashMap::new()));
+        assert!(doc.get("d").as_array().is_empty());
+        assert_eq!(doc.get("missing"), &Json::Null);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for text in ["", "[1, 2", "{\"a\" 1}", "[1,]", "tru", "\"open", "1 2", "{\"a\": 1,}"] {
+            assert!(Json::parse(text).is_err(), "{:?} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let ok = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&ok).is_ok());
+        let too_deep = format!("{}{}", "[".repeat(MAX_DEPTH + 1), "]".repeat(MAX_DEPTH + 1));
+        assert!(Json::parse(&too_deep).unwrap_err().contains("nested deeper"));
+        // Unterminated deep input must fail cleanly rather than overflow the stack
+        assert!(Json::parse(&"[{\"a\":".repeat(100_000)).is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use glam::Vec3;
+
+/// Uniform block binding point for `SunUniform`.
+pub const SUN_UNIFORM_BINDING: u32 = 2;
+
+/// Directional sun light shared by all lit shaders through the std140 `Sun` uniform block:
+/// ```glsl
+/// layout(std140) uniform Sun { vec4 uSunDirection; vec4 uSunColor; };
+/// ```
+/// `uSunDirection.xyz` points from the sun towards the scene; `uSunColor.a` is the ambient term.
+#[derive(Debug, Clone)]
+pub struct SunUniform {
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub ambient: f32,
+}
+
+impl Default for SunUniform {
+    fn default() -> Self {
+        Self { direction: Vec3::new(0.4, -0.8, 0.4).normalize(), color: Vec3::new(1.0, 0.98, 0.92), ambient: 0.45 }
+    }
+}
+
+impl SunUniform {
+    /// Size of the std140 block in floats: two vec4s.
+    pub const STD140_FLOATS: usize = 8;
+
+    pub fn to_std140(&self) -> [f32; Self::STD140_FLOATS] {
+        [
+            self.direction.x, self.direction.y, self.direction.z, 0.0,
+            self.color.x, self.color.y, self.color.z, self.ambient,
+        ]
+    }
+}
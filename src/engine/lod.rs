@@ -0,0 +1,592 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use glam::Vec3;
+use crate::engine::block::Block;
+use crate::engine::buffer_pool::{ChunkBufferPool, ChunkMesh};
+use crate::engine::chunk::{mesh_blocks, ChunkPos, ChunkVertices, CHUNK_SIZE};
+use crate::engine::constants::{lod, noise, blocks};
+use crate::engine::world::{World, surface_height, column_block};
+
+const CELLS: i32 = CHUNK_SIZE as i32;
+
+#[inline(always)]
+fn index(x: i32, y: i32, z: i32) -> usize { ((y * CELLS + z) * CELLS + x) as usize }
+
+/// Position of an LOD chunk: its level and coordinates in units of that level's chunk size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LodKey { pub level: u32, pub x: i32, pub y: i32, pub z: i32 }
+
+impl LodKey {
+    /// Blocks per cell edge: 2, 4, 8, ... for levels 1, 2, 3, ...
+    pub fn scale(&self) -> i32 { 1 << self.level }
+
+    /// Edge length of the chunk in blocks.
+    pub fn size(&self) -> i32 { CELLS * self.scale() }
+
+    /// World position of the chunk's minimum corner.
+    pub fn origin(&self) -> (i32, i32, i32) { (self.x * self.size(), self.y * self.size(), self.z * self.size()) }
+
+    pub fn columns(&self) -> ColumnRect {
+        let (x, _, z) = self.origin();
+        ColumnRect { min_x: x, min_z: z, max_x: x + self.size(), max_z: z + self.size() }
+    }
+}
+
+/// Rectangle of world block columns on the X/Z plane (max exclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnRect { pub min_x: i32, pub min_z: i32, pub max_x: i32, pub max_z: i32 }
+
+impl ColumnRect {
+    pub const EMPTY: Self = Self { min_x: 0, min_z: 0, max_x: 0, max_z: 0 };
+
+    pub fn is_empty(&self) -> bool { self.max_x <= self.min_x || self.max_z <= self.min_z }
+
+    pub fn contains(&self, x: i32, z: i32) -> bool {
+        x >= self.min_x && x < self.max_x && z >= self.min_z && z < self.max_z
+    }
+
+    pub fn contains_rect(&self, other: &ColumnRect) -> bool {
+        other.is_empty() || (other.min_x >= self.min_x && other.max_x <= self.max_x && other.min_z >= self.min_z && other.max_z <= self.max_z)
+    }
+
+    /// Overlap of two rectangles, or `EMPTY`.
+    pub fn intersect(&self, other: &ColumnRect) -> ColumnRect {
+        let rect = ColumnRect {
+            min_x: self.min_x.max(other.min_x),
+            min_z: self.min_z.max(other.min_z),
+            max_x: self.max_x.min(other.max_x),
+            max_z: self.max_z.min(other.max_z),
+        };
+        if rect.is_empty() { Self::EMPTY } else { rect }
+    }
+}
+
+/// A downsampled chunk of distant terrain: `CHUNK_SIZE`³ cells of `scale`³ blocks each.
+pub struct LodChunk {
+    pub key: LodKey,
+    cells: Vec<Block>,
+    /// Columns covered by finer terrain, left out of the mesh
+    hole: ColumnRect,
+    pub mesh: Option<ChunkMesh>,
+    pub transparent_mesh: Option<ChunkMesh>,
+    pub water_mesh: Option<ChunkMesh>,
+    pub dirty: bool,
+}
+
+impl LodChunk {
+    /// World position of the chunk's mesh origin.
+    pub fn offset(&self) -> Vec3 {
+        let (x, y, z) = self.key.origin();
+        Vec3::new(x as f32, y as f32, z as f32)
+    }
+
+    pub fn release_meshes(&mut self, pool: &mut ChunkBufferPool) {
+        if let Some(mesh) = self.mesh.take() { pool.free(mesh); }
+        if let Some(mesh) = self.transparent_mesh.take() { pool.free(mesh); }
+        if let Some(mesh) = self.water_mesh.take() { pool.free(mesh); }
+    }
+
+    /// Meshes the cells outside the hole. Borders towards other chunks and towards the hole
+    /// get skirts: side walls reaching `SKIRT_CELLS` below the neighboring surface, so height
+    /// differences between levels show a wall instead of a crack.
+    pub fn rebuild_mesh(&mut self, pool: &mut ChunkBufferPool) {
+        let vertices = self.build_vertices();
+        self.release_meshes(pool);
+        self.mesh = vertices.allocate_opaque(pool);
+        self.transparent_mesh = vertices.allocate_transparent(pool);
+        self.water_mesh = vertices.allocate_water(pool);
+        self.dirty = false;
+    }
+
+    fn build_vertices(&self) -> ChunkVertices {
+        let scale = self.key.scale();
+        let (ox, oy, oz) = self.key.origin();
+        let hole = self.hole;
+        let in_hole = |x: i32, z: i32| hole.contains(ox + x * scale, oz + z * scale);
+
+        let mut cells = self.cells.clone();
+        for z in 0..CELLS {
+            for x in 0..CELLS {
+                if in_hole(x, z) {
+                    for y in 0..CELLS { cells[index(x, y, z)] = Block::Air; }
+                }
+            }
+        }
+
+        // Topmost opaque cell per column; columns that are empty or full to the top get no skirt
+        let mut tops = vec![-1; (CELLS * CELLS) as usize];
+        for z in 0..CELLS {
+            for x in 0..CELLS {
+                tops[(z * CELLS + x) as usize] = (0..CELLS).rev()
+                    .find(|&y| is_opaque(cells[index(x, y, z)]))
+                    .unwrap_or(-1);
+            }
+        }
+        let skirt_top = |x: i32, z: i32| -> Option<i32> {
+            if !(0..CELLS).contains(&x) || !(0..CELLS).contains(&z) || in_hole(x, z) { return None; }
+            let top = tops[(z * CELLS + x) as usize];
+            if top < 0 || top == CELLS - 1 { None } else { Some(top) }
+        };
+        let below_sea = |y: i32| oy + y * scale + scale / 2 <= noise::SEA_LEVEL + 1;
+
+        let neighbor_block = |x: i32, y: i32, z: i32| -> Block {
+            if y < 0 { return Block::Solid(blocks::STONE); }
+            if y >= CELLS { return Block::Air; }
+            let outside = !(0..CELLS).contains(&x) || !(0..CELLS).contains(&z);
+            if !outside && !in_hole(x, z) {
+                return cells[index(x, y, z)];
+            }
+            // Virtual column beyond the border: open down to the skirt depth of the lowest
+            // adjacent surface, solid below it so no faces are made there
+            let floor = [(-1, 0), (1, 0), (0, -1), (0, 1)].iter()
+                .filter_map(|(dx, dz)| skirt_top(x + dx, z + dz))
+                .min()
+                .map(|top| top - lod::SKIRT_CELLS);
+            match floor {
+                Some(floor) if y >= floor => if below_sea(y) { Block::Solid(blocks::WATER) } else { Block::Air },
+                _ => Block::Solid(blocks::STONE),
+            }
+        };
+
+        mesh_blocks(&cells, neighbor_block, scale as f32)
+    }
+}
+
+fn is_opaque(block: Block) -> bool {
+    !matches!(block, Block::Air | Block::Solid(blocks::WATER) | Block::Solid(blocks::PORTAL))
+}
+
+struct LodGenRequest {
+    key: LodKey,
+}
+
+struct LodGenResult {
+    key: LodKey,
+    cells: Vec<Block>,
+}
+
+/// Rings of progressively coarser terrain around the full-resolution chunks.
+/// Ring n (1-based) is made of LOD chunks with 2^n-block cells and covers at least
+/// `radius` of its chunks around the player, minus the area of the finer rings inside it.
+pub struct LodTerrain {
+    pub enabled: bool,
+    pub levels: u32,
+    pub radius: i32,
+    /// Wait for every requested chunk each frame (see `World::deterministic_loading`)
+    pub deterministic_loading: bool,
+    pub chunks: HashMap<LodKey, LodChunk>,
+    pending: HashSet<LodKey>,
+    /// Per level: (area covered, hole left for the finer terrain inside it)
+    rings: Vec<(ColumnRect, ColumnRect)>,
+    request_tx: Sender<LodGenRequest>,
+    result_rx: Receiver<LodGenResult>,
+    _worker_handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl Default for LodTerrain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LodTerrain {
+    pub fn new() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<LodGenRequest>();
+        let (result_tx, result_rx) = mpsc::channel::<LodGenResult>();
+        let request_rx = Arc::new(Mutex::new(request_rx));
+
+        // Fewer workers than full-resolution chunks get, which take priority
+        let num_workers = thread::available_parallelism()
+            .map(|p| (p.get() / 4).max(1))
+            .unwrap_or(1);
+        let mut handles = Vec::with_capacity(num_workers);
+        for _ in 0..num_workers {
+            let rx = request_rx.clone();
+            let tx = result_tx.clone();
+            handles.push(thread::spawn(move || loop {
+                let request = {
+                    let guard = rx.lock().unwrap();
+                    guard.recv()
+                };
+                match request {
+                    Ok(req) => {
+                        let cells = generate_lod_cells(req.key);
+                        let _ = tx.send(LodGenResult { key: req.key, cells });
+                    }
+                    Err(_) => break,
+                }
+            }));
+        }
+
+        Self {
+            enabled: true,
+            levels: lod::LEVELS,
+            radius: lod::RADIUS,
+            deterministic_loading: false,
+            chunks: HashMap::new(),
+            pending: HashSet::new(),
+            rings: Vec::new(),
+            request_tx,
+            result_rx,
+            _worker_handles: handles,
+        }
+    }
+
+    /// Distance from the player to the nearest edge of the outermost ring, or `None` when disabled.
+    pub fn view_distance(&self) -> Option<f32> {
+        (self.enabled && self.levels > 0).then(|| ring_distance(self.levels, self.radius))
+    }
+
+    /// Number of LOD chunks currently loaded.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Lays out the rings around `player_pos` outside `full_res` (the columns drawn with
+    /// full-resolution chunks), then requests, receives, re-masks and unloads LOD chunks.
+    pub fn update(&mut self, player_pos: Vec3, full_res: ColumnRect, pool: &mut ChunkBufferPool) {
+        if !self.enabled {
+            for (_, mut chunk) in self.chunks.drain() { chunk.release_meshes(pool); }
+            self.pending.clear();
+            self.rings.clear();
+            return;
+        }
+
+        self.rings.clear();
+        let mut finer = full_res;
+        for level in 1..=self.levels {
+            let size = CELLS << level;
+            let cx = (player_pos.x as i32).div_euclid(size);
+            let cz = (player_pos.z as i32).div_euclid(size);
+            let mut radius = self.radius;
+            let area = loop {
+                let area = ColumnRect {
+                    min_x: (cx - radius) * size, min_z: (cz - radius) * size,
+                    max_x: (cx + radius + 1) * size, max_z: (cz + radius + 1) * size,
+                };
+                if area.contains_rect(&finer) { break area; }
+                radius += 1;
+            };
+            self.rings.push((area, finer));
+            finer = area;
+        }
+
+        // Receive generated chunks that are still wanted
+        let mut received = 0;
+        loop {
+            let result = if self.deterministic_loading {
+                if self.pending.is_empty() { break; }
+                self.result_rx.recv().ok()
+            } else if received < lod::MAX_RECEIVES_PER_FRAME {
+                self.result_rx.try_recv().ok()
+            } else {
+                None
+            };
+            let Some(result) = result else { break };
+            received += 1;
+            if !self.pending.remove(&result.key) { continue; }
+            if let Some(hole) = self.wanted_hole(&result.key) {
+                let chunk = LodChunk {
+                    key: result.key,
+                    cells: result.cells,
+                    hole,
+                    mesh: None,
+                    transparent_mesh: None,
+                    water_mesh: None,
+                    dirty: true,
+                };
+                if let Some(mut old) = self.chunks.insert(result.key, chunk) {
+                    old.release_meshes(pool);
+                }
+            }
+        }
+
+        // Unload chunks that left their ring and re-mask those whose hole moved
+        let rings = &self.rings;
+        let wanted = |key: &LodKey| wanted_hole(rings, key);
+        let to_remove: Vec<LodKey> = self.chunks.keys().filter(|k| wanted(k).is_none()).copied().collect();
+        for key in to_remove {
+            if let Some(mut chunk) = self.chunks.remove(&key) { chunk.release_meshes(pool); }
+        }
+        for chunk in self.chunks.values_mut() {
+            if let Some(hole) = wanted(&chunk.key) {
+                if hole != chunk.hole {
+                    chunk.hole = hole;
+                    chunk.dirty = true;
+                }
+            }
+        }
+        self.pending.retain(|key| wanted(key).is_some());
+
+        // Request missing chunks, nearest first
+        let mut to_request: Vec<(LodKey, i64)> = Vec::new();
+        for (i, (area, _)) in self.rings.iter().enumerate() {
+            let level = i as u32 + 1;
+            let size = CELLS << level;
+            for z in area.min_z.div_euclid(size)..area.max_z.div_euclid(size) {
+                for x in area.min_x.div_euclid(size)..area.max_x.div_euclid(size) {
+                    for y in 0..=(lod::MAX_HEIGHT - 1).div_euclid(size) {
+                        let key = LodKey { level, x, y, z };
+                        if self.chunks.contains_key(&key) || self.pending.contains(&key) { continue; }
+                        if wanted(&key).is_none() { continue; }
+                        let (kx, _, kz) = key.origin();
+                        let dx = (kx + size / 2) as i64 - player_pos.x as i64;
+                        let dz = (kz + size / 2) as i64 - player_pos.z as i64;
+                        to_request.push((key, dx * dx + dz * dz));
+                    }
+                }
+            }
+        }
+        to_request.sort_by_key(|&(_, dist)| dist);
+        for (key, _) in to_request.into_iter().take(lod::MAX_REQUESTS_PER_FRAME) {
+            self.pending.insert(key);
+            let _ = self.request_tx.send(LodGenRequest { key });
+        }
+    }
+
+    /// Remeshes dirty LOD chunks, nearest first, up to `MAX_REBUILDS_PER_FRAME`.
+    pub fn rebuild_dirty(&mut self, pool: &mut ChunkBufferPool, player_pos: Vec3) {
+        let mut dirty: Vec<(LodKey, f32)> = self.chunks.values()
+            .filter(|c| c.dirty)
+            .map(|c| (c.key, (c.offset() + Vec3::splat(c.key.size() as f32 * 0.5) - player_pos).length_squared()))
+            .collect();
+        dirty.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        for (key, _) in dirty.into_iter().take(lod::MAX_REBUILDS_PER_FRAME) {
+            if let Some(chunk) = self.chunks.get_mut(&key) {
+                chunk.rebuild_mesh(pool);
+            }
+        }
+    }
+
+    fn wanted_hole(&self, key: &LodKey) -> Option<ColumnRect> {
+        wanted_hole(&self.rings, key)
+    }
+}
+
+/// Minimum distance in blocks from the player to the outer edge of ring `levels` with the given
+/// radius; rings only grow past it to contain the finer ones.
+pub fn ring_distance(levels: u32, radius: i32) -> f32 {
+    (radius * (CELLS << levels)) as f32
+}
+
+/// The hole to cut out of an LOD chunk, or `None` if the chunk is outside its ring
+/// or entirely covered by finer terrain.
+fn wanted_hole(rings: &[(ColumnRect, ColumnRect)], key: &LodKey) -> Option<ColumnRect> {
+    let (area, finer) = rings.get(key.level as usize - 1)?;
+    let columns = key.columns();
+    if area.intersect(&columns).is_empty() || finer.contains_rect(&columns) { return None; }
+    Some(finer.intersect(&columns))
+}
+
+/// Generates the cells of an LOD chunk on a worker thread. Fine levels downsample the real
+/// voxel terrain (caves included); coarse levels only sample the heightmap, which is far
+/// cheaper than generating every block they cover.
+fn generate_lod_cells(key: LodKey) -> Vec<Block> {
+    use ::noise::Perlin;
+
+    let terrain_noise = Perlin::new(noise::SEED);
+    let detail_noise = Perlin::new(noise::SEED.wrapping_add(1));
+    let scale = key.scale();
+    let (ox, oy, oz) = key.origin();
+
+    // Surface height at each cell column's center
+    let mut heights = vec![0; (CELLS * CELLS) as usize];
+    for z in 0..CELLS {
+        for x in 0..CELLS {
+            heights[(z * CELLS + x) as usize] = surface_height(&terrain_noise, &detail_noise, ox + x * scale + scale / 2, oz + z * scale + scale / 2);
+        }
+    }
+
+    if scale <= lod::VOXEL_MAX_SCALE {
+        downsample_voxels(key, &heights)
+    } else {
+        cells_from_heightmap(key, &heights, oy)
+    }
+}
+
+/// Fills cells from the surface heights alone: a cell is solid when its center is under the
+/// surface and water when it is under sea level.
+fn cells_from_heightmap(key: LodKey, heights: &[i32], oy: i32) -> Vec<Block> {
+    let scale = key.scale();
+    let mut cells = vec![Block::Air; (CELLS * CELLS * CELLS) as usize];
+    for z in 0..CELLS {
+        for x in 0..CELLS {
+            let surface_y = heights[(z * CELLS + x) as usize];
+            // Index of the topmost solid cell (blocks occupy [y, y + 1), so the terrain ends at surface_y + 1)
+            let top = (surface_y + 1 - oy - scale / 2).div_euclid(scale);
+            for y in 0..CELLS {
+                let center = oy + y * scale + scale / 2;
+                cells[index(x, y, z)] = if y <= top {
+                    Block::Solid(column_block(surface_y, (top - y) * scale))
+                } else if center <= noise::SEA_LEVEL + 1 {
+                    Block::Solid(blocks::WATER)
+                } else {
+                    Block::Air
+                };
+            }
+        }
+    }
+    cells
+}
+
+/// Generates every full-resolution chunk the LOD chunk covers and reduces each `scale`³ group
+/// of blocks to one cell. Chunks entirely above the terrain and the sea are skipped.
+fn downsample_voxels(key: LodKey, heights: &[i32]) -> Vec<Block> {
+    let scale = key.scale();
+    let per_chunk = CELLS / scale;
+    let highest = heights.iter().copied().max().unwrap_or(0) + noise::DETAIL_HEIGHT as i32 + 1;
+    let mut cells = vec![Block::Air; (CELLS * CELLS * CELLS) as usize];
+
+    for sy in 0..scale {
+        for sz in 0..scale {
+            for sx in 0..scale {
+                let pos = ChunkPos { x: key.x * scale + sx, y: key.y * scale + sy, z: key.z * scale + sz };
+                let base_y = pos.y * CELLS;
+                if base_y > highest && base_y > noise::SEA_LEVEL { continue; }
+                let data = World::generate_terrain_data(pos);
+
+                for cy in 0..per_chunk {
+                    for cz in 0..per_chunk {
+                        for cx in 0..per_chunk {
+                            let block = reduce_group(&data, cx * scale, cy * scale, cz * scale, scale);
+                            cells[index(sx * per_chunk + cx, sy * per_chunk + cy, sz * per_chunk + cz)] = block;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    cells
+}
+
+/// Picks one block for a `scale`³ group: its topmost opaque block if at least half the group
+/// is opaque, otherwise water if that fills the rest to half, otherwise air.
+fn reduce_group(data: &[Block], x0: i32, y0: i32, z0: i32, scale: i32) -> Block {
+    let total = scale * scale * scale;
+    let (mut opaque, mut water) = (0, 0);
+    let mut top = None;
+    for y in y0..y0 + scale {
+        for z in z0..z0 + scale {
+            for x in x0..x0 + scale {
+                let block = data[index(x, y, z)];
+                if is_opaque(block) {
+                    opaque += 1;
+                    top = Some(block);
+                } else if block == Block::Solid(blocks::WATER) {
+                    water += 1;
+                }
+            }
+        }
+    }
+    match top {
+        Some(block) if opaque * 2 >= total => block,
+        _ if (opaque + water) * 2 >= total && water > 0 => Block::Solid(blocks::WATER),
+        _ => Block::Air,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(min_x: i32, min_z: i32, max_x: i32, max_z: i32) -> ColumnRect {
+        ColumnRect { min_x, min_z, max_x, max_z }
+    }
+
+    #[test]
+    fn column_rect_contains_is_max_exclusive() {
+        let r = rect(0, 0, 16, 32);
+        assert!(r.contains(0, 0));
+        assert!(r.contains(15, 31));
+        assert!(!r.contains(16, 0));
+        assert!(!r.contains(0, 32));
+        assert!(!r.contains(-1, 5));
+    }
+
+    #[test]
+    fn column_rect_contains_rect() {
+        let r = rect(-32, -32, 32, 32);
+        assert!(r.contains_rect(&rect(-32, -32, 32, 32)));
+        assert!(r.contains_rect(&rect(0, 0, 16, 16)));
+        assert!(!r.contains_rect(&rect(16, 16, 48, 48)));
+        // Anything contains the empty rectangle, wherever it is
+        assert!(r.contains_rect(&rect(100, 100, 100, 120)));
+        assert!(r.contains_rect(&ColumnRect::EMPTY));
+    }
+
+    #[test]
+    fn column_rect_intersect() {
+        let a = rect(0, 0, 32, 32);
+        assert_eq!(a.intersect(&rect(16, -16, 48, 16)), rect(16, 0, 32, 16));
+        assert_eq!(a.intersect(&rect(0, 0, 32, 32)), a);
+        // Touching edges don't overlap
+        assert_eq!(a.intersect(&rect(32, 0, 64, 32)), ColumnRect::EMPTY);
+        assert!(a.intersect(&rect(100, 100, 200, 200)).is_empty());
+    }
+
+    #[test]
+    fn wanted_hole_per_ring() {
+        // Level 1 chunks are `size` blocks wide; the finer area is exactly chunk (0, 0)
+        let size = CELLS * 2;
+        let finer = rect(0, 0, size, size);
+        let rings = [(rect(-2 * size, -2 * size, 3 * size, 3 * size), finer)];
+
+        // Fully covered by finer terrain: not wanted
+        assert_eq!(wanted_hole(&rings, &LodKey { level: 1, x: 0, y: 0, z: 0 }), None);
+        // Inside the ring and clear of the finer area: no hole
+        assert_eq!(wanted_hole(&rings, &LodKey { level: 1, x: -2, y: 1, z: -2 }), Some(ColumnRect::EMPTY));
+        // Outside the ring
+        assert_eq!(wanted_hole(&rings, &LodKey { level: 1, x: 3, y: 0, z: 0 }), None);
+        // No ring for this level
+        assert_eq!(wanted_hole(&rings, &LodKey { level: 2, x: 0, y: 0, z: 0 }), None);
+    }
+
+    #[test]
+    fn wanted_hole_cuts_partial_overlap() {
+        let size = CELLS * 2;
+        let half = size / 2;
+        let rings = [(rect(-2 * size, -2 * size, 3 * size, 3 * size), rect(half, half, size + half, size + half))];
+        assert_eq!(wanted_hole(&rings, &LodKey { level: 1, x: 0, y: 0, z: 0 }), Some(rect(half, half, size, size)));
+        assert_eq!(wanted_hole(&rings, &LodKey { level: 1, x: 1, y: 0, z: 0 }), Some(rect(size, half, size + half, size)));
+    }
+
+    fn group_with(fill: &[(i32, i32, i32, Block)]) -> Vec<Block> {
+        let mut data = vec![Block::Air; (CELLS * CELLS * CELLS) as usize];
+        for &(x, y, z, block) in fill { data[index(x, y, z)] = block; }
+        data
+    }
+
+    #[test]
+    fn reduce_group_prefers_topmost_opaque_block() {
+        let stone = Block::Solid(blocks::STONE);
+        let grass = Block::Solid(blocks::GRASS);
+        let data = group_with(&[
+            (0, 0, 0, stone), (1, 0, 0, stone), (0, 0, 1, stone), (1, 0, 1, stone),
+            (0, 1, 0, grass),
+        ]);
+        assert_eq!(reduce_group(&data, 0, 0, 0, 2), grass);
+    }
+
+    #[test]
+    fn reduce_group_water_and_air() {
+        let water = Block::Solid(blocks::WATER);
+        let stone = Block::Solid(blocks::STONE);
+        // Two opaque and two water blocks out of eight: half full, so water
+        let data = group_with(&[(0, 0, 0, stone), (1, 0, 0, stone), (0, 0, 1, water), (1, 0, 1, water)]);
+        assert_eq!(reduce_group(&data, 0, 0, 0, 2), water);
+        // Mostly air
+        let data = group_with(&[(0, 0, 0, stone), (1, 0, 0, water)]);
+        assert_eq!(reduce_group(&data, 0, 0, 0, 2), Block::Air);
+        // A group away from the origin reads its own blocks
+        let data = group_with(&[(2, 2, 2, stone)]);
+        assert_eq!(reduce_group(&data, 0, 0, 0, 2), Block::Air);
+    }
+
+    #[test]
+    fn ring_distance_scales_with_levels() {
+        assert_eq!(ring_distance(1, 4), (4 * CELLS * 2) as f32);
+        assert_eq!(ring_distance(lod::LEVELS, lod::RADIUS), (lod::RADIUS * (CELLS << lod::LEVELS)) as f32);
+    }
+}
@@ -1,20 +1,101 @@
 use gl::types::*;
+use glam::Vec3;
+use std::cell::RefCell;
 use std::mem;
 use std::ptr;
 
-/// GPU mesh with vertex array object and buffer.
-/// Vertex format: pos(3) + normal(3) + uv(2) + color(4) = 12 floats per vertex
-pub struct Mesh { pub vao: u32, vbo: u32, count: i32 }
+/// Floats per vertex in the engine's interleaved layout.
+pub const FLOATS_PER_VERTEX: usize = 13;
+
+/// Index data for an indexed mesh.
+pub enum MeshIndices<'a> {
+    U16(&'a [u16]),
+    U32(&'a [u32]),
+}
+
+/// Where an indexed mesh gets its element buffer from.
+enum IndexSource {
+    /// Plain `glDrawArrays` over the vertex stream
+    None,
+    /// Element buffer owned (and deleted) by this mesh
+    Owned { ebo: u32, ty: GLenum, count: i32 },
+    /// The shared quad index buffer (0-1-2, 0-2-3 per quad), not owned
+    SharedQuads { count: i32 },
+}
+
+/// GPU mesh with vertex array object and buffer, optionally indexed.
+/// Vertex format: pos(3) + normal(3) + uv(2) + color(4) + texture layer(1) = 13 floats per vertex
+pub struct Mesh { pub vao: u32, vbo: u32, count: i32, indices: IndexSource, bounds_min: Vec3, bounds_max: Vec3 }
 
 impl Mesh {
     /// Returns the number of vertices in this mesh.
-    #[allow(dead_code)]
     pub fn vertex_count(&self) -> i32 {
         self.count
     }
 
+    /// Returns the number of indices drawn, or 0 for non-indexed meshes.
+    #[allow(dead_code)]
+    pub fn index_count(&self) -> i32 {
+        match self.indices {
+            IndexSource::None => 0,
+            IndexSource::Owned { count, .. } | IndexSource::SharedQuads { count } => count,
+        }
+    }
+
+    /// Returns the local-space bounding box of the mesh as (min, max).
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        (self.bounds_min, self.bounds_max)
+    }
+
+    #[allow(dead_code)]
     pub fn from_vertices(vertices: &[f32]) -> Self {
-        assert!(vertices.len().is_multiple_of(12), "vertex slice must be multiple of 12 (pos3+normal3+uv2+color4)");
+        let mesh = Self::upload_vertices(vertices);
+        unsafe { gl::BindVertexArray(0); }
+        mesh
+    }
+
+    /// Creates a mesh with its own element buffer.
+    pub fn from_indexed(vertices: &[f32], indices: MeshIndices) -> Self {
+        let mut mesh = Self::upload_vertices(vertices);
+        unsafe {
+            let mut ebo = 0;
+            gl::GenBuffers(1, &mut ebo);
+            // The VAO is still bound, so this attaches the element buffer to it
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            let (ty, count) = match indices {
+                MeshIndices::U16(data) => {
+                    gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, mem::size_of_val(data) as isize, data.as_ptr() as *const _, gl::STATIC_DRAW);
+                    (gl::UNSIGNED_SHORT, data.len() as i32)
+                }
+                MeshIndices::U32(data) => {
+                    gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, mem::size_of_val(data) as isize, data.as_ptr() as *const _, gl::STATIC_DRAW);
+                    (gl::UNSIGNED_INT, data.len() as i32)
+                }
+            };
+            gl::BindVertexArray(0);
+            mesh.indices = IndexSource::Owned { ebo, ty, count };
+        }
+        mesh
+    }
+
+    /// Creates a mesh from quads of 4 vertices each (corners in CCW order),
+    /// drawn through the shared quad index buffer as two triangles per quad.
+    pub fn from_quads(vertices: &[f32]) -> Self {
+        assert!(vertices.len().is_multiple_of(FLOATS_PER_VERTEX * 4), "quad vertex slice must hold 4 vertices per quad");
+        let quads = vertices.len() / (FLOATS_PER_VERTEX * 4);
+        let mut mesh = Self::upload_vertices(vertices);
+        unsafe {
+            bind_quad_index_buffer(quads);
+            gl::BindVertexArray(0);
+        }
+        mesh.indices = IndexSource::SharedQuads { count: (quads * 6) as i32 };
+        mesh
+    }
+
+    /// Uploads the vertex stream and leaves the new VAO bound.
+    fn upload_vertices(vertices: &[f32]) -> Self {
+        assert!(vertices.len().is_multiple_of(FLOATS_PER_VERTEX), "vertex slice must be multiple of 13 (pos3+normal3+uv2+color4+layer1)");
+        let (bounds_min, bounds_max) = compute_bounds(vertices);
         unsafe {
             let (mut vbo, mut vao) = (0, 0);
             gl::GenVertexArrays(1, &mut vao);
@@ -27,29 +108,128 @@ impl Mesh {
                 vertices.as_ptr() as *const _,
                 gl::STATIC_DRAW,
             );
-            let stride = (12 * mem::size_of::<f32>()) as GLsizei;
-            // Position: location 0
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-            gl::EnableVertexAttribArray(0);
-            // Normal: location 1
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, (3 * mem::size_of::<f32>()) as *const _);
-            gl::EnableVertexAttribArray(1);
-            // UV: location 2
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, stride, (6 * mem::size_of::<f32>()) as *const _);
-            gl::EnableVertexAttribArray(2);
-            // Color: location 3
-            gl::VertexAttribPointer(3, 4, gl::FLOAT, gl::FALSE, stride, (8 * mem::size_of::<f32>()) as *const _);
-            gl::EnableVertexAttribArray(3);
-            Mesh { vao, vbo, count: (vertices.len() / 12) as i32 }
+            setup_vertex_attributes();
+            Mesh { vao, vbo, count: (vertices.len() / FLOATS_PER_VERTEX) as i32, indices: IndexSource::None, bounds_min, bounds_max }
+        }
+    }
+
+    /// Builds a unit cube centered on the origin with a flat vertex color.
+    pub fn cube(color: [f32; 4]) -> Self {
+        // (normal, four CCW corners as seen from outside)
+        let faces: [([f32; 3], [[f32; 3]; 4]); 6] = [
+            ([-1.0, 0.0, 0.0], [[-0.5, -0.5, -0.5], [-0.5, -0.5, 0.5], [-0.5, 0.5, 0.5], [-0.5, 0.5, -0.5]]),
+            ([1.0, 0.0, 0.0], [[0.5, -0.5, 0.5], [0.5, -0.5, -0.5], [0.5, 0.5, -0.5], [0.5, 0.5, 0.5]]),
+            ([0.0, -1.0, 0.0], [[-0.5, -0.5, -0.5], [0.5, -0.5, -0.5], [0.5, -0.5, 0.5], [-0.5, -0.5, 0.5]]),
+            ([0.0, 1.0, 0.0], [[-0.5, 0.5, 0.5], [0.5, 0.5, 0.5], [0.5, 0.5, -0.5], [-0.5, 0.5, -0.5]]),
+            ([0.0, 0.0, -1.0], [[0.5, -0.5, -0.5], [-0.5, -0.5, -0.5], [-0.5, 0.5, -0.5], [0.5, 0.5, -0.5]]),
+            ([0.0, 0.0, 1.0], [[-0.5, -0.5, 0.5], [0.5, -0.5, 0.5], [0.5, 0.5, 0.5], [-0.5, 0.5, 0.5]]),
+        ];
+        let uvs = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+        let mut vertices = Vec::with_capacity(6 * 4 * FLOATS_PER_VERTEX);
+        for (normal, corners) in faces.iter() {
+            for i in 0..4 {
+                vertices.extend_from_slice(&corners[i]);
+                vertices.extend_from_slice(normal);
+                vertices.extend_from_slice(&uvs[i]);
+                vertices.extend_from_slice(&color);
+                vertices.push(0.0);
+            }
         }
+        Self::from_quads(&vertices)
     }
 
+    #[allow(dead_code)]
     pub unsafe fn draw(&self) {
         gl::BindVertexArray(self.vao);
-        gl::DrawArrays(gl::TRIANGLES, 0, self.count);
+        match self.indices {
+            IndexSource::None => gl::DrawArrays(gl::TRIANGLES, 0, self.count),
+            IndexSource::Owned { ty, count, .. } => gl::DrawElements(gl::TRIANGLES, count, ty, ptr::null()),
+            IndexSource::SharedQuads { count } => gl::DrawElements(gl::TRIANGLES, count, gl::UNSIGNED_INT, ptr::null()),
+        }
+    }
+
+    /// Draws `instances` copies of the mesh. Per-instance attributes must already
+    /// be bound to this mesh's vertex array.
+    pub unsafe fn draw_instanced(&self, instances: i32) {
+        gl::BindVertexArray(self.vao);
+        match self.indices {
+            IndexSource::None => gl::DrawArraysInstanced(gl::TRIANGLES, 0, self.count, instances),
+            IndexSource::Owned { ty, count, .. } => gl::DrawElementsInstanced(gl::TRIANGLES, count, ty, ptr::null(), instances),
+            IndexSource::SharedQuads { count } => gl::DrawElementsInstanced(gl::TRIANGLES, count, gl::UNSIGNED_INT, ptr::null(), instances),
+        }
     }
 }
 
 impl Drop for Mesh {
-    fn drop(&mut self) { unsafe { gl::DeleteVertexArrays(1, &self.vao); gl::DeleteBuffers(1, &self.vbo); } }
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+            if let IndexSource::Owned { ebo, .. } = self.indices { gl::DeleteBuffers(1, &ebo); }
+        }
+    }
+}
+
+/// Configures attribute pointers 0-3 and 5 for the interleaved vertex layout
+/// on the currently bound VAO and ARRAY_BUFFER.
+pub unsafe fn setup_vertex_attributes() {
+    let stride = (FLOATS_PER_VERTEX * mem::size_of::<f32>()) as GLsizei;
+    // Position: location 0
+    gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
+    gl::EnableVertexAttribArray(0);
+    // Normal: location 1
+    gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, (3 * mem::size_of::<f32>()) as *const _);
+    gl::EnableVertexAttribArray(1);
+    // UV: location 2
+    gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, stride, (6 * mem::size_of::<f32>()) as *const _);
+    gl::EnableVertexAttribArray(2);
+    // Color: location 3
+    gl::VertexAttribPointer(3, 4, gl::FLOAT, gl::FALSE, stride, (8 * mem::size_of::<f32>()) as *const _);
+    gl::EnableVertexAttribArray(3);
+    // Texture array layer: location 5 (4 is reserved for the per-draw chunk offset)
+    gl::VertexAttribPointer(5, 1, gl::FLOAT, gl::FALSE, stride, (12 * mem::size_of::<f32>()) as *const _);
+    gl::EnableVertexAttribArray(5);
+}
+
+/// Shared element buffer holding the 0-1-2, 0-2-3 pattern for consecutive quads.
+/// It only ever grows, and resizing keeps the same buffer name so VAOs that reference it stay valid.
+struct QuadIndexBuffer { id: u32, quads: usize }
+
+thread_local! {
+    static QUAD_INDEX_BUFFER: RefCell<QuadIndexBuffer> = const { RefCell::new(QuadIndexBuffer { id: 0, quads: 0 }) };
+}
+
+/// Binds the shared quad index buffer to the current VAO, growing it to cover at least `quads` quads.
+pub unsafe fn bind_quad_index_buffer(quads: usize) {
+    QUAD_INDEX_BUFFER.with(|cell| {
+        let mut buffer = cell.borrow_mut();
+        if buffer.id == 0 {
+            gl::GenBuffers(1, &mut buffer.id);
+        }
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, buffer.id);
+        if quads > buffer.quads {
+            let capacity = quads.next_power_of_two().max(1024);
+            let mut indices: Vec<u32> = Vec::with_capacity(capacity * 6);
+            for q in 0..capacity as u32 {
+                let base = q * 4;
+                indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+            }
+            gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, mem::size_of_val(indices.as_slice()) as isize, indices.as_ptr() as *const _, gl::STATIC_DRAW);
+            buffer.quads = capacity;
+        }
+    });
+}
+
+/// Computes the axis-aligned bounds of an interleaved vertex stream.
+fn compute_bounds(vertices: &[f32]) -> (Vec3, Vec3) {
+    if vertices.is_empty() { return (Vec3::ZERO, Vec3::ZERO); }
+    let mut min = Vec3::splat(f32::MAX);
+    let mut max = Vec3::splat(f32::MIN);
+    for v in vertices.chunks_exact(FLOATS_PER_VERTEX) {
+        let p = Vec3::new(v[0], v[1], v[2]);
+        min = min.min(p);
+        max = max.max(p);
+    }
+    (min, max)
 }
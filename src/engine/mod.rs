@@ -1,13 +1,38 @@
 pub mod camera;
+pub mod camera_controller;
 pub mod game;
 pub mod core;
 pub mod input;
+pub mod actions;
+pub mod gamepad;
+pub mod replay;
 pub mod shader;
 pub mod mesh;
 pub mod entity;
 pub mod block;
 pub mod chunk;
 pub mod world;
+pub mod lod;
 pub mod shader_sources;
+pub mod shader_loader;
 pub mod constants;
-pub mod texture;
\ No newline at end of file
+pub mod texture;
+pub mod tile_animation;
+pub mod entity_renderer;
+pub mod json;
+pub mod model;
+pub mod gl_caps;
+pub mod buffer_pool;
+pub mod fog;
+pub mod water;
+pub mod lighting;
+pub mod shadow;
+pub mod framebuffer;
+pub mod post;
+pub mod sky;
+pub mod text;
+pub mod debug_hud;
+pub mod debug_draw;
+pub mod render_mode;
+pub mod profiler;
+pub mod screenshot;
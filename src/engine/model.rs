@@ -0,0 +1,828 @@
+use std::collections::HashMap;
+use std::path::Path;
+use glam::{Mat3, Mat4, Quat, Vec3};
+use crate::engine::json::Json;
+use crate::engine::mesh::{Mesh, MeshIndices, FLOATS_PER_VERTEX};
+
+/// CPU-side mesh data in the engine vertex layout:
+/// pos(3) + normal(3) + uv(2) + color(4) + layer(1) = 13 floats per vertex, indexed as triangles.
+pub struct MeshData {
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+    /// Material base color; already multiplied into the vertex colors.
+    pub base_color: [f32; 4],
+}
+
+impl MeshData {
+    fn new(base_color: [f32; 4]) -> Self {
+        Self { vertices: Vec::new(), indices: Vec::new(), base_color }
+    }
+
+    pub fn vertex_count(&self) -> usize { self.vertices.len() / FLOATS_PER_VERTEX }
+
+    fn push_vertex(&mut self, pos: [f32; 3], normal: [f32; 3], uv: [f32; 2], color: [f32; 4]) -> u32 {
+        let index = self.vertex_count() as u32;
+        self.vertices.extend_from_slice(&pos);
+        self.vertices.extend_from_slice(&normal);
+        self.vertices.extend_from_slice(&uv);
+        self.vertices.extend_from_slice(&[
+            color[0] * self.base_color[0],
+            color[1] * self.base_color[1],
+            color[2] * self.base_color[2],
+            color[3] * self.base_color[3],
+        ]);
+        self.vertices.push(0.0); // Models don't sample the block texture array
+        index
+    }
+
+    /// Uploads the mesh, using 16-bit indices when the vertex count allows it.
+    pub fn to_mesh(&self) -> Mesh {
+        if self.vertex_count() <= u16::MAX as usize + 1 {
+            let indices: Vec<u16> = self.indices.iter().map(|&i| i as u16).collect();
+            Mesh::from_indexed(&self.vertices, MeshIndices::U16(&indices))
+        } else {
+            Mesh::from_indexed(&self.vertices, MeshIndices::U32(&self.indices))
+        }
+    }
+}
+
+/// A loaded model: one `MeshData` per primitive (OBJ material group or glTF primitive).
+pub struct Model {
+    pub primitives: Vec<MeshData>,
+}
+
+#[allow(dead_code)]
+impl Model {
+    /// Loads an OBJ, glTF or GLB file based on its extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+        match ext.as_str() {
+            "obj" => load_obj(path),
+            "gltf" | "glb" => load_gltf(path),
+            _ => Err(format!("Unsupported model format: {}", path.display())),
+        }
+    }
+
+    /// Uploads each primitive as its own mesh.
+    pub fn to_meshes(&self) -> Vec<Mesh> {
+        self.primitives.iter().map(|p| p.to_mesh()).collect()
+    }
+
+    /// Uploads all primitives as a single mesh. Materials survive as baked vertex colors.
+    pub fn to_merged_mesh(&self) -> Mesh {
+        let mut merged = MeshData::new([1.0; 4]);
+        for primitive in &self.primitives {
+            let base = merged.vertex_count() as u32;
+            merged.vertices.extend_from_slice(&primitive.vertices);
+            merged.indices.extend(primitive.indices.iter().map(|&i| base + i));
+        }
+        merged.to_mesh()
+    }
+}
+
+// --- Wavefront OBJ ---------------------------------------------------------
+
+/// Loads a Wavefront OBJ file. Faces are triangulated as fans, and each `usemtl`
+/// group becomes a primitive with the material's diffuse color (`Kd`/`d`) as base color.
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Model, String> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read OBJ {}: {}", path.display(), e))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_obj(&text, &path.display().to_string(), |name| load_mtl(&base_dir.join(name)))
+}
+
+/// Parses OBJ text; `source` names it in errors. `mtllib` names are resolved with
+/// `load_materials`, and a library that fails to load only produces a warning.
+pub fn parse_obj(
+    text: &str,
+    source: &str,
+    mut load_materials: impl FnMut(&str) -> Result<HashMap<String, [f32; 4]>, String>,
+) -> Result<Model, String> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 4]> = Vec::new();
+    let mut texcoords: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut materials: HashMap<String, [f32; 4]> = HashMap::new();
+
+    let mut primitives: Vec<MeshData> = Vec::new();
+    let mut current = MeshData::new([1.0; 4]);
+    // Deduplicates (position, texcoord, normal) triples within the current primitive
+    let mut vertex_cache: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+
+    for (line_no, raw_line) in text.lines().enumerate() {
+        let line = raw_line.split('#').next().unwrap_or("").trim();
+        let mut parts = line.split_whitespace();
+        let keyword = match parts.next() { Some(k) => k, None => continue };
+        let args: Vec<&str> = parts.collect();
+        let err = |msg: &str| format!("{}:{}: {}", source, line_no + 1, msg);
+
+        match keyword {
+            "v" => {
+                let f = parse_floats(&args).ok_or_else(|| err("invalid vertex"))?;
+                if f.len() < 3 { return Err(err("vertex needs 3 components")); }
+                positions.push([f[0], f[1], f[2]]);
+                // Common extension: per-vertex colors after the position
+                colors.push(if f.len() >= 6 { [f[3], f[4], f[5], 1.0] } else { [1.0; 4] });
+            }
+            "vt" => {
+                let f = parse_floats(&args).ok_or_else(|| err("invalid texcoord"))?;
+                texcoords.push([f.first().copied().unwrap_or(0.0), f.get(1).copied().unwrap_or(0.0)]);
+            }
+            "vn" => {
+                let f = parse_floats(&args).ok_or_else(|| err("invalid normal"))?;
+                if f.len() < 3 { return Err(err("normal needs 3 components")); }
+                normals.push([f[0], f[1], f[2]]);
+            }
+            "mtllib" => {
+                for name in &args {
+                    match load_materials(name) {
+                        Ok(parsed) => materials.extend(parsed),
+                        Err(e) => eprintln!("Warning: {}", e),
+                    }
+                }
+            }
+            "usemtl" => {
+                let color = args.first().and_then(|name| materials.get(*name)).copied().unwrap_or([1.0; 4]);
+                if !current.indices.is_empty() {
+                    primitives.push(std::mem::replace(&mut current, MeshData::new(color)));
+                } else {
+                    current.base_color = color;
+                }
+                vertex_cache.clear();
+            }
+            "f" => {
+                if args.len() < 3 { return Err(err("face needs at least 3 vertices")); }
+                let mut face = Vec::with_capacity(args.len());
+                for token in &args {
+                    let mut refs = token.split('/');
+                    let v = resolve_obj_index(refs.next(), positions.len()).ok_or_else(|| err("invalid position index"))?;
+                    let vt = resolve_obj_index(refs.next(), texcoords.len());
+                    let vn = resolve_obj_index(refs.next(), normals.len());
+                    face.push((v, vt, vn));
+                }
+
+                // Faces without normals get a flat normal and unshared vertices
+                let flat_normal = {
+                    let a = Vec3::from(positions[face[0].0]);
+                    let b = Vec3::from(positions[face[1].0]);
+                    let c = Vec3::from(positions[face[2].0]);
+                    (b - a).cross(c - a).normalize_or_zero().to_array()
+                };
+
+                let mut face_indices = Vec::with_capacity(face.len());
+                for &(v, vt, vn) in &face {
+                    let uv = vt.map(|i| texcoords[i]).unwrap_or([0.0, 0.0]);
+                    let index = match vn {
+                        Some(n) => *vertex_cache.entry((v, vt, Some(n))).or_insert_with(|| {
+                            current.push_vertex(positions[v], normals[n], uv, colors[v])
+                        }),
+                        None => current.push_vertex(positions[v], flat_normal, uv, colors[v]),
+                    };
+                    face_indices.push(index);
+                }
+                for i in 1..face_indices.len() - 1 {
+                    current.indices.extend_from_slice(&[face_indices[0], face_indices[i], face_indices[i + 1]]);
+                }
+            }
+            _ => {} // o, g, s and friends don't affect geometry
+        }
+    }
+
+    if !current.indices.is_empty() { primitives.push(current); }
+    if primitives.is_empty() {
+        return Err(format!("OBJ {} contains no faces", source));
+    }
+    Ok(Model { primitives })
+}
+
+/// Reads the diffuse color of each material in a `.mtl` file.
+fn load_mtl(path: &Path) -> Result<HashMap<String, [f32; 4]>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read material library {}: {}", path.display(), e))?;
+    Ok(parse_mtl(&text))
+}
+
+/// Parses the diffuse color of each material in `.mtl` text.
+fn parse_mtl(text: &str) -> HashMap<String, [f32; 4]> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, [f32; 4])> = None;
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let args: Vec<&str>;
+        match parts.next() {
+            Some("newmtl") => {
+                if let Some((name, color)) = current.take() { materials.insert(name, color); }
+                current = Some((parts.collect::<Vec<_>>().join(" "), [1.0; 4]));
+            }
+            Some("Kd") => {
+                args = parts.collect();
+                if let (Some((_, color)), Some(f)) = (current.as_mut(), parse_floats(&args)) {
+                    if f.len() >= 3 { color[0] = f[0]; color[1] = f[1]; color[2] = f[2]; }
+                }
+            }
+            Some("d") => {
+                args = parts.collect();
+                if let (Some((_, color)), Some(f)) = (current.as_mut(), parse_floats(&args)) {
+                    if let Some(&d) = f.first() { color[3] = d; }
+                }
+            }
+            Some("Tr") => {
+                args = parts.collect();
+                if let (Some((_, color)), Some(f)) = (current.as_mut(), parse_floats(&args)) {
+                    if let Some(&tr) = f.first() { color[3] = 1.0 - tr; }
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((name, color)) = current { materials.insert(name, color); }
+    materials
+}
+
+fn parse_floats(args: &[&str]) -> Option<Vec<f32>> {
+    args.iter().map(|a| a.parse::<f32>().ok()).collect()
+}
+
+/// Converts a 1-based (or negative, relative) OBJ index into a 0-based index.
+fn resolve_obj_index(token: Option<&str>, len: usize) -> Option<usize> {
+    let value: i64 = token.filter(|t| !t.is_empty())?.parse().ok()?;
+    let index = if value < 0 { len as i64 + value } else { value - 1 };
+    if index >= 0 && (index as usize) < len { Some(index as usize) } else { None }
+}
+
+// --- glTF 2.0 --------------------------------------------------------------
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_CHUNK_JSON: u32 = 0x4E4F534A;
+const GLB_CHUNK_BIN: u32 = 0x004E4942;
+const GLTF_MODE_TRIANGLES: usize = 4;
+
+/// Loads a glTF 2.0 file, either `.gltf` JSON (external or base64-embedded buffers)
+/// or binary `.glb`. Node transforms of the default scene are baked into the vertices.
+pub fn load_gltf<P: AsRef<Path>>(path: P) -> Result<Model, String> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read glTF {}: {}", path.display(), e))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_gltf(&bytes, &path.display().to_string(), |uri| {
+        std::fs::read(base_dir.join(uri)).map_err(|e| format!("Failed to read buffer {}: {}", uri, e))
+    })
+}
+
+/// Parses glTF JSON or GLB bytes; `source` names them in errors. Buffers other than
+/// base64 data URIs and the GLB BIN chunk are read with `read_uri`.
+pub fn parse_gltf(
+    bytes: &[u8],
+    source: &str,
+    mut read_uri: impl FnMut(&str) -> Result<Vec<u8>, String>,
+) -> Result<Model, String> {
+    let (doc, bin_chunk) = if bytes.starts_with(GLB_MAGIC) {
+        parse_glb(bytes)?
+    } else {
+        let text = std::str::from_utf8(bytes).map_err(|e| format!("glTF is not valid UTF-8: {}", e))?;
+        (Json::parse(text)?, None)
+    };
+
+    let mut buffers = Vec::new();
+    for (i, buffer) in doc.get("buffers").as_array().iter().enumerate() {
+        let data = match buffer.get("uri").as_str() {
+            Some(uri) if uri.starts_with("data:") => {
+                let encoded = uri.split_once(";base64,").map(|(_, d)| d)
+                    .ok_or_else(|| format!("Buffer {} uses an unsupported data URI", i))?;
+                decode_base64(encoded)?
+            }
+            Some(uri) => read_uri(uri)?,
+            None => bin_chunk.clone().ok_or_else(|| format!("Buffer {} has no uri and no GLB BIN chunk", i))?,
+        };
+        buffers.push(data);
+    }
+
+    let gltf = Gltf { doc: &doc, buffers: &buffers };
+    let mut primitives = Vec::new();
+
+    let scenes = doc.get("scenes").as_array();
+    if scenes.is_empty() {
+        // No scene graph: load every mesh untransformed
+        for mesh_index in 0..doc.get("meshes").as_array().len() {
+            gltf.load_mesh(mesh_index, Mat4::IDENTITY, &mut primitives)?;
+        }
+    } else {
+        let scene_index = doc.get("scene").as_usize().unwrap_or(0);
+        let scene = scenes.get(scene_index).ok_or("glTF default scene index out of range")?;
+        for node in scene.get("nodes").as_array() {
+            let node_index = node.as_usize().ok_or("Invalid scene node index")?;
+            gltf.load_node(node_index, Mat4::IDENTITY, &mut primitives, 0)?;
+        }
+    }
+
+    if primitives.is_empty() {
+        return Err(format!("glTF {} contains no triangle primitives", source));
+    }
+    Ok(Model { primitives })
+}
+
+/// Splits a GLB container into its JSON document and optional BIN chunk.
+fn parse_glb(bytes: &[u8]) -> Result<(Json, Option<Vec<u8>>), String> {
+    let read_u32 = |offset: usize| -> Result<u32, String> {
+        bytes.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| "Truncated GLB file".to_string())
+    };
+    let version = read_u32(4)?;
+    if version != 2 { return Err(format!("Unsupported GLB version {}", version)); }
+    let total_len = (read_u32(8)? as usize).min(bytes.len());
+
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= total_len {
+        let chunk_len = read_u32(offset)? as usize;
+        let chunk_type = read_u32(offset + 4)?;
+        let data = bytes.get(offset + 8..offset + 8 + chunk_len).ok_or("Truncated GLB chunk")?;
+        match chunk_type {
+            GLB_CHUNK_JSON => {
+                let text = std::str::from_utf8(data).map_err(|e| format!("GLB JSON is not valid UTF-8: {}", e))?;
+                json = Some(Json::parse(text)?);
+            }
+            GLB_CHUNK_BIN if bin.is_none() => bin = Some(data.to_vec()),
+            _ => {}
+        }
+        offset += 8 + chunk_len;
+    }
+    Ok((json.ok_or("GLB file has no JSON chunk")?, bin))
+}
+
+struct Gltf<'a> {
+    doc: &'a Json,
+    buffers: &'a [Vec<u8>],
+}
+
+impl<'a> Gltf<'a> {
+    fn load_node(&self, index: usize, parent: Mat4, out: &mut Vec<MeshData>, depth: usize) -> Result<(), String> {
+        if depth > 64 { return Err("glTF node hierarchy is too deep (cycle?)".to_string()); }
+        let node = self.doc.get("nodes").as_array().get(index).ok_or("Node index out of range")?;
+
+        let local = if let Some(m) = node.get("matrix").as_f32_vec().filter(|m| m.len() == 16) {
+            Mat4::from_cols_slice(&m)
+        } else {
+            let t = node.get("translation").as_f32_vec().filter(|v| v.len() == 3).unwrap_or_else(|| vec![0.0; 3]);
+            let r = node.get("rotation").as_f32_vec().filter(|v| v.len() == 4).unwrap_or_else(|| vec![0.0, 0.0, 0.0, 1.0]);
+            let s = node.get("scale").as_f32_vec().filter(|v| v.len() == 3).unwrap_or_else(|| vec![1.0; 3]);
+            Mat4::from_scale_rotation_translation(
+                Vec3::new(s[0], s[1], s[2]),
+                Quat::from_xyzw(r[0], r[1], r[2], r[3]).normalize(),
+                Vec3::new(t[0], t[1], t[2]),
+            )
+        };
+        let world = parent * local;
+
+        if let Some(mesh_index) = node.get("mesh").as_usize() {
+            self.load_mesh(mesh_index, world, out)?;
+        }
+        for child in node.get("children").as_array() {
+            let child_index = child.as_usize().ok_or("Invalid child node index")?;
+            self.load_node(child_index, world, out, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn load_mesh(&self, index: usize, transform: Mat4, out: &mut Vec<MeshData>) -> Result<(), String> {
+        let mesh = self.doc.get("meshes").as_array().get(index).ok_or("Mesh index out of range")?;
+        let normal_matrix = Mat3::from_mat4(transform).inverse().transpose();
+        // Mirroring transforms flip the winding order
+        let flip_winding = transform.determinant() < 0.0;
+
+        for primitive in mesh.get("primitives").as_array() {
+            let mode = primitive.get("mode").as_usize().unwrap_or(GLTF_MODE_TRIANGLES);
+            if mode != GLTF_MODE_TRIANGLES {
+                eprintln!("Warning: skipping glTF primitive with unsupported mode {}", mode);
+                continue;
+            }
+            let attributes = primitive.get("attributes");
+            let position_accessor = attributes.get("POSITION").as_usize().ok_or("Primitive has no POSITION")?;
+            let (positions, position_components) = self.read_floats(position_accessor)?;
+            if position_components != 3 {
+                return Err(format!("glTF POSITION must be VEC3, got {} components", position_components));
+            }
+            let vertex_count = positions.len() / 3;
+
+            let normals = self.read_attribute(attributes, "NORMAL", vertex_count, &[3])?.map(|(n, _)| n);
+            let uvs = self.read_attribute(attributes, "TEXCOORD_0", vertex_count, &[2])?.map(|(t, _)| t);
+            let colors = self.read_attribute(attributes, "COLOR_0", vertex_count, &[3, 4])?;
+
+            let mut indices = match primitive.get("indices").as_usize() {
+                Some(a) => self.read_indices(a)?,
+                None => (0..vertex_count as u32).collect(),
+            };
+            if indices.iter().any(|&i| i as usize >= vertex_count) {
+                return Err("glTF index out of range".to_string());
+            }
+            if flip_winding {
+                for tri in indices.chunks_exact_mut(3) { tri.swap(1, 2); }
+            }
+
+            let base_color = primitive.get("material").as_usize()
+                .and_then(|m| self.doc.get("materials").as_array().get(m))
+                .and_then(|m| m.get("pbrMetallicRoughness").get("baseColorFactor").as_f32_vec())
+                .filter(|c| c.len() == 4)
+                .map(|c| [c[0], c[1], c[2], c[3]])
+                .unwrap_or([1.0; 4]);
+
+            // Without normals, accumulate smooth per-vertex normals from the faces
+            let computed_normals = if normals.is_none() {
+                let mut acc = vec![Vec3::ZERO; vertex_count];
+                for tri in indices.chunks_exact(3) {
+                    let p = |i: u32| Vec3::from_slice(&positions[i as usize * 3..i as usize * 3 + 3]);
+                    let n = (p(tri[1]) - p(tri[0])).cross(p(tri[2]) - p(tri[0]));
+                    for &i in tri { acc[i as usize] += n; }
+                }
+                Some(acc)
+            } else {
+                None
+            };
+
+            let mut data = MeshData::new(base_color);
+            for v in 0..vertex_count {
+                let pos = transform.transform_point3(Vec3::from_slice(&positions[v * 3..v * 3 + 3]));
+                let local_normal = match (&normals, &computed_normals) {
+                    (Some(n), _) => Vec3::from_slice(&n[v * 3..v * 3 + 3]),
+                    (None, Some(acc)) => acc[v],
+                    _ => Vec3::Y,
+                };
+                let normal = (normal_matrix * local_normal).normalize_or_zero();
+                let uv = uvs.as_ref().map(|t| [t[v * 2], t[v * 2 + 1]]).unwrap_or([0.0, 0.0]);
+                let color = match &colors {
+                    Some((c, 4)) => [c[v * 4], c[v * 4 + 1], c[v * 4 + 2], c[v * 4 + 3]],
+                    Some((c, _)) => [c[v * 3], c[v * 3 + 1], c[v * 3 + 2], 1.0],
+                    None => [1.0; 4],
+                };
+                data.push_vertex(pos.to_array(), normal.to_array(), uv, color);
+            }
+            data.indices = indices;
+            out.push(data);
+        }
+        Ok(())
+    }
+
+    /// Reads an optional per-vertex attribute, checking that it has one element per vertex
+    /// and one of the `allowed` component counts so indexing it by vertex can't go out of bounds.
+    fn read_attribute(&self, attributes: &Json, name: &str, vertex_count: usize, allowed: &[usize])
+        -> Result<Option<(Vec<f32>, usize)>, String> {
+        let accessor = match attributes.get(name).as_usize() {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+        let (values, components) = self.read_floats(accessor)?;
+        if !allowed.contains(&components) {
+            return Err(format!("glTF {} has {} components per element, expected {:?}", name, components, allowed));
+        }
+        let count = values.len() / components;
+        if count != vertex_count {
+            return Err(format!("glTF {} has {} elements but POSITION has {}", name, count, vertex_count));
+        }
+        Ok(Some((values, components)))
+    }
+
+    /// Returns the raw bytes of an accessor's elements along with (count, element size, stride).
+    fn accessor_view(&self, index: usize) -> Result<(&'a Json, &'a [u8], usize, usize), String> {
+        let accessor = self.doc.get("accessors").as_array().get(index).ok_or("Accessor index out of range")?;
+        let view_index = accessor.get("bufferView").as_usize().ok_or("Sparse or empty accessors are not supported")?;
+        let view = self.doc.get("bufferViews").as_array().get(view_index).ok_or("Buffer view index out of range")?;
+        let buffer = self.buffers.get(view.get("buffer").as_usize().unwrap_or(0)).ok_or("Buffer index out of range")?;
+
+        let components = match accessor.get("type").as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT4") => 16,
+            other => return Err(format!("Unsupported accessor type {:?}", other)),
+        };
+        let component_size = match accessor.get("componentType").as_usize() {
+            Some(5120) | Some(5121) => 1,
+            Some(5122) | Some(5123) => 2,
+            Some(5125) | Some(5126) => 4,
+            other => return Err(format!("Unsupported component type {:?}", other)),
+        };
+        let element_size = components * component_size;
+        let stride = view.get("byteStride").as_usize().unwrap_or(element_size);
+        if stride < element_size {
+            return Err(format!("Buffer view stride {} is smaller than its {} byte elements", stride, element_size));
+        }
+        let count = accessor.get("count").as_usize().unwrap_or(0);
+        // Counts and offsets come straight from the file, so keep the arithmetic from overflowing
+        const PAST_END: &str = "Accessor reads past the end of its buffer";
+        if count > buffer.len() / stride + 1 { return Err(PAST_END.to_string()); }
+        let start = view.get("byteOffset").as_usize().unwrap_or(0)
+            .checked_add(accessor.get("byteOffset").as_usize().unwrap_or(0)).ok_or(PAST_END)?;
+        let end = match count {
+            0 => start,
+            _ => stride.checked_mul(count - 1)
+                .and_then(|n| n.checked_add(element_size))
+                .and_then(|n| n.checked_add(start))
+                .ok_or(PAST_END)?,
+        };
+        let bytes = buffer.get(start..end).ok_or(PAST_END)?;
+        Ok((accessor, bytes, components, stride))
+    }
+
+    /// Reads an accessor as floats, normalizing integer components when flagged.
+    fn read_floats(&self, index: usize) -> Result<(Vec<f32>, usize), String> {
+        let (accessor, bytes, components, stride) = self.accessor_view(index)?;
+        let component_type = accessor.get("componentType").as_usize().unwrap_or(5126);
+        let normalized = accessor.get("normalized").as_bool().unwrap_or(false);
+        let count = accessor.get("count").as_usize().unwrap_or(0);
+
+        let mut out = Vec::with_capacity(count * components);
+        for e in 0..count {
+            for c in 0..components {
+                let value = read_component(bytes, e * stride, c, component_type);
+                out.push(if normalized { normalize_component(value, component_type) } else { value as f32 });
+            }
+        }
+        Ok((out, components))
+    }
+
+    fn read_indices(&self, index: usize) -> Result<Vec<u32>, String> {
+        let (accessor, bytes, _, stride) = self.accessor_view(index)?;
+        let component_type = accessor.get("componentType").as_usize().unwrap_or(5125);
+        let count = accessor.get("count").as_usize().unwrap_or(0);
+        Ok((0..count).map(|e| read_component(bytes, e * stride, 0, component_type) as u32).collect())
+    }
+}
+
+/// Reads component `c` of the element starting at `offset` as a f64.
+fn read_component(bytes: &[u8], offset: usize, c: usize, component_type: usize) -> f64 {
+    match component_type {
+        5120 => bytes[offset + c] as i8 as f64,
+        5121 => bytes[offset + c] as f64,
+        5122 => { let o = offset + c * 2; i16::from_le_bytes([bytes[o], bytes[o + 1]]) as f64 }
+        5123 => { let o = offset + c * 2; u16::from_le_bytes([bytes[o], bytes[o + 1]]) as f64 }
+        5125 => { let o = offset + c * 4; u32::from_le_bytes([bytes[o], bytes[o + 1], bytes[o + 2], bytes[o + 3]]) as f64 }
+        _ => { let o = offset + c * 4; f32::from_le_bytes([bytes[o], bytes[o + 1], bytes[o + 2], bytes[o + 3]]) as f64 }
+    }
+}
+
+fn normalize_component(value: f64, component_type: usize) -> f32 {
+    let v = match component_type {
+        5120 => (value / 127.0).max(-1.0),
+        5121 => value / 255.0,
+        5122 => (value / 32767.0).max(-1.0),
+        5123 => value / 65535.0,
+        5125 => value / u32::MAX as f64,
+        _ => value,
+    };
+    v as f32
+}
+
+/// Decodes standard (RFC 4648) base64, ignoring whitespace and padding.
+fn decode_base64(input: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return Err(format!("Invalid base64 character '{}'", c as char)),
+        };
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A one-triangle mesh whose float buffer holds 12 floats, with `extra` accessors
+    /// appended after the VEC3 POSITION accessor (index 0) as `(type, count)`.
+    fn triangle_doc(extra: &[(&str, &str, usize)]) -> (Json, Vec<Vec<u8>>) {
+        let buffer: Vec<u8> = (0..12).flat_map(|i| (i as f32).to_le_bytes()).collect();
+        let mut accessors = vec![r#"{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}"#.to_string()];
+        let mut attributes = vec![r#""POSITION": 0"#.to_string()];
+        for (i, (name, ty, count)) in extra.iter().enumerate() {
+            accessors.push(format!(r#"{{"bufferView": 0, "componentType": 5126, "count": {}, "type": "{}"}}"#, count, ty));
+            attributes.push(format!(r#""{}": {}"#, name, i + 1));
+        }
+        let text = format!(
+            r#"{{"bufferViews": [{{"buffer": 0, "byteLength": 48}}], "accessors": [{}],
+                "meshes": [{{"primitives": [{{"attributes": {{{}}}}}]}}]}}"#,
+            accessors.join(","), attributes.join(","),
+        );
+        (Json::parse(&text).unwrap(), vec![buffer])
+    }
+
+    fn load(extra: &[(&str, &str, usize)]) -> Result<Vec<MeshData>, String> {
+        let (doc, buffers) = triangle_doc(extra);
+        let mut out = Vec::new();
+        Gltf { doc: &doc, buffers: &buffers }.load_mesh(0, Mat4::IDENTITY, &mut out)?;
+        Ok(out)
+    }
+
+    fn load_error(extra: &[(&str, &str, usize)]) -> String {
+        match load(extra) {
+            Ok(_) => panic!("{:?} should be rejected", extra),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn loads_matching_attributes() {
+        let meshes = load(&[("NORMAL", "VEC3", 3), ("TEXCOORD_0", "VEC2", 3), ("COLOR_0", "VEC4", 3)]).unwrap();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].vertex_count(), 3);
+        assert_eq!(meshes[0].indices, vec![0, 1, 2]);
+        assert!(load(&[("COLOR_0", "VEC3", 3)]).is_ok());
+    }
+
+    #[test]
+    fn rejects_attribute_count_mismatch() {
+        for name in ["NORMAL", "TEXCOORD_0"] {
+            let ty = if name == "NORMAL" { "VEC3" } else { "VEC2" };
+            assert!(load_error(&[(name, ty, 2)]).contains("elements"));
+        }
+        assert!(load_error(&[("COLOR_0", "VEC4", 1)]).contains("elements"));
+    }
+
+    #[test]
+    fn rejects_attribute_type_mismatch() {
+        assert!(load_error(&[("NORMAL", "VEC2", 3)]).contains("components"));
+        assert!(load_error(&[("TEXCOORD_0", "VEC3", 3)]).contains("components"));
+        assert!(load_error(&[("COLOR_0", "VEC2", 3)]).contains("components"));
+        assert!(load_error(&[("COLOR_0", "SCALAR", 3)]).contains("components"));
+    }
+
+    fn obj(text: &str) -> Result<Model, String> {
+        parse_obj(text, "test.obj", |name| Err(format!("no library {}", name)))
+    }
+
+    fn position(mesh: &MeshData, vertex: usize) -> [f32; 3] {
+        let v = &mesh.vertices[vertex * FLOATS_PER_VERTEX..];
+        [v[0], v[1], v[2]]
+    }
+
+    #[test]
+    fn obj_faces_are_fan_triangulated() {
+        let model = obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv -1 0.5 0\nf 1 2 3 4 5\n").unwrap();
+        let mesh = &model.primitives[0];
+        assert_eq!(mesh.vertex_count(), 5);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+        // No normals given: the face gets a flat one from its winding
+        assert_eq!(&mesh.vertices[3..6], &[0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn obj_resolves_relative_indices() {
+        let text = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0.5 0.25\nvn 0 0 1\nv 5 5 5\nf -4/-1/-1 -3/-1/-1 -2/-1/-1\nf 1//1 2//1 4//1\n";
+        let model = obj(text).unwrap();
+        let mesh = &model.primitives[0];
+        // Vertices with the same position and normal are shared across faces
+        assert_eq!(mesh.indices, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(position(mesh, 2), [0.0, 1.0, 0.0]);
+        assert_eq!(position(mesh, 5), [5.0, 5.0, 5.0]);
+        assert_eq!(&mesh.vertices[6..8], &[0.5, 0.25]);
+
+        let shared = obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nvn 0 0 1\nf 1//1 2//1 3//1\nf -3//-1 -1//-1 -2//-1\n").unwrap();
+        assert_eq!(shared.primitives[0].vertex_count(), 4);
+        assert_eq!(shared.primitives[0].indices, vec![0, 1, 2, 1, 3, 2]);
+    }
+
+    #[test]
+    fn obj_rejects_bad_faces() {
+        let error = obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").err().unwrap();
+        assert!(error.starts_with("test.obj:4:"), "{}", error);
+        assert!(obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 -4 2\n").is_err());
+        assert!(obj("v 0 0 0\nv 1 0 0\nf 1 2\n").is_err());
+        assert!(obj("v 0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn obj_materials_set_base_color() {
+        let text = "mtllib colors.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0 0.5 0.5 0.5\n\
+                    usemtl red\nf 1 2 3\nusemtl glass\nf 2 4 3\nusemtl missing\nf 1 2 4\n";
+        let mut requested = Vec::new();
+        let model = parse_obj(text, "test.obj", |name| {
+            requested.push(name.to_string());
+            Ok(parse_mtl("newmtl red\nKd 1 0 0\n\nnewmtl glass\nKd 0 0.5 1\nd 0.25\n"))
+        }).unwrap();
+        assert_eq!(requested, vec!["colors.mtl"]);
+        assert_eq!(model.primitives.len(), 3);
+        assert_eq!(model.primitives[0].base_color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(model.primitives[1].base_color, [0.0, 0.5, 1.0, 0.25]);
+        assert_eq!(model.primitives[2].base_color, [1.0; 4]);
+        // The base color is baked into the vertex colors, on top of any OBJ vertex color
+        let color = |mesh: &MeshData, v: usize| mesh.vertices[v * FLOATS_PER_VERTEX + 8..v * FLOATS_PER_VERTEX + 12].to_vec();
+        assert_eq!(color(&model.primitives[0], 0), vec![1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(color(&model.primitives[1], 1), vec![0.0, 0.25, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn mtl_transparency() {
+        let materials = parse_mtl("newmtl a\nKd 0.1 0.2 0.3\nTr 0.75\nnewmtl two words\n");
+        assert_eq!(materials["a"], [0.1, 0.2, 0.3, 0.25]);
+        assert_eq!(materials["two words"], [1.0; 4]);
+    }
+
+    /// Three VEC3 positions of a triangle, translated up one unit by the scene's only node.
+    const TRIANGLE_POSITIONS: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+
+    fn triangle_gltf(buffer: &str, count: &str) -> String {
+        format!(
+            r#"{{"asset": {{"version": "2.0"}}, "buffers": [{}],
+                "bufferViews": [{{"buffer": 0, "byteLength": 36}}],
+                "accessors": [{{"bufferView": 0, "componentType": 5126, "count": {}, "type": "VEC3"}}],
+                "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}}}]}}],
+                "nodes": [{{"mesh": 0, "translation": [0, 1, 0]}}], "scenes": [{{"nodes": [0]}}]}}"#,
+            buffer, count,
+        )
+    }
+
+    fn triangle_bytes() -> Vec<u8> {
+        TRIANGLE_POSITIONS.iter().flat_map(|f| f.to_le_bytes()).collect()
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        while !json.len().is_multiple_of(4) { json.push(b' '); }
+        let mut out = Vec::new();
+        out.extend_from_slice(GLB_MAGIC);
+        out.extend_from_slice(&2u32.to_le_bytes());
+        out.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        out.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+        out.extend_from_slice(&json);
+        out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        out.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        out.extend_from_slice(bin);
+        out
+    }
+
+    fn no_files(uri: &str) -> Result<Vec<u8>, String> { Err(format!("unexpected read of {}", uri)) }
+
+    fn assert_translated_triangle(model: &Model) {
+        let mesh = &model.primitives[0];
+        assert_eq!(mesh.vertex_count(), 3);
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert_eq!(position(mesh, 0), [0.0, 1.0, 0.0]);
+        assert_eq!(position(mesh, 2), [0.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn loads_glb_bin_chunk() {
+        let bytes = glb(&triangle_gltf(r#"{"byteLength": 36}"#, "3"), &triangle_bytes());
+        assert_translated_triangle(&parse_gltf(&bytes, "test.glb", no_files).unwrap());
+    }
+
+    #[test]
+    fn rejects_truncated_glb() {
+        let bytes = glb(&triangle_gltf(r#"{"byteLength": 36}"#, "3"), &triangle_bytes());
+        for len in [6, 11, 16, bytes.len() - 4] {
+            assert!(parse_gltf(&bytes[..len], "test.glb", no_files).is_err(), "{} bytes", len);
+        }
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 1;
+        assert!(parse_gltf(&wrong_version, "test.glb", no_files).is_err());
+    }
+
+    #[test]
+    fn loads_base64_and_external_buffers() {
+        const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let encoded: String = triangle_bytes().chunks(3).flat_map(|chunk| {
+            let n = (chunk[0] as u32) << 16 | (chunk[1] as u32) << 8 | chunk[2] as u32;
+            (0..4).map(move |i| BASE64[(n >> (18 - 6 * i) & 63) as usize] as char)
+        }).collect();
+        let embedded = triangle_gltf(&format!(r#"{{"byteLength": 36, "uri": "data:application/octet-stream;base64,{}"}}"#, encoded), "3");
+        assert_translated_triangle(&parse_gltf(embedded.as_bytes(), "test.gltf", no_files).unwrap());
+
+        let external = triangle_gltf(r#"{"byteLength": 36, "uri": "triangle.bin"}"#, "3");
+        let model = parse_gltf(external.as_bytes(), "test.gltf", |uri| {
+            assert_eq!(uri, "triangle.bin");
+            Ok(triangle_bytes())
+        }).unwrap();
+        assert_translated_triangle(&model);
+
+        let bad = triangle_gltf(r#"{"byteLength": 36, "uri": "data:text/plain,abc"}"#, "3");
+        assert!(parse_gltf(bad.as_bytes(), "test.gltf", no_files).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_accessors() {
+        for count in ["4", "1e30", "18446744073709551615"] {
+            let bytes = glb(&triangle_gltf(r#"{"byteLength": 36}"#, count), &triangle_bytes());
+            assert!(parse_gltf(&bytes, "test.glb", no_files).is_err(), "count {}", count);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use glam::Vec2;
+use crate::engine::constants::post;
+use crate::engine::framebuffer::{AttachmentFormat, Framebuffer, FramebufferDesc};
+use crate::engine::shader_loader::ReloadableShader;
+use crate::engine::shader_sources::{
+    FULLSCREEN_VERT, POST_BRIGHT_FRAG, POST_BLUR_FRAG, POST_BLOOM_FRAG, POST_TONEMAP_FRAG,
+    POST_GAMMA_FRAG, POST_FXAA_FRAG, POST_VIGNETTE_FRAG,
+};
+
+/// A post-processing pass, listed in the order the chain applies them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostEffect {
+    Bloom,
+    Tonemap,
+    Gamma,
+    Fxaa,
+    Vignette,
+}
+
+impl PostEffect {
+    pub const ALL: [PostEffect; 5] = [PostEffect::Bloom, PostEffect::Tonemap, PostEffect::Gamma, PostEffect::Fxaa, PostEffect::Vignette];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PostEffect::Bloom => "bloom",
+            PostEffect::Tonemap => "tonemapping",
+            PostEffect::Gamma => "gamma correction",
+            PostEffect::Fxaa => "FXAA",
+            PostEffect::Vignette => "vignette",
+        }
+    }
+}
+
+/// Which passes run and their parameters; can be changed at any time.
+#[derive(Debug, Clone)]
+pub struct PostSettings {
+    enabled: [bool; PostEffect::ALL.len()],
+    pub exposure: f32,
+    pub gamma: f32,
+    pub bloom_threshold: f32,
+    pub bloom_intensity: f32,
+    pub vignette_strength: f32,
+    pub vignette_radius: f32,
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        Self {
+            enabled: [true; PostEffect::ALL.len()],
+            exposure: post::EXPOSURE,
+            gamma: post::GAMMA,
+            bloom_threshold: post::BLOOM_THRESHOLD,
+            bloom_intensity: post::BLOOM_INTENSITY,
+            vignette_strength: post::VIGNETTE_STRENGTH,
+            vignette_radius: post::VIGNETTE_RADIUS,
+        }
+    }
+}
+
+impl PostSettings {
+    pub fn is_enabled(&self, effect: PostEffect) -> bool { self.enabled[effect as usize] }
+
+    pub fn set_enabled(&mut self, effect: PostEffect, enabled: bool) { self.enabled[effect as usize] = enabled; }
+
+    /// Flips an effect on or off and returns its new state.
+    pub fn toggle(&mut self, effect: PostEffect) -> bool {
+        self.enabled[effect as usize] = !self.enabled[effect as usize];
+        self.enabled[effect as usize]
+    }
+}
+
+/// One small shader program per pass.
+struct PostShaders {
+    bright: ReloadableShader,
+    blur: ReloadableShader,
+    bloom: ReloadableShader,
+    tonemap: ReloadableShader,
+    gamma: ReloadableShader,
+    fxaa: ReloadableShader,
+    vignette: ReloadableShader,
+}
+
+impl PostShaders {
+    unsafe fn load() -> Result<Self, String> {
+        let load = |frag: &str| ReloadableShader::load(FULLSCREEN_VERT, frag, &[]);
+        Ok(Self {
+            bright: load(POST_BRIGHT_FRAG)?,
+            blur: load(POST_BLUR_FRAG)?,
+            bloom: load(POST_BLOOM_FRAG)?,
+            tonemap: load(POST_TONEMAP_FRAG)?,
+            gamma: load(POST_GAMMA_FRAG)?,
+            fxaa: load(POST_FXAA_FRAG)?,
+            vignette: load(POST_VIGNETTE_FRAG)?,
+        })
+    }
+
+    fn all_mut(&mut self) -> [&mut ReloadableShader; 7] {
+        [&mut self.bright, &mut self.blur, &mut self.bloom, &mut self.tonemap, &mut self.gamma, &mut self.fxaa, &mut self.vignette]
+    }
+}
+
+/// Runs the enabled post effects over an HDR scene target, writing the result to the
+/// default framebuffer. Intermediate results ping-pong between two full-size targets;
+/// bloom blurs at half resolution.
+pub struct PostProcessor {
+    pub settings: PostSettings,
+    vao: u32,
+    targets: [Framebuffer; 2],
+    bloom_targets: [Framebuffer; 2],
+    shaders: PostShaders,
+}
+
+impl PostProcessor {
+    pub unsafe fn new(width: i32, height: i32) -> Result<Self, String> {
+        let full = || Framebuffer::new(FramebufferDesc::color_only(AttachmentFormat::RGBA16F), width, height);
+        let half = || Framebuffer::new(FramebufferDesc::color_only(AttachmentFormat::RGBA16F), (width / 2).max(1), (height / 2).max(1));
+        // The fullscreen triangle is generated in the vertex shader, but core profile still needs a VAO
+        let mut vao = 0;
+        gl::GenVertexArrays(1, &mut vao);
+        Ok(Self {
+            settings: PostSettings::default(),
+            vao,
+            targets: [full()?, full()?],
+            bloom_targets: [half()?, half()?],
+            shaders: PostShaders::load()?,
+        })
+    }
+
+    /// Resizes the intermediate targets to a new window framebuffer size.
+    pub unsafe fn resize(&mut self, width: i32, height: i32) -> Result<(), String> {
+        for target in &mut self.targets { target.resize(width, height)?; }
+        for target in &mut self.bloom_targets { target.resize((width / 2).max(1), (height / 2).max(1))?; }
+        Ok(())
+    }
+
+    /// Picks up shader edits from disk (dev builds only).
+    pub unsafe fn poll_shaders(&mut self) {
+        for shader in self.shaders.all_mut() { shader.poll(); }
+    }
+
+    /// Applies the enabled effects to `scene`'s first color attachment and draws the result
+    /// into the default framebuffer of size `output`.
+    pub unsafe fn run(&mut self, scene: &Framebuffer, output: (i32, i32)) {
+        let effects: Vec<PostEffect> = PostEffect::ALL.iter().copied().filter(|&e| self.settings.is_enabled(e)).collect();
+        if effects.is_empty() {
+            // Nothing to do: copy the scene straight to the window
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, scene.id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+            gl::BlitFramebuffer(0, 0, scene.width(), scene.height(), 0, 0, output.0, output.1, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+            Framebuffer::bind_default(output.0, output.1);
+            return;
+        }
+
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Disable(gl::BLEND);
+        gl::BindVertexArray(self.vao);
+        let mut input = scene.color_texture(0);
+        let mut next = 0;
+        for (i, &effect) in effects.iter().enumerate() {
+            if effect == PostEffect::Bloom {
+                self.blur_bright_parts(input);
+            }
+            let last = i + 1 == effects.len();
+            if last {
+                Framebuffer::bind_default(output.0, output.1);
+            } else {
+                self.targets[next].bind();
+            }
+            self.draw_effect(effect, input, output);
+            if !last {
+                input = self.targets[next].color_texture(0);
+                next ^= 1;
+            }
+        }
+        gl::BindVertexArray(0);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::Enable(gl::DEPTH_TEST);
+    }
+
+    /// Draws one effect reading `input` into the bound framebuffer.
+    unsafe fn draw_effect(&self, effect: PostEffect, input: u32, output: (i32, i32)) {
+        let s = &self.settings;
+        let shader = match effect {
+            PostEffect::Bloom => &self.shaders.bloom,
+            PostEffect::Tonemap => &self.shaders.tonemap,
+            PostEffect::Gamma => &self.shaders.gamma,
+            PostEffect::Fxaa => &self.shaders.fxaa,
+            PostEffect::Vignette => &self.shaders.vignette,
+        };
+        shader.use_program();
+        bind_texture(0, input);
+        shader.set_sampler("uInput", 0);
+        match effect {
+            PostEffect::Bloom => {
+                self.bloom_targets[0].bind_color(0, 1);
+                shader.set_sampler("uBloom", 1);
+                shader.set("uIntensity", &s.bloom_intensity);
+            }
+            PostEffect::Tonemap => shader.set("uExposure", &s.exposure),
+            PostEffect::Gamma => shader.set("uGamma", &s.gamma),
+            PostEffect::Fxaa => shader.set("uTexelSize", &Vec2::new(1.0 / output.0.max(1) as f32, 1.0 / output.1.max(1) as f32)),
+            PostEffect::Vignette => {
+                shader.set("uStrength", &s.vignette_strength);
+                shader.set("uRadius", &s.vignette_radius);
+            }
+        }
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+    }
+
+    /// Extracts the bright parts of `input` at half resolution and blurs them into `bloom_targets[0]`.
+    unsafe fn blur_bright_parts(&self, input: u32) {
+        let [a, b] = &self.bloom_targets;
+        a.bind();
+        self.shaders.bright.use_program();
+        bind_texture(0, input);
+        self.shaders.bright.set_sampler("uInput", 0);
+        self.shaders.bright.set("uThreshold", &self.settings.bloom_threshold);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+
+        let blur = &self.shaders.blur;
+        blur.use_program();
+        blur.set_sampler("uInput", 0);
+        let texel = Vec2::new(1.0 / a.width() as f32, 1.0 / a.height() as f32);
+        for _ in 0..post::BLOOM_BLUR_PASSES {
+            b.bind();
+            bind_texture(0, a.color_texture(0));
+            blur.set("uDirection", &Vec2::new(texel.x, 0.0));
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+            a.bind();
+            bind_texture(0, b.color_texture(0));
+            blur.set("uDirection", &Vec2::new(0.0, texel.y));
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+    }
+}
+
+impl Drop for PostProcessor {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteVertexArrays(1, &self.vao); }
+    }
+}
+
+unsafe fn bind_texture(unit: u32, texture: u32) {
+    gl::ActiveTexture(gl::TEXTURE0 + unit);
+    gl::BindTexture(gl::TEXTURE_2D, texture);
+}
@@ -0,0 +1,371 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+use glam::Vec4;
+use crate::engine::constants::{debug_hud, profiler};
+use crate::engine::text::TextRenderer;
+
+/// Frames of GPU timer queries in flight. Results are read this many frames after they were
+/// issued, by which point the GPU has finished them and reading doesn't stall.
+const GPU_FRAMES_IN_FLIGHT: usize = 2;
+
+const GRAPH_BACKGROUND: Vec4 = Vec4::new(0.0, 0.0, 0.0, 0.5);
+const TARGET_LINE_COLOR: Vec4 = Vec4::new(1.0, 1.0, 1.0, 0.6);
+/// Untimed CPU work in a frame (everything outside top-level scopes)
+const OTHER_COLOR: Vec4 = Vec4::new(0.4, 0.4, 0.4, 0.8);
+const SCOPE_COLORS: [Vec4; 8] = [
+    Vec4::new(0.30, 0.70, 1.00, 1.0),
+    Vec4::new(1.00, 0.60, 0.20, 1.0),
+    Vec4::new(0.40, 0.90, 0.40, 1.0),
+    Vec4::new(0.95, 0.35, 0.45, 1.0),
+    Vec4::new(0.80, 0.50, 1.00, 1.0),
+    Vec4::new(1.00, 0.90, 0.30, 1.0),
+    Vec4::new(0.30, 0.90, 0.85, 1.0),
+    Vec4::new(0.90, 0.90, 0.90, 1.0),
+];
+
+/// Which clock timed a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeline {
+    Cpu,
+    /// GL timer queries
+    Gpu,
+}
+
+/// One timed scope.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub name: &'static str,
+    pub timeline: Timeline,
+    /// Number of scopes of the same timeline it was nested in
+    pub depth: u32,
+    /// Seconds since the profiler was created. GPU clocks can't be compared with the CPU's,
+    /// so GPU samples are placed relative to the start of their frame instead.
+    pub start: f64,
+    /// Seconds
+    pub duration: f32,
+}
+
+/// Everything timed during one frame.
+#[derive(Debug, Clone)]
+pub struct FrameProfile {
+    pub frame: u64,
+    /// Seconds since the profiler was created
+    pub start: f64,
+    /// Wall time from this frame's `begin_frame` to the next
+    pub cpu_time: f32,
+    /// From the first GPU scope starting to the last one ending, if the results arrived in time
+    pub gpu_time: Option<f32>,
+    pub samples: Vec<Sample>,
+}
+
+impl FrameProfile {
+    /// Total time of the top-level scopes called `name` on `timeline`, in seconds.
+    pub fn scope_time(&self, timeline: Timeline, name: &str) -> f32 {
+        self.top_level(timeline).filter(|s| s.name == name).map(|s| s.duration).sum()
+    }
+
+    fn top_level(&self, timeline: Timeline) -> impl Iterator<Item = &Sample> {
+        self.samples.iter().filter(move |s| s.timeline == timeline && s.depth == 0)
+    }
+}
+
+/// Handle for an open CPU scope; pass it back to `Profiler::cpu_end`.
+#[must_use]
+pub struct CpuScope(usize);
+
+/// Handle for an open GPU scope; pass it back to `Profiler::gpu_end`.
+#[must_use]
+pub struct GpuScope(usize);
+
+/// Timestamp queries for one frame's GPU scopes, two per scope, reused every
+/// `GPU_FRAMES_IN_FLIGHT` frames.
+#[derive(Default)]
+struct GpuQuerySet {
+    queries: Vec<u32>,
+    scopes: Vec<(&'static str, u32)>,
+    /// The query most recently written this frame, which may belong to an outer scope
+    last_issued: Option<usize>,
+    /// The frame the queries belong to, waiting for their results
+    profile: Option<FrameProfile>,
+}
+
+impl GpuQuerySet {
+    /// Reads the timestamps into GPU samples, or `None` if the GPU isn't done with them yet.
+    unsafe fn read(&self, frame_start: f64) -> Option<Vec<Sample>> {
+        let Some(last) = self.last_issued else { return Some(Vec::new()) };
+        // Timestamps complete in order, so the last one issued being ready means they all are
+        let mut available = 0;
+        gl::GetQueryObjectiv(self.queries[last], gl::QUERY_RESULT_AVAILABLE, &mut available);
+        if available == 0 { return None; }
+
+        let mut timestamps = vec![0u64; self.scopes.len() * 2];
+        for (query, timestamp) in self.queries.iter().zip(timestamps.iter_mut()) {
+            gl::GetQueryObjectui64v(*query, gl::QUERY_RESULT, timestamp);
+        }
+        let first = timestamps[0];
+        let samples = self.scopes.iter().enumerate().map(|(i, &(name, depth))| {
+            let (begin, end) = (timestamps[i * 2], timestamps[i * 2 + 1]);
+            Sample {
+                name,
+                timeline: Timeline::Gpu,
+                depth,
+                start: frame_start + begin.saturating_sub(first) as f64 * 1e-9,
+                duration: end.saturating_sub(begin) as f32 * 1e-9,
+            }
+        }).collect();
+        Some(samples)
+    }
+}
+
+/// Frame profiler. CPU scopes are timed with `Instant`; GPU scopes with GL timestamp queries
+/// that are read back a couple of frames later, so finished frames reach `history` with a
+/// short delay. Scopes of each kind nest, and a scope's name can appear many times a frame.
+///
+/// ```ignore
+/// let scope = engine.profiler.cpu_begin("rebuild_dirty");
+/// world.rebuild_dirty();
+/// engine.profiler.cpu_end(scope);
+/// ```
+pub struct Profiler {
+    epoch: Instant,
+    current: Option<FrameProfile>,
+    open_cpu_scopes: u32,
+    open_gpu_scopes: u32,
+    gpu: [GpuQuerySet; GPU_FRAMES_IN_FLIGHT],
+    /// Completed frames, oldest first
+    history: VecDeque<FrameProfile>,
+    /// Frames kept for a trace export while capturing
+    capture: Option<Vec<FrameProfile>>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self { Self::new() }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            current: None,
+            open_cpu_scopes: 0,
+            open_gpu_scopes: 0,
+            gpu: Default::default(),
+            history: VecDeque::with_capacity(profiler::HISTORY),
+            capture: None,
+        }
+    }
+
+    fn now(&self) -> f64 { self.epoch.elapsed().as_secs_f64() }
+
+    /// Ends the previous frame and starts timing `frame`. Call once at the top of every frame.
+    pub unsafe fn begin_frame(&mut self, frame: u64) {
+        let now = self.now();
+        if let Some(mut profile) = self.current.take() {
+            profile.cpu_time = (now - profile.start) as f32;
+            let slot = profile.frame as usize % GPU_FRAMES_IN_FLIGHT;
+            self.gpu[slot].profile = Some(profile);
+        }
+        self.open_cpu_scopes = 0;
+        self.open_gpu_scopes = 0;
+
+        // This frame's query set was last used `GPU_FRAMES_IN_FLIGHT` frames ago; collect that frame
+        let slot = frame as usize % GPU_FRAMES_IN_FLIGHT;
+        if let Some(mut profile) = self.gpu[slot].profile.take() {
+            if let Some(samples) = self.gpu[slot].read(profile.start) {
+                let end = samples.iter().map(|s| s.start + s.duration as f64).fold(profile.start, f64::max);
+                profile.gpu_time = samples.first().map(|first| (end - first.start) as f32);
+                profile.samples.extend(samples);
+            }
+            self.finish(profile);
+        }
+        self.gpu[slot].scopes.clear();
+        self.gpu[slot].last_issued = None;
+
+        self.current = Some(FrameProfile { frame, start: now, cpu_time: 0.0, gpu_time: None, samples: Vec::new() });
+    }
+
+    fn finish(&mut self, profile: FrameProfile) {
+        if let Some(capture) = &mut self.capture { capture.push(profile.clone()); }
+        if self.history.len() == profiler::HISTORY { self.history.pop_front(); }
+        self.history.push_back(profile);
+    }
+
+    pub fn cpu_begin(&mut self, name: &'static str) -> CpuScope {
+        let start = self.now();
+        let depth = self.open_cpu_scopes;
+        let Some(current) = &mut self.current else { return CpuScope(usize::MAX) };
+        self.open_cpu_scopes += 1;
+        current.samples.push(Sample { name, timeline: Timeline::Cpu, depth, start, duration: 0.0 });
+        CpuScope(current.samples.len() - 1)
+    }
+
+    pub fn cpu_end(&mut self, scope: CpuScope) {
+        let now = self.now();
+        let Some(sample) = self.current.as_mut().and_then(|c| c.samples.get_mut(scope.0)) else { return };
+        sample.duration = (now - sample.start) as f32;
+        self.open_cpu_scopes = self.open_cpu_scopes.saturating_sub(1);
+    }
+
+    /// Starts timing GPU work submitted from now on, without waiting for the GPU.
+    pub unsafe fn gpu_begin(&mut self, name: &'static str) -> GpuScope {
+        let set = &mut self.gpu[self.current.as_ref().map_or(0, |c| c.frame as usize) % GPU_FRAMES_IN_FLIGHT];
+        let index = set.scopes.len();
+        if set.queries.len() < (index + 1) * 2 {
+            let mut queries = [0u32; 2];
+            gl::GenQueries(2, queries.as_mut_ptr());
+            set.queries.extend_from_slice(&queries);
+        }
+        gl::QueryCounter(set.queries[index * 2], gl::TIMESTAMP);
+        set.last_issued = Some(index * 2);
+        set.scopes.push((name, self.open_gpu_scopes));
+        self.open_gpu_scopes += 1;
+        GpuScope(index)
+    }
+
+    pub unsafe fn gpu_end(&mut self, scope: GpuScope) {
+        let set = &mut self.gpu[self.current.as_ref().map_or(0, |c| c.frame as usize) % GPU_FRAMES_IN_FLIGHT];
+        if let Some(&query) = set.queries.get(scope.0 * 2 + 1) {
+            gl::QueryCounter(query, gl::TIMESTAMP);
+            set.last_issued = Some(scope.0 * 2 + 1);
+        }
+        self.open_gpu_scopes = self.open_gpu_scopes.saturating_sub(1);
+    }
+
+    /// Completed frames, oldest first.
+    #[allow(dead_code)]
+    pub fn history(&self) -> impl Iterator<Item = &FrameProfile> { self.history.iter() }
+
+    /// Average CPU and GPU frame times over the history, in seconds.
+    pub fn average_frame_times(&self) -> (f32, Option<f32>) {
+        if self.history.is_empty() { return (0.0, None); }
+        let cpu = self.history.iter().map(|f| f.cpu_time).sum::<f32>() / self.history.len() as f32;
+        let gpu: Vec<f32> = self.history.iter().filter_map(|f| f.gpu_time).collect();
+        let gpu = if gpu.is_empty() { None } else { Some(gpu.iter().sum::<f32>() / gpu.len() as f32) };
+        (cpu, gpu)
+    }
+
+    /// Names of the top-level scopes on `timeline` in the history, in first-seen order.
+    fn scope_names(&self, timeline: Timeline) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        for sample in self.history.iter().flat_map(|f| f.top_level(timeline)) {
+            if !names.contains(&sample.name) { names.push(sample.name); }
+        }
+        names
+    }
+
+    /// Starts keeping every frame for `finish_capture`.
+    pub fn start_capture(&mut self) { self.capture = Some(Vec::new()); }
+
+    pub fn is_capturing(&self) -> bool { self.capture.is_some() }
+
+    /// Writes the frames captured since `start_capture` as Chrome trace-event JSON and returns
+    /// how many there were. The last couple of frames are still waiting for GPU results and are left out.
+    pub fn finish_capture(&mut self, path: &Path) -> Result<usize, String> {
+        let frames = self.capture.take().ok_or("No profile capture in progress")?;
+        write_chrome_trace(path, &frames).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(frames.len())
+    }
+
+    /// Draws stacked CPU and GPU frame time graphs with a legend in the bottom left corner.
+    pub fn render_graph(&self, text: &mut TextRenderer, screen_size: (i32, i32)) {
+        let margin = debug_hud::MARGIN;
+        let height = profiler::GRAPH_HEIGHT;
+        let width = profiler::HISTORY as f32 * profiler::BAR_WIDTH;
+        let gpu_y = screen_size.1 as f32 - margin - height;
+        let cpu_y = gpu_y - margin - height;
+        self.render_timeline(text, Timeline::Cpu, margin, cpu_y, width, height);
+        self.render_timeline(text, Timeline::Gpu, margin, gpu_y, width, height);
+    }
+
+    fn render_timeline(&self, text: &mut TextRenderer, timeline: Timeline, x: f32, y: f32, width: f32, height: f32) {
+        let pixels_per_second = height / (profiler::GRAPH_MAX_MS * 1e-3);
+        let names = self.scope_names(timeline);
+        text.rect(x, y, width, height, GRAPH_BACKGROUND);
+
+        // One stacked bar per frame, newest on the right
+        let mut bar_x = x + width - self.history.len() as f32 * profiler::BAR_WIDTH;
+        for frame in &self.history {
+            let mut top = y + height;
+            for (i, name) in names.iter().enumerate() {
+                let bar = (frame.scope_time(timeline, name) * pixels_per_second).min(top - y);
+                top -= bar;
+                text.rect(bar_x, top, profiler::BAR_WIDTH, bar, SCOPE_COLORS[i % SCOPE_COLORS.len()]);
+            }
+            if timeline == Timeline::Cpu {
+                let bar = (frame.cpu_time * pixels_per_second).min(height) - (y + height - top);
+                if bar > 0.0 { text.rect(bar_x, top - bar, profiler::BAR_WIDTH, bar, OTHER_COLOR); }
+            }
+            bar_x += profiler::BAR_WIDTH;
+        }
+        let target_y = y + height - profiler::TARGET_FRAME_MS * 1e-3 * pixels_per_second;
+        text.rect(x, target_y, width, 1.0, TARGET_LINE_COLOR);
+
+        // Legend: average time per scope over the history
+        let frames = self.history.len().max(1) as f32;
+        let title = match timeline {
+            Timeline::Cpu => format!("CPU {:.2} ms", self.average_frame_times().0 * 1e3),
+            Timeline::Gpu => match self.average_frame_times().1 {
+                Some(gpu) => format!("GPU {:.2} ms", gpu * 1e3),
+                None => "GPU -".to_string(),
+            },
+        };
+        let legend_x = x + width + debug_hud::MARGIN;
+        let line_height = text.line_height();
+        text.text(legend_x, y + text.scale, &title, TARGET_LINE_COLOR);
+        for (i, name) in names.iter().enumerate() {
+            let average = self.history.iter().map(|f| f.scope_time(timeline, name)).sum::<f32>() / frames;
+            let line_y = y + line_height * (i + 1) as f32;
+            if line_y + line_height > y + height { break; }
+            text.text(legend_x, line_y + text.scale, &format!("{} {:.2} ms", name, average * 1e3), SCOPE_COLORS[i % SCOPE_COLORS.len()]);
+        }
+    }
+}
+
+impl Drop for Profiler {
+    fn drop(&mut self) {
+        for set in &self.gpu {
+            if !set.queries.is_empty() {
+                unsafe { gl::DeleteQueries(set.queries.len() as i32, set.queries.as_ptr()); }
+            }
+        }
+    }
+}
+
+/// Writes frames in the Chrome trace-event format: one complete ("X") event per frame and
+/// per scope, CPU on one track and GPU on another, with times in microseconds.
+fn write_chrome_trace(path: &Path, frames: &[FrameProfile]) -> std::io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    writeln!(w, "{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[")?;
+    writeln!(w, "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":1,\"args\":{{\"name\":\"CPU\"}}}},")?;
+    write!(w, "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":2,\"args\":{{\"name\":\"GPU\"}}}}")?;
+    let event = |w: &mut BufWriter<File>, name: &str, tid: u32, start: f64, duration: f32| {
+        write!(w, ",\n{{\"name\":\"{}\",\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{:.3},\"dur\":{:.3}}}",
+            escape_json(name), tid, start * 1e6, duration as f64 * 1e6)
+    };
+    for frame in frames {
+        event(&mut w, &format!("frame {}", frame.frame), 1, frame.start, frame.cpu_time)?;
+        for sample in &frame.samples {
+            let tid = match sample.timeline { Timeline::Cpu => 1, Timeline::Gpu => 2 };
+            event(&mut w, sample.name, tid, sample.start, sample.duration)?;
+        }
+    }
+    writeln!(w, "\n]}}")?;
+    w.flush()
+}
+
+fn escape_json(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
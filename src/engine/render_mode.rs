@@ -0,0 +1,95 @@
+/// How terrain is drawn, for spotting meshing and culling bugs. Each mode other than
+/// `Normal` and `Wireframe` is a variant of the block shader compiled with `shader_define`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RenderMode {
+    #[default]
+    Normal,
+    Wireframe,
+    /// Surface normals as colors; faces with wrong winding are culled and leave holes
+    Normals,
+    /// A random color per chunk, to see chunk boundaries and LOD rings
+    ChunkColors,
+    /// Additive heatmap of how many fragments land on each pixel, depth test off
+    Overdraw,
+    /// Sun, ambient, face shading and shadows only, as a heat ramp
+    Lighting,
+    /// Lit and fogged as usual, but untextured
+    TextureOff,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 7] = [
+        RenderMode::Normal, RenderMode::Wireframe, RenderMode::Normals, RenderMode::ChunkColors,
+        RenderMode::Overdraw, RenderMode::Lighting, RenderMode::TextureOff,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Normal => "normal",
+            RenderMode::Wireframe => "wireframe",
+            RenderMode::Normals => "normals",
+            RenderMode::ChunkColors => "chunk colors",
+            RenderMode::Overdraw => "overdraw",
+            RenderMode::Lighting => "sun lighting",
+            RenderMode::TextureOff => "texture off",
+        }
+    }
+
+    /// The mode after this one, wrapping around.
+    pub fn next(self) -> RenderMode {
+        let index = RenderMode::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        RenderMode::ALL[(index + 1) % RenderMode::ALL.len()]
+    }
+
+    /// The define selecting this mode in `block_world.frag`, if it needs its own shader variant.
+    pub fn shader_define(self) -> Option<&'static str> {
+        match self {
+            RenderMode::Normal | RenderMode::Wireframe => None,
+            RenderMode::Normals => Some("DEBUG_NORMALS"),
+            RenderMode::ChunkColors => Some("DEBUG_CHUNK_COLORS"),
+            RenderMode::Overdraw => Some("DEBUG_OVERDRAW"),
+            RenderMode::Lighting => Some("DEBUG_LIGHTING"),
+            RenderMode::TextureOff => Some("DEBUG_TEXTURE_OFF"),
+        }
+    }
+
+    /// Whether the shader for this mode reads the sun and shadow uniforms.
+    pub fn is_lit(self) -> bool {
+        matches!(self, RenderMode::Normal | RenderMode::Wireframe | RenderMode::Lighting | RenderMode::TextureOff)
+    }
+
+    /// Whether the shader for this mode reads the fog uniforms.
+    pub fn is_fogged(self) -> bool {
+        matches!(self, RenderMode::Normal | RenderMode::Wireframe | RenderMode::TextureOff)
+    }
+
+    /// Whether the mode sets its own blending, so passes must leave it alone.
+    pub fn overrides_blending(self) -> bool { self == RenderMode::Overdraw }
+
+    /// Sets up the GL state this mode draws with.
+    pub unsafe fn begin(self) {
+        match self {
+            RenderMode::Wireframe => gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE),
+            RenderMode::Overdraw => {
+                gl::Disable(gl::DEPTH_TEST);
+                gl::DepthMask(gl::FALSE);
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::ONE, gl::ONE);
+            }
+            _ => {}
+        }
+    }
+
+    /// Restores the state changed by `begin`.
+    pub unsafe fn end(self) {
+        match self {
+            RenderMode::Wireframe => gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL),
+            RenderMode::Overdraw => {
+                gl::Disable(gl::BLEND);
+                gl::DepthMask(gl::TRUE);
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            _ => {}
+        }
+    }
+}
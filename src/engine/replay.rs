@@ -0,0 +1,399 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use glam::Vec3;
+use glfw::Action;
+use crate::engine::actions::{gamepad_axis_name, parse_gamepad_axis, InputSource};
+use crate::engine::input::InputEvent;
+
+/// First line of every replay file.
+const REPLAY_HEADER: &str = "oxidize-replay 2";
+
+/// Everything needed to reproduce one frame: its `dt` and the input events applied during it.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayFrame {
+    pub dt: f32,
+    pub events: Vec<InputEvent>,
+}
+
+/// State a recording starts from, restored before its first frame is replayed: the camera,
+/// `Engine::time` and whatever the game saves with `Game::save_replay_state`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplayStart {
+    pub camera_position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub time: f32,
+    pub cursor_captured: bool,
+    /// Game values by name
+    pub values: Vec<(String, f64)>,
+}
+
+impl ReplayStart {
+    /// Stores a game value; names can't contain whitespace.
+    pub fn set(&mut self, name: &str, value: f64) {
+        debug_assert!(!name.is_empty() && !name.contains(char::is_whitespace), "bad replay value name {:?}", name);
+        match self.values.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = value,
+            None => self.values.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.iter().find(|(n, _)| n == name).map(|&(_, value)| value)
+    }
+}
+
+/// Streams frames to a text file as they happen, so a crash still leaves a usable recording.
+/// The starting state comes first, then one `frame <dt>` line per frame, followed by one
+/// line per event:
+/// ```text
+/// camera 12.5 80.0 -3.25 1.5707964 -0.2
+/// time 42.0
+/// cursor_captured true
+/// value controller 1.0
+/// frame 0.016667
+/// key:W press
+/// cursor 512.5 300.25
+/// axis LeftX 0.25
+/// ```
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+    frames: u64,
+    started: bool,
+    /// Written ahead of the first frame's events
+    initial_events: Vec<InputEvent>,
+}
+
+impl InputRecorder {
+    /// Creates the file. `initial_events` (see `InputState::held_state_events`) are played
+    /// before the first frame's own events, for recordings started while input is held.
+    /// Call `start` before recording the first frame.
+    pub fn create(path: &Path, initial_events: Vec<InputEvent>) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", REPLAY_HEADER).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(Self { writer, frames: 0, started: false, initial_events })
+    }
+
+    /// Whether `start` has written the starting state yet.
+    pub fn has_started(&self) -> bool { self.started }
+
+    pub fn start(&mut self, start: &ReplayStart) -> Result<(), String> {
+        let write = |w: &mut BufWriter<File>| -> std::io::Result<()> {
+            let p = start.camera_position;
+            writeln!(w, "camera {:?} {:?} {:?} {:?} {:?}", p.x, p.y, p.z, start.yaw, start.pitch)?;
+            writeln!(w, "time {:?}", start.time)?;
+            writeln!(w, "cursor_captured {}", start.cursor_captured)?;
+            for (name, value) in &start.values { writeln!(w, "value {} {:?}", name, value)?; }
+            Ok(())
+        };
+        write(&mut self.writer).map_err(|e| format!("Failed to write input recording: {}", e))?;
+        self.started = true;
+        Ok(())
+    }
+
+    pub fn record(&mut self, dt: f32, events: &[InputEvent]) -> Result<(), String> {
+        if !self.started { return Err("Input recording has no starting state".to_string()); }
+        let initial = std::mem::take(&mut self.initial_events);
+        let write = |w: &mut BufWriter<File>| -> std::io::Result<()> {
+            // `{:?}` prints the shortest text that parses back to the same float
+            writeln!(w, "frame {:?}", dt)?;
+            for event in initial.iter().chain(events) { writeln!(w, "{}", format_event(event))?; }
+            Ok(())
+        };
+        write(&mut self.writer).map_err(|e| format!("Failed to write input recording: {}", e))?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Flushes the file and returns the number of frames recorded.
+    pub fn finish(mut self) -> Result<u64, String> {
+        self.writer.flush().map_err(|e| format!("Failed to write input recording: {}", e))?;
+        Ok(self.frames)
+    }
+}
+
+/// Plays a recording back frame by frame.
+pub struct InputReplay {
+    /// Taken by the engine when it restores the starting state
+    start: Option<ReplayStart>,
+    frames: Vec<ReplayFrame>,
+    next: usize,
+    /// Close the window once the last frame has played
+    pub quit_when_done: bool,
+}
+
+impl InputReplay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let (start, frames) = parse_replay(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Self { start: Some(start), frames, next: 0, quit_when_done: false })
+    }
+
+    /// The state to restore before the first frame; `None` once taken.
+    pub fn take_start(&mut self) -> Option<ReplayStart> { self.start.take() }
+
+    /// The next recorded frame, or `None` once the recording is over.
+    pub fn next_frame(&mut self) -> Option<&ReplayFrame> {
+        let frame = self.frames.get(self.next)?;
+        self.next += 1;
+        Some(frame)
+    }
+
+    pub fn frame_count(&self) -> usize { self.frames.len() }
+
+    /// Total recorded time in seconds.
+    pub fn duration(&self) -> f32 { self.frames.iter().map(|f| f.dt).sum() }
+}
+
+fn action_name(action: Action) -> &'static str {
+    match action {
+        Action::Press => "press",
+        Action::Release => "release",
+        Action::Repeat => "repeat",
+    }
+}
+
+fn format_event(event: &InputEvent) -> String {
+    match *event {
+        InputEvent::Key(key, action) => format!("{} {}", InputSource::Key(key), action_name(action)),
+        InputEvent::MouseButton(button, action) => format!("{} {}", InputSource::MouseButton(button), action_name(action)),
+        InputEvent::GamepadButton(button, action) => format!("{} {}", InputSource::GamepadButton(button), action_name(action)),
+        InputEvent::Cursor(x, y) => format!("cursor {:?} {:?}", x, y),
+        InputEvent::Scroll(x, y) => format!("scroll {:?} {:?}", x, y),
+        InputEvent::Char(c) => format!("char {}", c as u32),
+        InputEvent::GamepadAxis(axis, value) => format!("axis {} {:?}", gamepad_axis_name(axis), value),
+    }
+}
+
+fn parse_replay(text: &str) -> Result<(ReplayStart, Vec<ReplayFrame>), String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == REPLAY_HEADER => {}
+        _ => return Err(format!("Not a replay file (expected '{}')", REPLAY_HEADER)),
+    }
+
+    let mut start = ReplayStart::default();
+    let (mut has_camera, mut has_time) = (false, false);
+    let mut frames: Vec<ReplayFrame> = Vec::new();
+    for (line_no, line) in lines {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+        let err = |what: &str| format!("Line {}: {} in '{}'", line_no + 1, what, line);
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let number = |i: usize| -> Result<f64, String> {
+            parts.get(i).and_then(|p| p.parse().ok()).ok_or_else(|| err("bad number"))
+        };
+        let starting = |what: &str| -> Result<(), String> {
+            if frames.is_empty() { Ok(()) } else { Err(err(&format!("{} after the first frame", what))) }
+        };
+        let event = match parts[0] {
+            "frame" => {
+                if !has_camera || !has_time { return Err(err("frame before the starting camera and time")); }
+                frames.push(ReplayFrame { dt: number(1)? as f32, events: Vec::new() });
+                continue;
+            }
+            "camera" => {
+                starting("camera")?;
+                start.camera_position = Vec3::new(number(1)? as f32, number(2)? as f32, number(3)? as f32);
+                start.yaw = number(4)? as f32;
+                start.pitch = number(5)? as f32;
+                has_camera = true;
+                continue;
+            }
+            "time" => {
+                starting("time")?;
+                start.time = number(1)? as f32;
+                has_time = true;
+                continue;
+            }
+            "cursor_captured" => {
+                starting("cursor_captured")?;
+                start.cursor_captured = parts.get(1).and_then(|p| p.parse().ok()).ok_or_else(|| err("expected true or false"))?;
+                continue;
+            }
+            "value" => {
+                starting("value")?;
+                let name = parts.get(1).ok_or_else(|| err("missing value name"))?;
+                start.set(name, number(2)?);
+                continue;
+            }
+            "cursor" => InputEvent::Cursor(number(1)?, number(2)?),
+            "scroll" => InputEvent::Scroll(number(1)?, number(2)?),
+            "char" => InputEvent::Char(char::from_u32(number(1)? as u32).ok_or_else(|| err("bad character"))?),
+            "axis" => {
+                let axis = parts.get(1).and_then(|name| parse_gamepad_axis(name)).ok_or_else(|| err("unknown axis"))?;
+                InputEvent::GamepadAxis(axis, number(2)? as f32)
+            }
+            source => {
+                let action = match parts.get(1) {
+                    Some(&"press") => Action::Press,
+                    Some(&"release") => Action::Release,
+                    Some(&"repeat") => Action::Repeat,
+                    _ => return Err(err("expected press, release or repeat")),
+                };
+                match InputSource::parse(source).map_err(|e| err(&e))? {
+                    InputSource::Key(key) => InputEvent::Key(key, action),
+                    InputSource::MouseButton(button) => InputEvent::MouseButton(button, action),
+                    InputSource::GamepadButton(button) => InputEvent::GamepadButton(button, action),
+                    InputSource::GamepadAxis(..) => return Err(err("axes are recorded with 'axis'")),
+                }
+            }
+        };
+        frames.last_mut().ok_or_else(|| err("event before the first frame"))?.events.push(event);
+    }
+    Ok((start, frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glfw::{GamepadAxis, GamepadButton, Key, MouseButton};
+    use crate::engine::input::InputState;
+
+    /// Header and starting state for hand-written replays.
+    fn header() -> String { format!("{}\ncamera 0 0 0 0 0\ntime 0\n", REPLAY_HEADER) }
+
+    fn sample_start() -> ReplayStart {
+        let mut start = ReplayStart {
+            camera_position: Vec3::new(12.5, 80.1, -3.333_333_3),
+            yaw: std::f32::consts::FRAC_PI_2,
+            pitch: -0.2,
+            time: 1234.5,
+            cursor_captured: true,
+            values: Vec::new(),
+        };
+        start.set("controller", 2.0);
+        start.set("time_of_day", 0.1f32 as f64);
+        start
+    }
+
+    fn sample_events() -> Vec<InputEvent> {
+        vec![
+            InputEvent::Key(Key::W, Action::Press),
+            InputEvent::Key(Key::LeftShift, Action::Repeat),
+            InputEvent::Key(Key::W, Action::Release),
+            InputEvent::MouseButton(MouseButton::Button2, Action::Press),
+            InputEvent::GamepadButton(GamepadButton::ButtonA, Action::Release),
+            InputEvent::Cursor(512.5, 300.125),
+            InputEvent::Cursor(-0.1, 1e-7),
+            InputEvent::Scroll(0.0, -1.5),
+            InputEvent::Char('é'),
+            InputEvent::Char(' '),
+            InputEvent::GamepadAxis(GamepadAxis::AxisLeftX, 0.1),
+            InputEvent::GamepadAxis(GamepadAxis::AxisRightTrigger, -0.333_333_34),
+        ]
+    }
+
+    #[test]
+    fn events_round_trip() {
+        for event in sample_events() {
+            let text = format!("{}frame 0.5\n{}\n", header(), format_event(&event));
+            let (_, frames) = parse_replay(&text).unwrap();
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0].events, vec![event], "{}", format_event(&event));
+        }
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let dts = [1.0 / 60.0, 0.1, 1e-3];
+        let mut text = header();
+        for (i, dt) in dts.iter().enumerate() {
+            text.push_str(&format!("frame {:?}\n", dt));
+            for event in sample_events().iter().skip(i) { text.push_str(&format_event(event)); text.push('\n'); }
+        }
+        let (_, frames) = parse_replay(&text).unwrap();
+        assert_eq!(frames.len(), dts.len());
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.dt, dts[i]);
+            assert_eq!(frame.events, sample_events()[i..].to_vec());
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_replays() {
+        let bad = [
+            "key:W press\n",
+            "frame fast\n",
+            "frame 0.1\nkey:W hold\n",
+            "frame 0.1\naxis Sideways 0.5\n",
+            "frame 0.1\npad_axis:LeftX+ press\n",
+            "frame 0.1\ncursor 1\n",
+            "frame 0.1\ntime 3\n",
+            "cursor_captured maybe\nframe 0.1\n",
+            "value controller\nframe 0.1\n",
+        ];
+        for body in bad {
+            let text = header() + body;
+            assert!(parse_replay(&text).is_err(), "{:?} should not parse", text);
+        }
+        // Version 1 recordings have no starting state
+        assert!(parse_replay("oxidize-replay 1\nframe 0.1\n").is_err());
+        assert!(parse_replay(&format!("{}\nframe 0.1\n", REPLAY_HEADER)).is_err());
+        assert!(parse_replay(&format!("{}\ntime 0\nframe 0.1\n", REPLAY_HEADER)).is_err());
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("oxidize_replay_{}_{}.replay", name, std::process::id()))
+    }
+
+    #[test]
+    fn start_state_round_trips() {
+        let path = temp_path("start");
+        let mut recorder = InputRecorder::create(&path, Vec::new()).unwrap();
+        assert!(recorder.record(0.1, &[]).is_err());
+        recorder.start(&sample_start()).unwrap();
+        recorder.record(0.1, &[]).unwrap();
+        recorder.finish().unwrap();
+
+        let mut replay = InputReplay::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let start = replay.take_start().unwrap();
+        assert_eq!(start, sample_start());
+        assert_eq!(start.get("controller"), Some(2.0));
+        assert_eq!(start.get("time_of_day").map(|t| t as f32), Some(0.1));
+        assert_eq!(start.get("missing"), None);
+        assert!(replay.take_start().is_none());
+    }
+
+    #[test]
+    fn recorder_writes_initial_events_first() {
+        let path = temp_path("initial");
+        let initial = vec![InputEvent::Key(Key::W, Action::Press), InputEvent::Cursor(10.0, 20.0)];
+        let mut recorder = InputRecorder::create(&path, initial.clone()).unwrap();
+        recorder.start(&ReplayStart::default()).unwrap();
+        recorder.record(0.25, &[InputEvent::Key(Key::Space, Action::Press)]).unwrap();
+        recorder.record(0.5, &[]).unwrap();
+        assert_eq!(recorder.finish().unwrap(), 2);
+
+        let mut replay = InputReplay::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(replay.frame_count(), 2);
+        assert_eq!(replay.duration(), 0.75);
+        let first = replay.next_frame().unwrap().clone();
+        assert_eq!(first.events, [initial, vec![InputEvent::Key(Key::Space, Action::Press)]].concat());
+        assert!(replay.next_frame().unwrap().events.is_empty());
+        assert!(replay.next_frame().is_none());
+    }
+
+    #[test]
+    fn held_state_events_restore_input() {
+        let mut live = InputState::default();
+        for event in sample_events() { live.apply(event); }
+        live.apply(InputEvent::Key(Key::A, Action::Press));
+
+        let mut replayed = InputState::default();
+        for event in live.held_state_events() { replayed.apply(event); }
+        for key in [Key::W, Key::A, Key::LeftShift] {
+            assert_eq!(replayed.key(key).down, live.key(key).down, "{:?}", key);
+        }
+        assert!(replayed.mouse_button(MouseButton::Button2).down);
+        assert!(!replayed.gamepad_button(GamepadButton::ButtonA).down);
+        assert_eq!(replayed.gamepad_axis(GamepadAxis::AxisLeftX), live.gamepad_axis(GamepadAxis::AxisLeftX));
+        assert_eq!(replayed.cursor_position(), live.cursor_position());
+        // The snapshot sets the cursor without reporting movement
+        assert_eq!(replayed.cursor_delta(), glam::Vec2::ZERO);
+    }
+}
@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+use image::RgbaImage;
+use crate::engine::constants::screenshots;
+
+/// Reads the color of `framebuffer` (0 for the window's back buffer) into an opaque image,
+/// flipped so that its first row is the top of the screen.
+pub unsafe fn read_framebuffer(framebuffer: u32, width: i32, height: i32) -> RgbaImage {
+    gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer);
+    gl::ReadBuffer(if framebuffer == 0 { gl::BACK } else { gl::COLOR_ATTACHMENT0 });
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+    let (width, height) = (width.max(0) as u32, height.max(0) as u32);
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    gl::ReadPixels(0, 0, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _);
+    gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+
+    // Blending leaves arbitrary alpha behind; a screenshot shouldn't be see-through
+    for alpha in pixels.iter_mut().skip(3).step_by(4) { *alpha = 255; }
+    let mut image = RgbaImage::from_raw(width, height, pixels).expect("pixel buffer matches the image size");
+    // GL rows start at the bottom
+    image::imageops::flip_vertical_in_place(&mut image);
+    image
+}
+
+/// `dir/<prefix>_<n>.<extension>` for the first `n` not already taken.
+pub fn next_free_path(dir: &Path, prefix: &str, extension: &str) -> PathBuf {
+    (1..).map(|n| dir.join(format!("{}_{:04}{}", prefix, n, extension)))
+        .find(|path| !path.exists())
+        .expect("ran out of file names")
+}
+
+/// Encodes and saves PNGs on a background thread so capturing doesn't hitch the frame. The
+/// queue is short, so a long image sequence slows rendering down to the encoder's pace
+/// instead of piling frames up in memory.
+pub struct ImageWriter {
+    sender: Option<SyncSender<(PathBuf, RgbaImage)>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Default for ImageWriter {
+    fn default() -> Self { Self::new() }
+}
+
+impl ImageWriter {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<(PathBuf, RgbaImage)>(screenshots::WRITE_QUEUE);
+        let thread = thread::spawn(move || {
+            for (path, image) in receiver {
+                let result = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| image.save(&path).map_err(|e| e.to_string()));
+                if let Err(e) = result { eprintln!("Failed to save {}: {}", path.display(), e); }
+            }
+        });
+        Self { sender: Some(sender), thread: Some(thread) }
+    }
+
+    /// Queues `image` to be written to `path` as a PNG, creating its directory if needed.
+    pub fn save(&self, path: PathBuf, image: RgbaImage) {
+        if let Some(sender) = &self.sender {
+            if sender.send((path, image)).is_err() { eprintln!("Screenshot writer thread has stopped"); }
+        }
+    }
+}
+
+impl Drop for ImageWriter {
+    fn drop(&mut self) {
+        // Closing the queue lets the thread finish what's left and exit
+        self.sender.take();
+        if let Some(thread) = self.thread.take() { let _ = thread.join(); }
+    }
+}
+
+/// Writes every frame to numbered PNGs in a directory, for turning into a video.
+pub struct FrameSequence {
+    pub dir: PathBuf,
+    pub frames: u64,
+}
+
+impl FrameSequence {
+    pub fn new(dir: PathBuf) -> Self { Self { dir, frames: 0 } }
+
+    /// Path for the next frame, counting it.
+    pub fn next_path(&mut self) -> PathBuf {
+        let path = self.dir.join(format!("frame_{:06}.png", self.frames));
+        self.frames += 1;
+        path
+    }
+}
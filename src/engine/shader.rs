@@ -1,12 +1,54 @@
 use gl::types::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::ptr;
+use glam::{Mat3, Mat4, Vec2, Vec3, Vec4};
 
-pub struct ShaderProgram { pub id: u32 }
+/// A linked shader program with uniform locations and uniform block indices cached at link time.
+pub struct ShaderProgram {
+    pub id: u32,
+    uniforms: HashMap<String, i32>,
+    uniform_blocks: HashMap<String, u32>,
+    /// Names we have already warned about, so a bad name doesn't flood the console every frame
+    warned: RefCell<HashSet<String>>,
+}
+
+/// A value that can be uploaded to a uniform location.
+pub trait UniformValue {
+    unsafe fn upload(&self, location: i32);
+}
+
+impl UniformValue for f32 { unsafe fn upload(&self, loc: i32) { gl::Uniform1f(loc, *self); } }
+impl UniformValue for i32 { unsafe fn upload(&self, loc: i32) { gl::Uniform1i(loc, *self); } }
+impl UniformValue for u32 { unsafe fn upload(&self, loc: i32) { gl::Uniform1ui(loc, *self); } }
+impl UniformValue for bool { unsafe fn upload(&self, loc: i32) { gl::Uniform1i(loc, *self as i32); } }
+impl UniformValue for Vec2 { unsafe fn upload(&self, loc: i32) { gl::Uniform2f(loc, self.x, self.y); } }
+impl UniformValue for Vec3 { unsafe fn upload(&self, loc: i32) { gl::Uniform3f(loc, self.x, self.y, self.z); } }
+impl UniformValue for Vec4 { unsafe fn upload(&self, loc: i32) { gl::Uniform4f(loc, self.x, self.y, self.z, self.w); } }
+impl UniformValue for Mat3 {
+    unsafe fn upload(&self, loc: i32) { gl::UniformMatrix3fv(loc, 1, gl::FALSE, self.to_cols_array().as_ptr()); }
+}
+impl UniformValue for Mat4 {
+    unsafe fn upload(&self, loc: i32) { gl::UniformMatrix4fv(loc, 1, gl::FALSE, self.to_cols_array().as_ptr()); }
+}
+
+// Arrays upload from the location of element 0
+impl UniformValue for [f32] { unsafe fn upload(&self, loc: i32) { gl::Uniform1fv(loc, self.len() as i32, self.as_ptr()); } }
+impl UniformValue for [i32] { unsafe fn upload(&self, loc: i32) { gl::Uniform1iv(loc, self.len() as i32, self.as_ptr()); } }
+impl UniformValue for [Vec2] { unsafe fn upload(&self, loc: i32) { gl::Uniform2fv(loc, self.len() as i32, self.as_ptr() as *const f32); } }
+impl UniformValue for [Vec3] { unsafe fn upload(&self, loc: i32) { gl::Uniform3fv(loc, self.len() as i32, self.as_ptr() as *const f32); } }
+impl UniformValue for [Vec4] { unsafe fn upload(&self, loc: i32) { gl::Uniform4fv(loc, self.len() as i32, self.as_ptr() as *const f32); } }
+impl UniformValue for [Mat4] {
+    unsafe fn upload(&self, loc: i32) { gl::UniformMatrix4fv(loc, self.len() as i32, gl::FALSE, self.as_ptr() as *const f32); }
+}
 
 impl ShaderProgram {
     pub unsafe fn from_source(vertex_src: &str, fragment_src: &str) -> Result<Self, String> {
         let vs = compile_shader(vertex_src, gl::VERTEX_SHADER)?;
-        let fs = compile_shader(fragment_src, gl::FRAGMENT_SHADER)?;
+        let fs = match compile_shader(fragment_src, gl::FRAGMENT_SHADER) {
+            Ok(fs) => fs,
+            Err(e) => { gl::DeleteShader(vs); return Err(e); }
+        };
         let program = gl::CreateProgram();
         gl::AttachShader(program, vs);
         gl::AttachShader(program, fs);
@@ -21,32 +63,113 @@ impl ShaderProgram {
             let mut buf = vec![0u8; len as usize];
             gl::GetProgramInfoLog(program, len, ptr::null_mut(), buf.as_mut_ptr() as *mut _);
             buf.truncate(buf.iter().position(|&c| c == 0).unwrap_or(buf.len()));
+            gl::DeleteProgram(program);
             return Err(String::from_utf8_lossy(&buf).to_string());
         }
-        Ok(Self { id: program })
+        Ok(Self {
+            id: program,
+            uniforms: query_uniforms(program),
+            uniform_blocks: query_uniform_blocks(program),
+            warned: RefCell::new(HashSet::new()),
+        })
     }
     pub unsafe fn use_program(&self) { gl::UseProgram(self.id); }
-    pub unsafe fn set_mat4(&self, name: &str, mat: &glam::Mat4) {
-        let cname = std::ffi::CString::new(name).unwrap();
-        let loc = gl::GetUniformLocation(self.id, cname.as_ptr());
-        gl::UniformMatrix4fv(loc, 1, gl::FALSE, mat.to_cols_array().as_ptr());
+
+    /// Returns the cached location of an active uniform, warning once if it does not exist.
+    pub fn uniform_location(&self, name: &str) -> Option<i32> {
+        let loc = self.uniforms.get(name).copied();
+        if loc.is_none() { self.warn_once(name, "uniform"); }
+        loc
     }
-    pub unsafe fn set_vec3(&self, name: &str, v: &glam::Vec3) {
-        let cname = std::ffi::CString::new(name).unwrap();
-        let loc = gl::GetUniformLocation(self.id, cname.as_ptr());
-        gl::Uniform3f(loc, v.x, v.y, v.z);
+
+    /// Sets a uniform of any supported type. The program must be in use.
+    pub unsafe fn set<T: UniformValue + ?Sized>(&self, name: &str, value: &T) {
+        if let Some(loc) = self.uniform_location(name) { value.upload(loc); }
     }
-    pub unsafe fn set_int(&self, name: &str, value: i32) {
-        let cname = std::ffi::CString::new(name).unwrap();
-        let loc = gl::GetUniformLocation(self.id, cname.as_ptr());
-        gl::Uniform1i(loc, value);
+
+    /// Points a sampler uniform at a texture unit.
+    pub unsafe fn set_sampler(&self, name: &str, unit: u32) { self.set(name, &(unit as i32)); }
+
+    /// Connects a named uniform block to a uniform buffer binding point.
+    pub unsafe fn bind_uniform_block(&self, name: &str, binding: u32) {
+        match self.uniform_blocks.get(name) {
+            Some(&index) => gl::UniformBlockBinding(self.id, index, binding),
+            None => self.warn_once(name, "uniform block"),
+        }
     }
+
+    fn warn_once(&self, name: &str, kind: &str) {
+        if self.warned.borrow_mut().insert(name.to_string()) {
+            eprintln!("Warning: {} '{}' does not exist in shader program {} (misspelled or optimized out?)", kind, name, self.id);
+        }
+    }
+}
+
+/// Typed convenience setters over `set`.
+#[allow(dead_code)]
+impl ShaderProgram {
+    pub unsafe fn set_float(&self, name: &str, value: f32) { self.set(name, &value); }
+    pub unsafe fn set_int(&self, name: &str, value: i32) { self.set(name, &value); }
+    pub unsafe fn set_bool(&self, name: &str, value: bool) { self.set(name, &value); }
+    pub unsafe fn set_vec2(&self, name: &str, v: &Vec2) { self.set(name, v); }
+    pub unsafe fn set_vec3(&self, name: &str, v: &Vec3) { self.set(name, v); }
+    pub unsafe fn set_vec4(&self, name: &str, v: &Vec4) { self.set(name, v); }
+    pub unsafe fn set_mat3(&self, name: &str, mat: &Mat3) { self.set(name, mat); }
+    pub unsafe fn set_mat4(&self, name: &str, mat: &Mat4) { self.set(name, mat); }
+    pub unsafe fn set_float_array(&self, name: &str, values: &[f32]) { self.set(name, values); }
+    pub unsafe fn set_vec3_array(&self, name: &str, values: &[Vec3]) { self.set(name, values); }
+    pub unsafe fn set_vec4_array(&self, name: &str, values: &[Vec4]) { self.set(name, values); }
+    pub unsafe fn set_mat4_array(&self, name: &str, values: &[Mat4]) { self.set(name, values); }
 }
 
 impl Drop for ShaderProgram {
     fn drop(&mut self) { unsafe { gl::DeleteProgram(self.id); } }
 }
 
+/// Collects the locations of all active uniforms (including every element of arrays).
+unsafe fn query_uniforms(program: u32) -> HashMap<String, i32> {
+    let mut uniforms = HashMap::new();
+    let (mut count, mut max_len) = (0, 0);
+    gl::GetProgramiv(program, gl::ACTIVE_UNIFORMS, &mut count);
+    gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_len);
+    let mut buf = vec![0u8; max_len.max(1) as usize];
+    for i in 0..count as u32 {
+        let (mut len, mut size, mut ty) = (0, 0, 0);
+        gl::GetActiveUniform(program, i, buf.len() as i32, &mut len, &mut size, &mut ty, buf.as_mut_ptr() as *mut _);
+        let name = String::from_utf8_lossy(&buf[..len as usize]).to_string();
+        let loc = gl::GetUniformLocation(program, buf.as_ptr() as *const _);
+        if loc < 0 { continue; } // Members of uniform blocks have no location
+
+        // Arrays are reported as "name[0]"; register the bare name and each element
+        if let Some(base) = name.strip_suffix("[0]") {
+            uniforms.insert(base.to_string(), loc);
+            for element in 1..size {
+                let element_name = format!("{}[{}]", base, element);
+                let cname = std::ffi::CString::new(element_name.clone()).unwrap();
+                let element_loc = gl::GetUniformLocation(program, cname.as_ptr());
+                if element_loc >= 0 { uniforms.insert(element_name, element_loc); }
+            }
+        }
+        uniforms.insert(name, loc);
+    }
+    uniforms
+}
+
+unsafe fn query_uniform_blocks(program: u32) -> HashMap<String, u32> {
+    let mut blocks = HashMap::new();
+    let mut count = 0;
+    gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_BLOCKS, &mut count);
+    for i in 0..count as u32 {
+        let mut len = 0;
+        gl::GetActiveUniformBlockiv(program, i, gl::UNIFORM_BLOCK_NAME_LENGTH, &mut len);
+        let mut buf = vec![0u8; len.max(1) as usize];
+        let mut written = 0;
+        gl::GetActiveUniformBlockName(program, i, buf.len() as i32, &mut written, buf.as_mut_ptr() as *mut _);
+        blocks.insert(String::from_utf8_lossy(&buf[..written as usize]).to_string(), i);
+    }
+    blocks
+}
+
 unsafe fn compile_shader(src: &str, ty: GLenum) -> Result<u32, String> {
     let shader = gl::CreateShader(ty);
     let c_str = std::ffi::CString::new(src).unwrap();
@@ -60,7 +183,38 @@ unsafe fn compile_shader(src: &str, ty: GLenum) -> Result<u32, String> {
         let mut buf = vec![0u8; len as usize];
         gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf.as_mut_ptr() as *mut _);
         buf.truncate(buf.iter().position(|&c| c == 0).unwrap_or(buf.len()));
+        gl::DeleteShader(shader);
         return Err(String::from_utf8_lossy(&buf).to_string());
     }
     Ok(shader)
 }
+
+/// A uniform buffer object for std140 uniform blocks.
+pub struct UniformBuffer {
+    pub id: u32,
+    size: usize,
+}
+
+impl UniformBuffer {
+    /// Allocates a buffer of `size` bytes and attaches it to a binding point.
+    pub unsafe fn new(size: usize, binding: u32) -> Self {
+        let mut id = 0;
+        gl::GenBuffers(1, &mut id);
+        gl::BindBuffer(gl::UNIFORM_BUFFER, id);
+        gl::BufferData(gl::UNIFORM_BUFFER, size as isize, ptr::null(), gl::DYNAMIC_DRAW);
+        gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, id);
+        Self { id, size }
+    }
+
+    /// Replaces the buffer contents. `data` must already be laid out as std140.
+    pub unsafe fn update(&self, data: &[f32]) {
+        let bytes = std::mem::size_of_val(data);
+        assert!(bytes <= self.size, "uniform data larger than buffer");
+        gl::BindBuffer(gl::UNIFORM_BUFFER, self.id);
+        gl::BufferSubData(gl::UNIFORM_BUFFER, 0, bytes as isize, data.as_ptr() as *const _);
+    }
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) { unsafe { gl::DeleteBuffers(1, &self.id); } }
+}
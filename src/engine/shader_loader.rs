@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use crate::engine::constants::{SHADER_ASSET_DIR, SHADER_DIR_ENV, SHADER_RELOAD_INTERVAL_SECS};
+use crate::engine::shader::ShaderProgram;
+use crate::engine::shader_sources::embedded_shader;
+
+/// Where shader files are read from.
+#[derive(Debug, Clone)]
+pub enum ShaderSourceDir {
+    /// Sources compiled into the binary (release builds)
+    Embedded,
+    /// Sources read from a directory and watched for changes (dev mode)
+    Disk(PathBuf),
+}
+
+impl ShaderSourceDir {
+    /// Picks the shader directory: `$OXIDIZE_SHADER_DIR` if set, `assets/shaders` in debug builds
+    /// when it exists, otherwise the embedded copies.
+    pub fn detect() -> Self {
+        if let Ok(dir) = std::env::var(SHADER_DIR_ENV) {
+            return ShaderSourceDir::Disk(PathBuf::from(dir));
+        }
+        if cfg!(debug_assertions) && Path::new(SHADER_ASSET_DIR).is_dir() {
+            return ShaderSourceDir::Disk(PathBuf::from(SHADER_ASSET_DIR));
+        }
+        ShaderSourceDir::Embedded
+    }
+
+    fn read(&self, name: &str) -> Result<String, String> {
+        match self {
+            ShaderSourceDir::Embedded => embedded_shader(name)
+                .map(str::to_string)
+                .ok_or_else(|| format!("No embedded shader named '{}'", name)),
+            ShaderSourceDir::Disk(dir) => std::fs::read_to_string(dir.join(name))
+                .map_err(|e| format!("Failed to read shader {}: {}", dir.join(name).display(), e)),
+        }
+    }
+
+    fn path(&self, name: &str) -> Option<PathBuf> {
+        match self {
+            ShaderSourceDir::Embedded => None,
+            ShaderSourceDir::Disk(dir) => Some(dir.join(name)),
+        }
+    }
+}
+
+/// A fully expanded shader stage plus the files it was built from.
+/// `files[i]` is the file GLSL reports as source string `i` in error logs.
+#[derive(Debug)]
+pub struct PreprocessedSource {
+    pub source: String,
+    pub files: Vec<String>,
+}
+
+/// Expands `#include "file"` directives (each file at most once) and injects `#define`s after `#version`.
+/// `#line` directives keep compiler error line numbers pointing at the original files.
+pub fn preprocess(dir: &ShaderSourceDir, name: &str, defines: &[(String, String)]) -> Result<PreprocessedSource, String> {
+    let mut out = PreprocessedSource { source: String::new(), files: Vec::new() };
+    let mut included = HashSet::new();
+    expand(dir, name, defines, &mut out, &mut included, 0)?;
+    Ok(out)
+}
+
+fn expand(
+    dir: &ShaderSourceDir,
+    name: &str,
+    defines: &[(String, String)],
+    out: &mut PreprocessedSource,
+    included: &mut HashSet<String>,
+    depth: usize,
+) -> Result<(), String> {
+    if depth > 16 { return Err(format!("Include depth exceeded while including '{}'", name)); }
+    if !included.insert(name.to_string()) { return Ok(()); }
+
+    let text = dir.read(name)?;
+    let file_index = out.files.len();
+    out.files.push(name.to_string());
+    if depth > 0 { out.source.push_str(&format!("#line 1 {}\n", file_index)); }
+
+    for (line_no, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("#version") {
+            out.source.push_str(line);
+            out.source.push('\n');
+            for (key, value) in defines {
+                out.source.push_str(&format!("#define {} {}\n", key, value));
+            }
+            out.source.push_str(&format!("#line {} {}\n", line_no + 2, file_index));
+        } else if let Some(rest) = trimmed.strip_prefix("#include") {
+            let include = rest.trim().trim_matches(|c| c == '"' || c == '<' || c == '>');
+            if include.is_empty() {
+                return Err(format!("{}:{}: malformed #include", name, line_no + 1));
+            }
+            expand(dir, include, defines, out, included, depth + 1)
+                .map_err(|e| format!("{}:{}: {}", name, line_no + 1, e))?;
+            out.source.push_str(&format!("#line {} {}\n", line_no + 2, file_index));
+        } else {
+            out.source.push_str(line);
+            out.source.push('\n');
+        }
+    }
+    Ok(())
+}
+
+/// Compiles a program from named vertex and fragment files with the given defines.
+pub unsafe fn compile_program(
+    dir: &ShaderSourceDir,
+    vertex: &str,
+    fragment: &str,
+    defines: &[(String, String)],
+) -> Result<(ShaderProgram, Vec<String>), String> {
+    let vs = preprocess(dir, vertex, defines)?;
+    let fs = preprocess(dir, fragment, defines)?;
+    let program = ShaderProgram::from_source(&vs.source, &fs.source).map_err(|log| {
+        format!(
+            "{} + {}:\n{}\n(source strings: vertex {:?}, fragment {:?})",
+            vertex, fragment, log.trim_end(), vs.files, fs.files,
+        )
+    })?;
+    let mut files = vs.files;
+    files.extend(fs.files);
+    files.sort();
+    files.dedup();
+    Ok((program, files))
+}
+
+/// A shader program that, when loaded from disk, recompiles itself when any of its files change.
+/// A failed recompile keeps the previous program running and reports the GLSL log.
+pub struct ReloadableShader {
+    program: ShaderProgram,
+    vertex: String,
+    fragment: String,
+    defines: Vec<(String, String)>,
+    dir: ShaderSourceDir,
+    watched: Vec<(PathBuf, Option<SystemTime>)>,
+    uniform_blocks: Vec<(String, u32)>,
+    last_poll: Instant,
+    /// Error from the most recent failed reload, cleared on success
+    pub last_error: Option<String>,
+}
+
+impl ReloadableShader {
+    /// Loads a program from the auto-detected shader directory.
+    pub unsafe fn load(vertex: &str, fragment: &str, defines: &[(&str, &str)]) -> Result<Self, String> {
+        Self::load_from(ShaderSourceDir::detect(), vertex, fragment, defines)
+    }
+
+    pub unsafe fn load_from(dir: ShaderSourceDir, vertex: &str, fragment: &str, defines: &[(&str, &str)]) -> Result<Self, String> {
+        let defines: Vec<(String, String)> = defines.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let (program, files) = compile_program(&dir, vertex, fragment, &defines)?;
+        let mut shader = Self {
+            program,
+            vertex: vertex.to_string(),
+            fragment: fragment.to_string(),
+            defines,
+            dir,
+            watched: Vec::new(),
+            uniform_blocks: Vec::new(),
+            last_poll: Instant::now(),
+            last_error: None,
+        };
+        shader.watch(&files);
+        Ok(shader)
+    }
+
+    /// Binds a uniform block now and again after every reload.
+    pub unsafe fn bind_uniform_block(&mut self, name: &str, binding: u32) {
+        self.program.bind_uniform_block(name, binding);
+        self.uniform_blocks.push((name.to_string(), binding));
+    }
+
+    /// Checks the watched files (at most every `SHADER_RELOAD_INTERVAL_SECS`) and recompiles on change.
+    /// Returns true if the program was replaced; callers must re-set any plain uniforms.
+    pub unsafe fn poll(&mut self) -> bool {
+        if self.watched.is_empty() || self.last_poll.elapsed() < Duration::from_secs_f32(SHADER_RELOAD_INTERVAL_SECS) {
+            return false;
+        }
+        self.last_poll = Instant::now();
+        let changed = self.watched.iter().any(|(path, mtime)| modified_time(path) != *mtime);
+        if !changed { return false; }
+        self.reload()
+    }
+
+    /// Recompiles from source immediately.
+    pub unsafe fn reload(&mut self) -> bool {
+        // Record the new timestamps up front so a broken file is only reported once per save
+        for (path, mtime) in &mut self.watched {
+            *mtime = modified_time(path);
+        }
+
+        match compile_program(&self.dir, &self.vertex, &self.fragment, &self.defines) {
+            Ok((program, files)) => {
+                for (name, binding) in &self.uniform_blocks {
+                    program.bind_uniform_block(name, *binding);
+                }
+                self.program = program;
+                self.watch(&files);
+                self.last_error = None;
+                println!("Reloaded shader {} + {}", self.vertex, self.fragment);
+                true
+            }
+            Err(e) => {
+                eprintln!("Shader reload failed, keeping previous program:\n{}", e);
+                self.last_error = Some(e);
+                false
+            }
+        }
+    }
+
+    fn watch(&mut self, files: &[String]) {
+        self.watched = files.iter()
+            .filter_map(|f| self.dir.path(f))
+            .map(|p| { let t = modified_time(&p); (p, t) })
+            .collect();
+    }
+}
+
+impl Deref for ReloadableShader {
+    type Target = ShaderProgram;
+    fn deref(&self) -> &ShaderProgram { &self.program }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defines(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    /// A scratch shader directory holding `files`, for cases the embedded shaders don't cover.
+    fn disk_dir(test: &str, files: &[(&str, &str)]) -> ShaderSourceDir {
+        let dir = std::env::temp_dir().join(format!("oxidize_shader_test_{}_{}", test, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, text) in files { std::fs::write(dir.join(name), text).unwrap(); }
+        ShaderSourceDir::Disk(dir)
+    }
+
+    #[test]
+    fn expands_includes_once() {
+        let out = preprocess(&ShaderSourceDir::Embedded, "water.frag", &[]).unwrap();
+        assert_eq!(out.files, vec!["water.frag", "camera.glsl", "lighting.glsl", "waves.glsl"]);
+        assert!(!out.source.contains("#include"));
+        assert_eq!(out.source.matches("uniform Camera").count(), 1);
+        // Each included file starts at its own line 1 and the parent resumes after the directive
+        assert!(out.source.contains("#line 1 1\n"));
+        assert!(out.source.contains("#line 8 0\n"));
+    }
+
+    #[test]
+    fn injects_defines_after_version() {
+        let out = preprocess(&ShaderSourceDir::Embedded, "sky.vert", &defines(&[("FOO", "1"), ("BAR", "2.0")])).unwrap();
+        let lines: Vec<&str> = out.source.lines().collect();
+        assert_eq!(lines[0], "#version 330 core");
+        assert_eq!(lines[1], "#define FOO 1");
+        assert_eq!(lines[2], "#define BAR 2.0");
+        assert_eq!(lines[3], "#line 2 0");
+        // Defines go in once even though the include has no #version of its own
+        assert_eq!(out.source.matches("#define FOO").count(), 1);
+    }
+
+    #[test]
+    fn missing_includes_report_the_including_line() {
+        let err = preprocess(&ShaderSourceDir::Embedded, "missing.frag", &[]).unwrap_err();
+        assert!(err.contains("missing.frag"));
+
+        let dir = disk_dir("missing", &[("main.frag", "#version 330 core\n\n#include \"nope.glsl\"\n")]);
+        let err = preprocess(&dir, "main.frag", &[]).unwrap_err();
+        assert!(err.starts_with("main.frag:3:"), "{}", err);
+        assert!(err.contains("nope.glsl"));
+    }
+
+    #[test]
+    fn include_cycles_terminate() {
+        let dir = disk_dir("cycle", &[
+            ("main.frag", "#version 330 core\n#include \"a.glsl\"\nvoid main() {}\n"),
+            ("a.glsl", "#include \"b.glsl\"\nfloat a;\n"),
+            ("b.glsl", "#include \"a.glsl\"\n#include \"main.frag\"\nfloat b;\n"),
+        ]);
+        let out = preprocess(&dir, "main.frag", &[]).unwrap();
+        assert_eq!(out.files, vec!["main.frag", "a.glsl", "b.glsl"]);
+        assert_eq!(out.source.matches("float a;").count(), 1);
+        assert_eq!(out.source.matches("void main").count(), 1);
+    }
+
+    #[test]
+    fn rejects_malformed_and_too_deep_includes() {
+        let dir = disk_dir("malformed", &[("main.frag", "#include \"\"\n")]);
+        assert!(preprocess(&dir, "main.frag", &[]).unwrap_err().contains("malformed #include"));
+
+        let chain: Vec<(String, String)> = (0..20)
+            .map(|i| (format!("f{}.glsl", i), format!("#include \"f{}.glsl\"\n", i + 1)))
+            .collect();
+        let files: Vec<(&str, &str)> = chain.iter().map(|(n, t)| (n.as_str(), t.as_str())).collect();
+        let dir = disk_dir("deep", &files);
+        assert!(preprocess(&dir, "f0.glsl", &[]).unwrap_err().contains("Include depth exceeded"));
+    }
+}
@@ -1,50 +1,60 @@
-pub const BLOCK_WORLD_VERT: &str = r#"#version 330 core
-layout (location = 0) in vec3 aPos;
-layout (location = 1) in vec3 aNormal;
-layout (location = 2) in vec2 aUV;
-layout (location = 3) in vec4 aColor;
-uniform mat4 uViewProj;
-uniform mat4 uModel;
-out vec4 vColor;
-out vec3 vWorldPos;
-out vec3 vNormal;
-out vec2 vUV;
-void main() {
-    vec4 world = uModel * vec4(aPos, 1.0);
-    vWorldPos = world.xyz;
-    vNormal = aNormal;
-    vColor = aColor;
-    vUV = aUV;
-    gl_Position = uViewProj * world;
-}"#;
+//! Shader sources embedded at compile time from `assets/shaders`.
+//! Programs are looked up by file name so the same names work when loading from disk in dev mode.
 
-pub const BLOCK_WORLD_FRAG: &str = r#"#version 330 core
-in vec4 vColor;
-in vec3 vWorldPos;
-in vec3 vNormal;
-in vec2 vUV;
-out vec4 FragColor;
-uniform vec3 uCameraPos;
-uniform sampler2D uTexture;
-const vec3 LIGHT_DIR = normalize(vec3(0.4, -0.8, 0.4));
-const vec3 LIGHT_COLOR = vec3(1.0, 0.98, 0.92);
-const float AMBIENT = 0.45;
-void main() {
-    // Sample texture
-    vec4 texColor = texture(uTexture, vUV);
-    
-    // Combine texture with vertex color (for tinting/variation)
-    vec4 baseColor = texColor * vColor;
-    
-    // Lighting
-    float diff = max(dot(normalize(vNormal), -LIGHT_DIR), 0.0);
-    vec3 lit = baseColor.rgb * (LIGHT_COLOR * (AMBIENT + diff * 0.6));
-    
-    // Distance fog
-    float dist = length(vWorldPos - uCameraPos);
-    float fog = clamp((dist - 100.0) / 120.0, 0.0, 1.0);
-    vec3 fogColor = vec3(0.6, 0.75, 0.95);
-    vec3 finalColor = mix(lit, fogColor, fog * 0.6);
-    
-    FragColor = vec4(finalColor, baseColor.a);
-}"#;
+pub const BLOCK_WORLD_VERT: &str = "block_world.vert";
+pub const BLOCK_WORLD_FRAG: &str = "block_world.frag";
+pub const ENTITY_VERT: &str = "entity.vert";
+pub const ENTITY_FRAG: &str = "entity.frag";
+pub const WATER_VERT: &str = "water.vert";
+pub const WATER_FRAG: &str = "water.frag";
+pub const SHADOW_DEPTH_VERT: &str = "shadow_depth.vert";
+pub const SHADOW_DEPTH_FRAG: &str = "shadow_depth.frag";
+pub const SKY_VERT: &str = "sky.vert";
+pub const SKY_FRAG: &str = "sky.frag";
+pub const TEXT_VERT: &str = "text.vert";
+pub const TEXT_FRAG: &str = "text.frag";
+pub const DEBUG_LINE_VERT: &str = "debug_line.vert";
+pub const DEBUG_LINE_FRAG: &str = "debug_line.frag";
+pub const FULLSCREEN_VERT: &str = "fullscreen.vert";
+pub const POST_BRIGHT_FRAG: &str = "post_bright.frag";
+pub const POST_BLUR_FRAG: &str = "post_blur.frag";
+pub const POST_BLOOM_FRAG: &str = "post_bloom.frag";
+pub const POST_TONEMAP_FRAG: &str = "post_tonemap.frag";
+pub const POST_GAMMA_FRAG: &str = "post_gamma.frag";
+pub const POST_FXAA_FRAG: &str = "post_fxaa.frag";
+pub const POST_VIGNETTE_FRAG: &str = "post_vignette.frag";
+
+/// Every shader file (programs and `#include`d snippets) by file name.
+pub const EMBEDDED_SHADERS: &[(&str, &str)] = &[
+    ("camera.glsl", include_str!("../../assets/shaders/camera.glsl")),
+    ("lighting.glsl", include_str!("../../assets/shaders/lighting.glsl")),
+    ("waves.glsl", include_str!("../../assets/shaders/waves.glsl")),
+    ("shadows.glsl", include_str!("../../assets/shaders/shadows.glsl")),
+    ("block_world.vert", include_str!("../../assets/shaders/block_world.vert")),
+    ("block_world.frag", include_str!("../../assets/shaders/block_world.frag")),
+    ("entity.vert", include_str!("../../assets/shaders/entity.vert")),
+    ("entity.frag", include_str!("../../assets/shaders/entity.frag")),
+    ("water.vert", include_str!("../../assets/shaders/water.vert")),
+    ("water.frag", include_str!("../../assets/shaders/water.frag")),
+    ("shadow_depth.vert", include_str!("../../assets/shaders/shadow_depth.vert")),
+    ("shadow_depth.frag", include_str!("../../assets/shaders/shadow_depth.frag")),
+    ("sky.vert", include_str!("../../assets/shaders/sky.vert")),
+    ("sky.frag", include_str!("../../assets/shaders/sky.frag")),
+    ("text.vert", include_str!("../../assets/shaders/text.vert")),
+    ("text.frag", include_str!("../../assets/shaders/text.frag")),
+    ("debug_line.vert", include_str!("../../assets/shaders/debug_line.vert")),
+    ("debug_line.frag", include_str!("../../assets/shaders/debug_line.frag")),
+    ("fullscreen.vert", include_str!("../../assets/shaders/fullscreen.vert")),
+    ("post_bright.frag", include_str!("../../assets/shaders/post_bright.frag")),
+    ("post_blur.frag", include_str!("../../assets/shaders/post_blur.frag")),
+    ("post_bloom.frag", include_str!("../../assets/shaders/post_bloom.frag")),
+    ("post_tonemap.frag", include_str!("../../assets/shaders/post_tonemap.frag")),
+    ("post_gamma.frag", include_str!("../../assets/shaders/post_gamma.frag")),
+    ("post_fxaa.frag", include_str!("../../assets/shaders/post_fxaa.frag")),
+    ("post_vignette.frag", include_str!("../../assets/shaders/post_vignette.frag")),
+];
+
+/// Returns the embedded source of a shader file.
+pub fn embedded_shader(name: &str) -> Option<&'static str> {
+    EMBEDDED_SHADERS.iter().find(|(n, _)| *n == name).map(|(_, src)| *src)
+}
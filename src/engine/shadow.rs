@@ -0,0 +1,198 @@
+use std::ptr;
+use glam::{Mat4, Vec3, Vec4};
+use crate::engine::camera::Camera;
+use crate::engine::constants::shadows;
+use crate::engine::shader::ShaderProgram;
+use crate::engine::world::World;
+
+/// Upper bound on cascades; matches `MAX_SHADOW_CASCADES` in `shadows.glsl`.
+pub const MAX_SHADOW_CASCADES: usize = 4;
+/// Texture unit the lit shaders sample the cascades from.
+pub const SHADOW_MAP_UNIT: u32 = 3;
+
+/// Runtime-adjustable shadow quality.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    /// Number of cascades, 1..=`MAX_SHADOW_CASCADES`
+    pub cascade_count: usize,
+    /// Width and height of each cascade's depth map in texels
+    pub resolution: i32,
+    /// View distance covered by the cascades
+    pub distance: f32,
+    /// Blend between logarithmic (1.0) and uniform (0.0) split distances
+    pub split_lambda: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cascade_count: shadows::CASCADE_COUNT,
+            resolution: shadows::RESOLUTION,
+            distance: shadows::DISTANCE,
+            split_lambda: shadows::SPLIT_LAMBDA,
+        }
+    }
+}
+
+/// One cascade: the sun's view-projection and the view distance where the next cascade takes over.
+#[derive(Debug, Clone, Copy)]
+pub struct Cascade {
+    pub view_proj: Mat4,
+    pub far: f32,
+}
+
+/// Cascaded shadow maps for the sun, stored as layers of one depth texture array.
+pub struct ShadowMap {
+    fbo: u32,
+    texture: u32,
+    /// (resolution, layers) of the allocated texture
+    allocated: (i32, usize),
+    settings: ShadowSettings,
+    cascades: Vec<Cascade>,
+}
+
+impl ShadowMap {
+    pub unsafe fn new(settings: ShadowSettings) -> Self {
+        let mut fbo = 0;
+        gl::GenFramebuffers(1, &mut fbo);
+        let mut map = Self { fbo, texture: 0, allocated: (0, 0), settings, cascades: Vec::new() };
+        map.set_settings(settings);
+        map
+    }
+
+    #[allow(dead_code)]
+    pub fn settings(&self) -> &ShadowSettings { &self.settings }
+
+    /// Applies new settings, reallocating the depth texture if its size changed.
+    pub unsafe fn set_settings(&mut self, mut settings: ShadowSettings) {
+        settings.cascade_count = settings.cascade_count.clamp(1, MAX_SHADOW_CASCADES);
+        settings.resolution = settings.resolution.max(64);
+        settings.distance = settings.distance.max(1.0);
+        self.settings = settings;
+        if self.allocated != (settings.resolution, settings.cascade_count) {
+            self.allocate();
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn cascades(&self) -> &[Cascade] { &self.cascades }
+
+    unsafe fn allocate(&mut self) {
+        if self.texture != 0 { gl::DeleteTextures(1, &self.texture); }
+        let (resolution, layers) = (self.settings.resolution, self.settings.cascade_count);
+        gl::GenTextures(1, &mut self.texture);
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.texture);
+        gl::TexImage3D(gl::TEXTURE_2D_ARRAY, 0, gl::DEPTH_COMPONENT24 as i32, resolution, resolution, layers as i32, 0,
+            gl::DEPTH_COMPONENT, gl::FLOAT, ptr::null());
+        // Hardware depth comparison with bilinear filtering gives a free 2x2 PCF per tap
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL as i32);
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as i32);
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as i32);
+        let border = [1.0f32; 4];
+        gl::TexParameterfv(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_BORDER_COLOR, border.as_ptr());
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        self.allocated = (resolution, layers);
+    }
+
+    /// Fits each cascade to its slice of the camera frustum as seen from a sun shining along
+    /// `light_dir`. Cascades are bounding spheres snapped to whole texels so shadows don't
+    /// shimmer as the camera moves or turns.
+    pub fn update(&mut self, camera: &Camera, light_dir: Vec3) {
+        let s = self.settings;
+        let near = camera.z_near;
+        let far = s.distance.min(camera.z_far);
+        let light_dir = light_dir.normalize();
+        let up = if light_dir.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+
+        self.cascades.clear();
+        let mut slice_near = near;
+        for i in 1..=s.cascade_count {
+            let t = i as f32 / s.cascade_count as f32;
+            let log_split = near * (far / near).powf(t);
+            let uniform_split = near + (far - near) * t;
+            let slice_far = s.split_lambda * log_split + (1.0 - s.split_lambda) * uniform_split;
+
+            let corners = camera.frustum_corners(slice_near, slice_far);
+            let center = corners.iter().copied().sum::<Vec3>() / 8.0;
+            let radius = corners.iter().map(|c| c.distance(center)).fold(0.0f32, f32::max);
+            let radius = (radius * 16.0).ceil() / 16.0;
+
+            let eye = center - light_dir * (radius + shadows::CASTER_MARGIN);
+            let view = Mat4::look_at_rh(eye, center, up);
+            let mut proj = Mat4::orthographic_rh_gl(-radius, radius, -radius, radius, 0.0, 2.0 * radius + shadows::CASTER_MARGIN);
+
+            // Snap the projection so world space maps onto the same texels every frame
+            let half_res = s.resolution as f32 * 0.5;
+            let origin = (proj * view).transform_point3(Vec3::ZERO) * half_res;
+            let offset = (origin.round() - origin) / half_res;
+            proj.w_axis += Vec4::new(offset.x, offset.y, 0.0, 0.0);
+
+            self.cascades.push(Cascade { view_proj: proj * view, far: slice_far });
+            slice_near = slice_far;
+        }
+    }
+
+    /// Renders the world's opaque chunks into every cascade with `shader` (which must take
+    /// `uLightViewProj`), then restores the previous framebuffer and the `viewport` size.
+    pub unsafe fn render(&mut self, world: &mut World, shader: &ShaderProgram, viewport: (i32, i32)) {
+        if !self.settings.enabled || self.cascades.is_empty() { return; }
+        let mut previous = 0;
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+        gl::DrawBuffer(gl::NONE);
+        gl::ReadBuffer(gl::NONE);
+        gl::Viewport(0, 0, self.settings.resolution, self.settings.resolution);
+        // Terrain meshes are one-sided surfaces, so both faces have to cast
+        gl::Disable(gl::CULL_FACE);
+        gl::Enable(gl::POLYGON_OFFSET_FILL);
+        gl::PolygonOffset(1.5, 3.0);
+
+        shader.use_program();
+        for (layer, cascade) in self.cascades.iter().enumerate() {
+            gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, self.texture, 0, layer as i32);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+            shader.set("uLightViewProj", &cascade.view_proj);
+            world.render_shadow_casters(cascade.view_proj);
+        }
+
+        gl::Disable(gl::POLYGON_OFFSET_FILL);
+        gl::Enable(gl::CULL_FACE);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+        gl::Viewport(0, 0, viewport.0, viewport.1);
+    }
+
+    /// Binds the cascades to `SHADOW_MAP_UNIT` and sets the sampling uniforms of a lit shader.
+    pub unsafe fn apply(&self, shader: &ShaderProgram) {
+        let enabled = self.settings.enabled && !self.cascades.is_empty();
+        gl::ActiveTexture(gl::TEXTURE0 + SHADOW_MAP_UNIT);
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.texture);
+        gl::ActiveTexture(gl::TEXTURE0);
+
+        let mut matrices = [Mat4::IDENTITY; MAX_SHADOW_CASCADES];
+        let mut splits = [0.0f32; MAX_SHADOW_CASCADES];
+        for (i, cascade) in self.cascades.iter().enumerate() {
+            matrices[i] = cascade.view_proj;
+            splits[i] = cascade.far;
+        }
+        shader.set_sampler("uShadowMap", SHADOW_MAP_UNIT);
+        shader.set("uShadowsEnabled", &enabled);
+        shader.set("uCascadeCount", &(self.cascades.len() as i32));
+        shader.set("uCascadeViewProj", &matrices[..]);
+        shader.set("uCascadeSplits", &Vec4::from(splits));
+        shader.set("uShadowTexelSize", &(1.0 / self.settings.resolution as f32));
+    }
+}
+
+impl Drop for ShadowMap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            if self.texture != 0 { gl::DeleteTextures(1, &self.texture); }
+        }
+    }
+}
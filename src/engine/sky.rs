@@ -0,0 +1,165 @@
+use glam::{Vec2, Vec3};
+use crate::engine::camera::CAMERA_UNIFORM_BINDING;
+use crate::engine::constants::sky;
+use crate::engine::fog::FogUniform;
+use crate::engine::lighting::SunUniform;
+use crate::engine::shader_loader::ReloadableShader;
+use crate::engine::shader_sources::{SKY_VERT, SKY_FRAG};
+
+/// Sky colors (linear) at full day, at sunset and at night.
+const DAY_ZENITH: Vec3 = Vec3::new(0.12, 0.32, 0.85);
+const DAY_HORIZON: Vec3 = Vec3::new(0.55, 0.70, 0.90);
+const SUNSET_HORIZON: Vec3 = Vec3::new(0.95, 0.45, 0.20);
+const NIGHT_ZENITH: Vec3 = Vec3::new(0.004, 0.007, 0.02);
+const NIGHT_HORIZON: Vec3 = Vec3::new(0.02, 0.03, 0.06);
+
+const SUN_NOON_COLOR: Vec3 = Vec3::new(1.0, 0.97, 0.9);
+const SUN_LOW_COLOR: Vec3 = Vec3::new(1.0, 0.55, 0.25);
+const MOON_LIGHT_COLOR: Vec3 = Vec3::new(0.18, 0.22, 0.32);
+
+/// Time of day and the sky, sun and fog derived from it.
+pub struct Sky {
+    /// 0..1 through the day: 0 = midnight, 0.25 = sunrise, 0.5 = noon, 0.75 = sunset
+    pub time_of_day: f32,
+    pub day_length_secs: f32,
+    pub paused: bool,
+    /// Fraction of the sky covered by clouds, 0..1
+    pub cloud_coverage: f32,
+    pub cloud_altitude: f32,
+    elapsed: f32,
+}
+
+/// Everything the renderers need from the sky for one frame.
+#[derive(Debug, Clone)]
+pub struct SkyState {
+    pub zenith: Vec3,
+    pub horizon: Vec3,
+    /// Unit vectors pointing towards the sun and the moon
+    pub sun_dir: Vec3,
+    pub moon_dir: Vec3,
+    /// Light from whichever body is up; drives lighting and shadows
+    pub light: SunUniform,
+    /// 0 by day, 1 in full night
+    pub star_visibility: f32,
+}
+
+impl Default for Sky {
+    fn default() -> Self { Self::new() }
+}
+
+impl Sky {
+    pub fn new() -> Self {
+        Self {
+            time_of_day: sky::START_TIME_OF_DAY,
+            day_length_secs: sky::DAY_LENGTH_SECS,
+            paused: false,
+            cloud_coverage: sky::CLOUD_COVERAGE,
+            cloud_altitude: sky::CLOUD_ALTITUDE,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advances the time of day and cloud drift.
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+        if !self.paused && self.day_length_secs > 0.0 {
+            self.time_of_day = (self.time_of_day + dt / self.day_length_secs).rem_euclid(1.0);
+        }
+    }
+
+    /// Direction towards the sun: rises in +X, sets in -X, tilted slightly south.
+    pub fn sun_direction(&self) -> Vec3 {
+        let angle = (self.time_of_day - 0.25) * std::f32::consts::TAU;
+        Vec3::new(angle.cos(), angle.sin(), 0.25).normalize()
+    }
+
+    pub fn state(&self) -> SkyState {
+        let sun_dir = self.sun_direction();
+        let moon_dir = -sun_dir;
+        let height = sun_dir.y;
+        let day = smoothstep(-0.15, 0.25, height);
+        // Strongest when the sun is right at the horizon
+        let sunset = (1.0 - (height / 0.3).abs()).clamp(0.0, 1.0).powi(2);
+
+        let zenith = NIGHT_ZENITH.lerp(DAY_ZENITH, day);
+        let horizon = NIGHT_HORIZON.lerp(DAY_HORIZON, day).lerp(SUNSET_HORIZON, sunset * 0.75);
+
+        // Light comes from whichever body is above the horizon, fading from sun to moon light
+        // through twilight so the scene never goes fully black
+        let direction = if height > -0.05 { -sun_dir } else { -moon_dir };
+        let color = MOON_LIGHT_COLOR.lerp(sun_disk_color(height), smoothstep(-0.05, 0.15, height));
+        let light = SunUniform { direction, color, ambient: 0.3 + 0.15 * day };
+
+        SkyState { zenith, horizon, sun_dir, moon_dir, light, star_visibility: 1.0 - smoothstep(-0.2, 0.05, height) }
+    }
+
+    /// Fog that fades terrain into the horizon color by `view_distance`.
+    pub fn fog(&self, state: &SkyState, view_distance: f32) -> FogUniform {
+        FogUniform::haze(state.horizon, view_distance)
+    }
+
+}
+
+/// Draws the sky dome (gradient, sun and moon, stars, clouds) as a fullscreen triangle.
+/// Call it first each frame: it writes no depth, so terrain simply draws over it.
+pub struct SkyRenderer {
+    shader: ReloadableShader,
+    vao: u32,
+}
+
+impl SkyRenderer {
+    pub unsafe fn new() -> Result<Self, String> {
+        let mut shader = ReloadableShader::load(SKY_VERT, SKY_FRAG, &[])?;
+        shader.bind_uniform_block("Camera", CAMERA_UNIFORM_BINDING);
+        // The triangle is generated in the vertex shader, but core profile still needs a VAO
+        let mut vao = 0;
+        gl::GenVertexArrays(1, &mut vao);
+        Ok(Self { shader, vao })
+    }
+
+    /// Picks up shader edits from disk (dev builds only).
+    pub unsafe fn poll_shader(&mut self) {
+        self.shader.poll();
+    }
+
+    /// Expects the `Camera` uniform block to be up to date.
+    pub unsafe fn render(&self, sky: &Sky, state: &SkyState) {
+        let shader = &self.shader;
+        shader.use_program();
+        shader.set("uZenithColor", &state.zenith);
+        shader.set("uHorizonColor", &state.horizon);
+        shader.set("uSunDir", &state.sun_dir);
+        shader.set("uMoonDir", &state.moon_dir);
+        shader.set("uSunColor", &sun_disk_color(state.sun_dir.y));
+        shader.set("uStarVisibility", &state.star_visibility);
+        shader.set("uTime", &sky.elapsed);
+        shader.set("uCloudCoverage", &sky.cloud_coverage);
+        shader.set("uCloudAltitude", &sky.cloud_altitude);
+        shader.set("uCloudScale", &sky::CLOUD_SCALE);
+        shader.set("uCloudWind", &Vec2::from(sky::CLOUD_WIND));
+
+        gl::Disable(gl::DEPTH_TEST);
+        gl::DepthMask(gl::FALSE);
+        gl::BindVertexArray(self.vao);
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+        gl::DepthMask(gl::TRUE);
+        gl::Enable(gl::DEPTH_TEST);
+    }
+}
+
+impl Drop for SkyRenderer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteVertexArrays(1, &self.vao); }
+    }
+}
+
+/// Color of the sun itself, reddening towards the horizon.
+fn sun_disk_color(height: f32) -> Vec3 {
+    SUN_LOW_COLOR.lerp(SUN_NOON_COLOR, smoothstep(0.0, 0.4, height))
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
@@ -0,0 +1,273 @@
+use std::mem;
+use std::ptr;
+use glam::{Vec2, Vec4};
+use crate::engine::shader_loader::ReloadableShader;
+use crate::engine::shader_sources::{TEXT_VERT, TEXT_FRAG};
+
+/// Glyph cell size in font pixels.
+const GLYPH_SIZE: i32 = 8;
+/// Glyphs per atlas row.
+const ATLAS_COLUMNS: i32 = 16;
+const FIRST_CHAR: u8 = b' ';
+/// Atlas cell that is solid white, used for rectangles.
+const SOLID_CELL: usize = FONT_8X8.len();
+const ATLAS_ROWS: i32 = (SOLID_CELL as i32 + ATLAS_COLUMNS) / ATLAS_COLUMNS;
+/// Floats per vertex: position(2) + uv(2) + color(4).
+const TEXT_VERTEX_FLOATS: usize = 8;
+
+/// Immediate-mode screen text: queue strings and rectangles in pixel coordinates (origin
+/// top left) any time during the frame, then `flush` draws them all in one call.
+pub struct TextRenderer {
+    /// Screen pixels per font pixel
+    pub scale: f32,
+    shader: ReloadableShader,
+    texture: u32,
+    vao: u32,
+    vbo: u32,
+    /// Capacity of `vbo` in floats
+    vbo_capacity: usize,
+    vertices: Vec<f32>,
+}
+
+impl TextRenderer {
+    pub unsafe fn new() -> Result<Self, String> {
+        let shader = ReloadableShader::load(TEXT_VERT, TEXT_FRAG, &[])?;
+
+        // Unpack the 1-bit font into an R8 atlas, plus one solid cell
+        let (width, height) = (ATLAS_COLUMNS * GLYPH_SIZE, ATLAS_ROWS * GLYPH_SIZE);
+        let mut pixels = vec![0u8; (width * height) as usize];
+        for (cell, glyph) in FONT_8X8.iter().chain(std::iter::once(&[0xFF; 8])).enumerate() {
+            let (cx, cy) = (cell as i32 % ATLAS_COLUMNS * GLYPH_SIZE, cell as i32 / ATLAS_COLUMNS * GLYPH_SIZE);
+            for (y, &bits) in (0..GLYPH_SIZE).zip(glyph.iter()) {
+                for x in 0..GLYPH_SIZE {
+                    // Bit 0 is the leftmost pixel
+                    if bits & (1 << x) != 0 {
+                        pixels[((cy + y) * width + cx + x) as usize] = 255;
+                    }
+                }
+            }
+        }
+        let mut texture = 0;
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::R8 as i32, width, height, 0, gl::RED, gl::UNSIGNED_BYTE, pixels.as_ptr() as *const _);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+
+        let (mut vao, mut vbo) = (0, 0);
+        gl::GenVertexArrays(1, &mut vao);
+        gl::GenBuffers(1, &mut vbo);
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        let stride = (TEXT_VERTEX_FLOATS * mem::size_of::<f32>()) as i32;
+        let attributes = [(0, 2, 0), (1, 2, 2), (2, 4, 4)];
+        for (location, size, offset) in attributes {
+            gl::VertexAttribPointer(location, size, gl::FLOAT, gl::FALSE, stride, (offset * mem::size_of::<f32>()) as *const _);
+            gl::EnableVertexAttribArray(location);
+        }
+        gl::BindVertexArray(0);
+
+        Ok(Self { scale: 2.0, shader, texture, vao, vbo, vbo_capacity: 0, vertices: Vec::new() })
+    }
+
+    /// Height of one line of text in screen pixels.
+    pub fn line_height(&self) -> f32 { (GLYPH_SIZE + 2) as f32 * self.scale }
+
+    /// Size of a string in screen pixels.
+    pub fn measure(&self, text: &str) -> Vec2 {
+        let widest = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let lines = text.lines().count().max(1);
+        Vec2::new(widest as f32 * GLYPH_SIZE as f32 * self.scale, lines as f32 * self.line_height())
+    }
+
+    /// Queues a string with its top left corner at `(x, y)`. `\n` starts a new line;
+    /// characters outside printable ASCII draw as `?`.
+    pub fn text(&mut self, x: f32, y: f32, text: &str, color: Vec4) {
+        let size = GLYPH_SIZE as f32 * self.scale;
+        let (mut pen_x, mut pen_y) = (x, y);
+        for c in text.chars() {
+            if c == '\n' {
+                pen_x = x;
+                pen_y += self.line_height();
+                continue;
+            }
+            if c != ' ' {
+                let code = if (' '..='~').contains(&c) { c as u8 } else { b'?' };
+                self.quad(pen_x, pen_y, size, size, (code - FIRST_CHAR) as usize, color);
+            }
+            pen_x += size;
+        }
+    }
+
+    /// Queues a filled rectangle.
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Vec4) {
+        self.quad(x, y, width, height, SOLID_CELL, color);
+    }
+
+    fn quad(&mut self, x: f32, y: f32, w: f32, h: f32, cell: usize, color: Vec4) {
+        let (columns, rows) = (ATLAS_COLUMNS as f32, ATLAS_ROWS as f32);
+        let u0 = (cell as i32 % ATLAS_COLUMNS) as f32 / columns;
+        let v0 = (cell as i32 / ATLAS_COLUMNS) as f32 / rows;
+        let (u1, v1) = (u0 + 1.0 / columns, v0 + 1.0 / rows);
+        let corners = [(x, y, u0, v0), (x + w, y, u1, v0), (x + w, y + h, u1, v1), (x, y + h, u0, v1)];
+        for &i in &[0, 1, 2, 0, 2, 3] {
+            let (px, py, u, v) = corners[i];
+            self.vertices.extend_from_slice(&[px, py, u, v, color.x, color.y, color.z, color.w]);
+        }
+    }
+
+    /// Picks up shader edits from disk (dev builds only).
+    pub unsafe fn poll_shader(&mut self) {
+        self.shader.poll();
+    }
+
+    /// Draws everything queued this frame over the bound framebuffer and clears the queue.
+    pub unsafe fn flush(&mut self, screen_size: (i32, i32)) {
+        if self.vertices.is_empty() { return; }
+
+        gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+        let bytes = (self.vertices.len() * mem::size_of::<f32>()) as isize;
+        if self.vertices.len() > self.vbo_capacity {
+            gl::BufferData(gl::ARRAY_BUFFER, bytes, self.vertices.as_ptr() as *const _, gl::STREAM_DRAW);
+            self.vbo_capacity = self.vertices.len();
+        } else {
+            // Orphan the old storage so we don't wait on last frame's draw
+            gl::BufferData(gl::ARRAY_BUFFER, (self.vbo_capacity * mem::size_of::<f32>()) as isize, ptr::null(), gl::STREAM_DRAW);
+            gl::BufferSubData(gl::ARRAY_BUFFER, 0, bytes, self.vertices.as_ptr() as *const _);
+        }
+
+        let shader = &self.shader;
+        shader.use_program();
+        shader.set("uScreenSize", &Vec2::new(screen_size.0 as f32, screen_size.1 as f32));
+        shader.set_sampler("uFont", 0);
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, self.texture);
+
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Disable(gl::CULL_FACE);
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        gl::BindVertexArray(self.vao);
+        gl::DrawArrays(gl::TRIANGLES, 0, (self.vertices.len() / TEXT_VERTEX_FLOATS) as i32);
+        gl::BindVertexArray(0);
+        gl::Disable(gl::BLEND);
+        gl::Enable(gl::CULL_FACE);
+        gl::Enable(gl::DEPTH_TEST);
+
+        self.vertices.clear();
+    }
+}
+
+impl Drop for TextRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+/// Printable ASCII (space to `~`) as 8x8 bitmaps, one byte per row, bit 0 leftmost.
+/// From the public domain font8x8 set.
+const FONT_8X8: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '''
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
@@ -1,67 +1,112 @@
-use gl::types::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use image::RgbaImage;
+use crate::engine::gl_caps::GlCaps;
 
-/// OpenGL texture wrapper
-pub struct Texture {
+// Anisotropic filtering enums (core in GL 4.6, otherwise EXT/ARB_texture_filter_anisotropic)
+const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
+/// Upper bound on the anisotropy level requested for block textures.
+const MAX_ANISOTROPY: f32 = 16.0;
+
+/// OpenGL 2D array texture wrapper, one layer per image
+pub struct TextureArray {
     pub id: u32,
     pub width: u32,
     pub height: u32,
+    pub layers: u32,
 }
 
-impl Texture {
-    /// Load a texture from file
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
-        let img = image::open(path)
-            .map_err(|e| format!("Failed to load texture: {}", e))?
-            .flipv() // OpenGL expects bottom-left origin
-            .to_rgba8();
-        
-        let (width, height) = img.dimensions();
-        let data = img.into_raw();
-        
-        Self::from_rgba(&data, width, height)
-    }
-    
-    /// Create a texture from raw RGBA data
-    pub fn from_rgba(data: &[u8], width: u32, height: u32) -> Result<Self, String> {
+impl TextureArray {
+    /// Create a mipmapped texture array from equally sized sRGB RGBA layers
+    pub fn from_layers(layers: &[RgbaImage]) -> Result<Self, String> {
+        let first = layers.first().ok_or_else(|| "Texture array needs at least one layer".to_string())?;
+        let (width, height) = first.dimensions();
+        if let Some(index) = layers.iter().position(|l| l.dimensions() != (width, height)) {
+            return Err(format!("Texture array layer {} is {:?}, expected {}x{}", index, layers[index].dimensions(), width, height));
+        }
         let mut id: u32 = 0;
-        
+
         unsafe {
             gl::GenTextures(1, &mut id);
-            gl::BindTexture(gl::TEXTURE_2D, id);
-            
-            // Set texture parameters
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST_MIPMAP_LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-            
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, id);
+
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, gl::NEAREST_MIPMAP_LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            if let Some(level) = max_anisotropy() {
+                gl::TexParameterf(gl::TEXTURE_2D_ARRAY, TEXTURE_MAX_ANISOTROPY, level);
+            }
+
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
                 0,
-                gl::RGBA as i32,
+                gl::SRGB8_ALPHA8 as i32, // decoded to linear when sampled; the post chain re-encodes
                 width as i32,
                 height as i32,
+                layers.len() as i32,
                 0,
                 gl::RGBA,
                 gl::UNSIGNED_BYTE,
-                data.as_ptr() as *const _,
+                std::ptr::null(),
             );
-            
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+            for (layer, image) in layers.iter().enumerate() {
+                gl::TexSubImage3D(
+                    gl::TEXTURE_2D_ARRAY,
+                    0,
+                    0, 0, layer as i32,
+                    width as i32, height as i32, 1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    image.as_raw().as_ptr() as *const _,
+                );
+            }
+
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
         }
-        
-        Ok(Self { id, width, height })
+
+        Ok(Self { id, width, height, layers: layers.len() as u32 })
     }
-    
-    /// Bind this texture to a texture unit
+
+    /// Bind this texture array to a texture unit
     pub unsafe fn bind(&self, unit: u32) {
         gl::ActiveTexture(gl::TEXTURE0 + unit);
-        gl::BindTexture(gl::TEXTURE_2D, self.id);
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+    }
+
+    /// Replaces one layer with `width * height` RGBA pixels and rebuilds that layer's mip chain
+    /// on the CPU, so animating a few tiles doesn't regenerate the mipmaps of every layer.
+    pub unsafe fn upload_layer(&self, layer: u32, data: &[u8]) {
+        assert_eq!(data.len(), (self.width * self.height * 4) as usize, "layer data must be width * height RGBA pixels");
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        let (mut width, mut height) = (self.width, self.height);
+        let mut level_data;
+        let mut pixels = data;
+        let mut level = 0;
+        loop {
+            gl::TexSubImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                level,
+                0, 0, layer as i32,
+                width as i32, height as i32, 1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const _,
+            );
+            if width == 1 && height == 1 { break; }
+            let (next, next_width, next_height) = downsample_srgb(pixels, width, height);
+            level_data = next;
+            pixels = &level_data;
+            width = next_width;
+            height = next_height;
+            level += 1;
+        }
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
     }
 }
 
-impl Drop for Texture {
+impl Drop for TextureArray {
     fn drop(&mut self) {
         unsafe {
             gl::DeleteTextures(1, &self.id);
@@ -69,176 +114,218 @@ impl Drop for Texture {
     }
 }
 
-/// Generates a procedural texture atlas for blocks
-/// Layout: 16x16 grid, each tile is 16x16 pixels = 256x256 total
-/// Row 0: grass_top, grass_side, dirt, stone, bedrock, water, sand, ...
-pub fn generate_block_atlas() -> Texture {
-    const TILE_SIZE: u32 = 16;
-    const ATLAS_TILES: u32 = 16;
-    const ATLAS_SIZE: u32 = TILE_SIZE * ATLAS_TILES;
-    
-    let mut data = vec![0u8; (ATLAS_SIZE * ATLAS_SIZE * 4) as usize];
-    
-    // Helper to set a pixel in the atlas
-    let set_pixel = |data: &mut [u8], tile_x: u32, tile_y: u32, px: u32, py: u32, r: u8, g: u8, b: u8, a: u8| {
-        let x = tile_x * TILE_SIZE + px;
-        let y = tile_y * TILE_SIZE + py;
-        let idx = ((y * ATLAS_SIZE + x) * 4) as usize;
-        data[idx] = r;
-        data[idx + 1] = g;
-        data[idx + 2] = b;
-        data[idx + 3] = a;
+/// Halves an sRGB RGBA image with a 2x2 box filter, averaging color in linear space like
+/// `glGenerateMipmap` does for sRGB textures. Odd edges clamp.
+fn downsample_srgb(data: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
+    let to_linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
     };
-    
-    // Simple hash for pseudo-random variation
-    let hash = |x: u32, y: u32, seed: u32| -> u8 {
-        let n = x.wrapping_mul(374761393)
-            .wrapping_add(y.wrapping_mul(668265263))
-            .wrapping_add(seed.wrapping_mul(1013904223));
-        ((n >> 13) ^ n) as u8
+    let to_srgb = |l: f32| {
+        let c = if l <= 0.0031308 { l * 12.92 } else { 1.055 * l.powf(1.0 / 2.4) - 0.055 };
+        (c * 255.0).round().clamp(0.0, 255.0) as u8
     };
-    
-    // Tile 0: Grass top
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
-            let var = hash(px, py, 0) as i32 - 128;
-            let r = (90 + var / 8).clamp(0, 255) as u8;
-            let g = (180 + var / 4).clamp(0, 255) as u8;
-            let b = (70 + var / 10).clamp(0, 255) as u8;
-            set_pixel(&mut data, 0, 0, px, py, r, g, b, 255);
+    let (out_width, out_height) = ((width / 2).max(1), (height / 2).max(1));
+    let mut out = Vec::with_capacity((out_width * out_height * 4) as usize);
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let texels = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| {
+                let (sx, sy) = ((x * 2 + dx).min(width - 1), (y * 2 + dy).min(height - 1));
+                ((sy * width + sx) * 4) as usize
+            });
+            for c in 0..3 {
+                let sum: f32 = texels.iter().map(|&t| to_linear(data[t + c])).sum();
+                out.push(to_srgb(sum / 4.0));
+            }
+            let alpha: u32 = texels.iter().map(|&t| data[t + 3] as u32).sum();
+            out.push(((alpha + 2) / 4) as u8);
         }
     }
-    
-    // Tile 1: Grass side (dirt with grass edge on top)
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
+    (out, out_width, out_height)
+}
+
+/// Anisotropy level to request, or None when the context doesn't support it.
+unsafe fn max_anisotropy() -> Option<f32> {
+    let caps = GlCaps::get();
+    let supported = caps.supports_version(4, 6)
+        || caps.has_extension("GL_EXT_texture_filter_anisotropic")
+        || caps.has_extension("GL_ARB_texture_filter_anisotropic");
+    if !supported { return None; }
+    let mut max = 0.0f32;
+    gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+    (max > 1.0).then(|| max.min(MAX_ANISOTROPY))
+}
+
+/// Size in pixels of the procedurally painted tiles.
+pub const PROCEDURAL_TILE_SIZE: u32 = 16;
+
+/// A directory of block textures, one PNG per tile: `<root>/textures/block/<tile name>.png`.
+pub struct ResourcePack {
+    pub root: PathBuf,
+}
+
+impl ResourcePack {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self { root: root.as_ref().to_path_buf() }
+    }
+
+    /// Returns the pack at `path` if the directory exists.
+    pub fn open<P: AsRef<Path>>(path: P) -> Option<Self> {
+        if path.as_ref().is_dir() { Some(Self::new(path)) } else { None }
+    }
+
+    pub fn tile_path(&self, name: &str) -> PathBuf {
+        self.root.join("textures").join("block").join(format!("{}.png", name))
+    }
+
+    /// Animation metadata next to a tile: `<tile name>.png.mcmeta`.
+    pub fn animation_path(&self, name: &str) -> PathBuf {
+        self.root.join("textures").join("block").join(format!("{}.png.mcmeta", name))
+    }
+
+    /// Loads the full PNG for a tile (including every frame of an animation strip),
+    /// or `None` if the pack doesn't provide it.
+    pub fn load_image(&self, name: &str) -> Option<RgbaImage> {
+        let path = self.tile_path(name);
+        if !path.is_file() { return None; }
+        match image::open(&path) {
+            Ok(img) => Some(img.to_rgba8()),
+            Err(e) => {
+                eprintln!("Warning: failed to load {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Loads the PNG for a tile, or `None` if the pack doesn't provide it.
+    /// Tiles taller than they are wide (animation strips) are cropped to their first square frame.
+    pub fn load_tile(&self, name: &str) -> Option<RgbaImage> {
+        let img = self.load_image(name)?;
+        let (w, h) = img.dimensions();
+        if h > w { Some(image::imageops::crop_imm(&img, 0, 0, w, w).to_image()) } else { Some(img) }
+    }
+}
+
+/// Builds the block texture array with one layer per tile (layer index == `block_textures` index).
+/// Tiles come from the resource pack when present and are scaled (nearest) to the largest tile size.
+pub fn build_block_texture_array(pack: Option<&ResourcePack>) -> TextureArray {
+    let tiles = load_block_tiles(pack);
+    let tile_size = tiles.iter().map(|t| t.width()).max().unwrap_or(PROCEDURAL_TILE_SIZE);
+    let tiles: Vec<RgbaImage> = tiles.into_iter()
+        .map(|tile| if tile.dimensions() == (tile_size, tile_size) {
+            tile
+        } else {
+            image::imageops::resize(&tile, tile_size, tile_size, image::imageops::FilterType::Nearest)
+        })
+        .collect();
+    TextureArray::from_layers(&tiles).expect("Failed to create block texture array")
+}
+
+/// Returns one image per block tile (indexed by `block_textures`), from the pack or procedural.
+pub fn load_block_tiles(pack: Option<&ResourcePack>) -> Vec<RgbaImage> {
+    block_textures::NAMES.iter().enumerate()
+        .map(|(index, name)| {
+            pack.and_then(|p| p.load_tile(name))
+                .unwrap_or_else(|| procedural_tile(index as u32))
+        })
+        .collect()
+}
+
+/// Paints one of the built-in block tiles at `PROCEDURAL_TILE_SIZE`.
+pub fn procedural_tile(tile_index: u32) -> RgbaImage {
+    procedural_frame(tile_index, 0.0)
+}
+
+/// Paints a built-in tile at animation `phase` (0..1, looping). Static tiles ignore the phase.
+pub fn procedural_frame(tile_index: u32, phase: f32) -> RgbaImage {
+    RgbaImage::from_fn(PROCEDURAL_TILE_SIZE, PROCEDURAL_TILE_SIZE, |px, py| {
+        image::Rgba(procedural_pixel(tile_index, px, py, phase))
+    })
+}
+
+/// Simple hash for pseudo-random variation
+fn hash(x: u32, y: u32, seed: u32) -> u8 {
+    let n = x.wrapping_mul(374761393)
+        .wrapping_add(y.wrapping_mul(668265263))
+        .wrapping_add(seed.wrapping_mul(1013904223));
+    ((n >> 13) ^ n) as u8
+}
+
+fn procedural_pixel(tile_index: u32, px: u32, py: u32, phase: f32) -> [u8; 4] {
+    let c = |v: i32| v.clamp(0, 255) as u8;
+    let t = phase * std::f32::consts::TAU;
+    match tile_index {
+        block_textures::GRASS_TOP => {
+            let var = hash(px, py, 0) as i32 - 128;
+            [c(90 + var / 8), c(180 + var / 4), c(70 + var / 10), 255]
+        }
+        block_textures::GRASS_SIDE => {
+            // Dirt with grass edge on top
             let var = hash(px, py, 1) as i32 - 128;
             if py < 4 {
-                // Grass part at top
-                let r = (90 + var / 8).clamp(0, 255) as u8;
-                let g = (160 + var / 4).clamp(0, 255) as u8;
-                let b = (60 + var / 10).clamp(0, 255) as u8;
-                set_pixel(&mut data, 1, 0, px, py, r, g, b, 255);
+                [c(90 + var / 8), c(160 + var / 4), c(60 + var / 10), 255]
             } else {
-                // Dirt part
-                let r = (140 + var / 6).clamp(0, 255) as u8;
-                let g = (100 + var / 8).clamp(0, 255) as u8;
-                let b = (65 + var / 10).clamp(0, 255) as u8;
-                set_pixel(&mut data, 1, 0, px, py, r, g, b, 255);
+                [c(140 + var / 6), c(100 + var / 8), c(65 + var / 10), 255]
             }
         }
-    }
-    
-    // Tile 2: Dirt
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
+        block_textures::DIRT => {
             let var = hash(px, py, 2) as i32 - 128;
-            let r = (140 + var / 6).clamp(0, 255) as u8;
-            let g = (100 + var / 8).clamp(0, 255) as u8;
-            let b = (65 + var / 10).clamp(0, 255) as u8;
-            set_pixel(&mut data, 2, 0, px, py, r, g, b, 255);
+            [c(140 + var / 6), c(100 + var / 8), c(65 + var / 10), 255]
         }
-    }
-    
-    // Tile 3: Stone
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
+        block_textures::STONE => {
             let var = hash(px, py, 3) as i32 - 128;
             // Add some larger noise for stone texture
             let var2 = hash(px / 3, py / 3, 33) as i32 - 128;
             let base = 128 + var / 8 + var2 / 6;
-            let r = base.clamp(0, 255) as u8;
-            let g = (base - 5).clamp(0, 255) as u8;
-            let b = (base - 3).clamp(0, 255) as u8;
-            set_pixel(&mut data, 3, 0, px, py, r, g, b, 255);
+            [c(base), c(base - 5), c(base - 3), 255]
         }
-    }
-    
-    // Tile 4: Bedrock
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
+        block_textures::BEDROCK => {
             let var = hash(px, py, 4) as i32 - 128;
             let var2 = hash(px / 2, py / 2, 44) as i32 - 128;
             let base = 40 + var / 12 + var2 / 8;
-            let r = base.clamp(0, 255) as u8;
-            let g = base.clamp(0, 255) as u8;
-            let b = (base + 5).clamp(0, 255) as u8;
-            set_pixel(&mut data, 4, 0, px, py, r, g, b, 255);
+            [c(base), c(base), c(base + 5), 255]
         }
-    }
-    
-    // Tile 5: Water (semi-transparent blue)
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
+        block_textures::WATER => {
+            // Semi-transparent blue with a wave-like pattern
             let var = hash(px, py, 5) as i32 - 128;
-            // Add wave-like pattern
-            let wave = ((px as f32 * 0.5 + py as f32 * 0.3).sin() * 10.0) as i32;
-            let r = (50 + var / 16 + wave / 2).clamp(0, 255) as u8;
-            let g = (100 + var / 12 + wave).clamp(0, 255) as u8;
-            let b = (200 + var / 8 + wave / 2).clamp(0, 255) as u8;
-            set_pixel(&mut data, 5, 0, px, py, r, g, b, 160);
+            let wave = ((px as f32 * 0.5 + py as f32 * 0.3 + t).sin() * 10.0) as i32;
+            [c(50 + var / 16 + wave / 2), c(100 + var / 12 + wave), c(200 + var / 8 + wave / 2), 160]
         }
-    }
-    
-    // Tile 6: Sand
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
+        block_textures::SAND => {
             let var = hash(px, py, 6) as i32 - 128;
             let var2 = hash(px / 2, py / 2, 66) as i32 - 128;
-            let r = (220 + var / 10 + var2 / 12).clamp(0, 255) as u8;
-            let g = (195 + var / 10 + var2 / 12).clamp(0, 255) as u8;
-            let b = (140 + var / 8 + var2 / 10).clamp(0, 255) as u8;
-            set_pixel(&mut data, 6, 0, px, py, r, g, b, 255);
+            [c(220 + var / 10 + var2 / 12), c(195 + var / 10 + var2 / 12), c(140 + var / 8 + var2 / 10), 255]
         }
-    }
-    
-    // Tile 7: Gravel
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
+        block_textures::GRAVEL => {
+            // Mix of gray with slight brown tint
             let var = hash(px, py, 7) as i32 - 128;
             let var2 = hash(px / 2, py / 2, 77) as i32 - 128;
-            // Mix of gray with slight brown tint
             let base = 100 + var / 6 + var2 / 8;
-            let r = (base + 5).clamp(0, 255) as u8;
-            let g = base.clamp(0, 255) as u8;
-            let b = (base - 5).clamp(0, 255) as u8;
-            set_pixel(&mut data, 7, 0, px, py, r, g, b, 255);
+            [c(base + 5), c(base), c(base - 5), 255]
         }
-    }
-    
-    // Tile 8: Debug/missing texture (magenta checkerboard)
-    for py in 0..TILE_SIZE {
-        for px in 0..TILE_SIZE {
-            let checker = ((px / 4) + (py / 4)) % 2 == 0;
-            if checker {
-                set_pixel(&mut data, 8, 0, px, py, 255, 0, 255, 255);
-            } else {
-                set_pixel(&mut data, 8, 0, px, py, 0, 0, 0, 255);
-            }
+        block_textures::LAVA => {
+            // Glowing orange with slowly drifting bright veins
+            let var = hash(px, py, 9) as i32 - 128;
+            let flow = (px as f32 * 0.4 + t).sin() + (py as f32 * 0.5 - t).sin() + ((px + py) as f32 * 0.25 + t).cos();
+            let heat = (flow * 25.0) as i32;
+            [c(215 + var / 16 + heat / 2), c(90 + var / 12 + heat), c(20 + heat / 3), 255]
+        }
+        block_textures::PORTAL => {
+            // Translucent purple swirl around the tile center
+            let (dx, dy) = (px as f32 - 7.5, py as f32 - 7.5);
+            let swirl = ((dx * dx + dy * dy).sqrt() * 0.8 + dy.atan2(dx) * 2.0 - t * 2.0).sin();
+            let var = hash(px, py, 10) as i32 - 128;
+            let glow = (swirl * 50.0) as i32;
+            [c(120 + var / 16 + glow / 2), c(30 + glow / 4), c(200 + var / 12 + glow / 2), 190]
+        }
+        _ => {
+            // Debug/missing texture (magenta checkerboard)
+            if ((px / 4) + (py / 4)).is_multiple_of(2) { [255, 0, 255, 255] } else { [0, 0, 0, 255] }
         }
     }
-    
-    Texture::from_rgba(&data, ATLAS_SIZE, ATLAS_SIZE).expect("Failed to create block atlas")
 }
 
-/// UV coordinates for a tile in the atlas
-/// Returns (u_min, v_min, u_max, v_max)
-pub fn get_tile_uvs(tile_index: u32) -> (f32, f32, f32, f32) {
-    const TILES_PER_ROW: u32 = 16;
-    const TILE_UV_SIZE: f32 = 1.0 / 16.0;
-    
-    let tile_x = tile_index % TILES_PER_ROW;
-    let tile_y = tile_index / TILES_PER_ROW;
-    
-    let u_min = tile_x as f32 * TILE_UV_SIZE;
-    let v_min = tile_y as f32 * TILE_UV_SIZE;
-    let u_max = u_min + TILE_UV_SIZE;
-    let v_max = v_min + TILE_UV_SIZE;
-    
-    (u_min, v_min, u_max, v_max)
-}
-
-/// Block texture indices in the atlas
+/// Block tile indices, which are also their layers in the block texture array
 pub mod block_textures {
     pub const GRASS_TOP: u32 = 0;
     pub const GRASS_SIDE: u32 = 1;
@@ -249,4 +336,43 @@ pub mod block_textures {
     pub const SAND: u32 = 6;
     pub const GRAVEL: u32 = 7;
     pub const MISSING: u32 = 8;
+    pub const LAVA: u32 = 9;
+    pub const PORTAL: u32 = 10;
+
+    /// Resource pack file name (without `.png`) of each tile, indexed by tile index.
+    pub const NAMES: [&str; 11] = [
+        "grass_top", "grass_side", "dirt", "stone", "bedrock", "water", "sand", "gravel", "missing",
+        "lava", "portal",
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsample_averages_in_linear_space() {
+        // Black and white average to linear mid-grey, which is 188 in sRGB
+        let data = [0, 0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 255, 255, 255, 255];
+        let (out, w, h) = downsample_srgb(&data, 2, 2);
+        assert_eq!((w, h), (1, 1));
+        assert_eq!(out, vec![188, 188, 188, 128]);
+    }
+
+    #[test]
+    fn downsample_reaches_one_texel() {
+        let mut data = vec![200u8; 16 * 4 * 4];
+        let (mut w, mut h) = (16, 4);
+        let mut levels = 0;
+        while (w, h) != (1, 1) {
+            let (next, nw, nh) = downsample_srgb(&data, w, h);
+            assert_eq!(next.len(), (nw * nh * 4) as usize);
+            assert!(next.iter().all(|&c| c == 200), "uniform images stay uniform");
+            data = next;
+            w = nw;
+            h = nh;
+            levels += 1;
+        }
+        assert_eq!(levels, 4);
+    }
 }
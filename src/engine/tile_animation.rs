@@ -0,0 +1,185 @@
+use image::RgbaImage;
+use crate::engine::constants::TILE_ANIMATION_TICK_SECS;
+use crate::engine::json::Json;
+use crate::engine::texture::{block_textures, procedural_frame, ResourcePack, TextureArray};
+
+/// Built-in animations used when the resource pack doesn't provide the tile:
+/// (tile, frame count, seconds per frame, interpolate).
+const PROCEDURAL_ANIMATIONS: [(u32, usize, f32, bool); 3] = [
+    (block_textures::WATER, 16, 0.125, true),
+    (block_textures::LAVA, 16, 0.3, true),
+    (block_textures::PORTAL, 32, 0.05, false),
+];
+
+/// An animated block tile: a strip of frames played in sequence on one texture array layer.
+pub struct TileAnimation {
+    pub layer: u32,
+    frames: Vec<RgbaImage>,
+    /// Playback order as (frame index, duration in seconds).
+    sequence: Vec<(usize, f32)>,
+    /// Blend each frame into the next instead of switching abruptly.
+    pub interpolate: bool,
+    period: f32,
+}
+
+impl TileAnimation {
+    /// Creates an animation playing `frames` in order, each for `frame_time` seconds.
+    pub fn new(layer: u32, frames: Vec<RgbaImage>, frame_time: f32, interpolate: bool) -> Result<Self, String> {
+        let sequence = (0..frames.len()).map(|i| (i, frame_time)).collect();
+        Self::with_sequence(layer, frames, sequence, interpolate)
+    }
+
+    /// Creates an animation with an explicit playback order and per-frame durations.
+    pub fn with_sequence(layer: u32, frames: Vec<RgbaImage>, sequence: Vec<(usize, f32)>, interpolate: bool) -> Result<Self, String> {
+        if frames.is_empty() || sequence.is_empty() {
+            return Err(format!("Animation for layer {} has no frames", layer));
+        }
+        if let Some(&(index, _)) = sequence.iter().find(|&&(index, _)| index >= frames.len()) {
+            return Err(format!("Animation for layer {} references frame {} of {}", layer, index, frames.len()));
+        }
+        if sequence.iter().any(|&(_, duration)| duration <= 0.0) {
+            return Err(format!("Animation for layer {} has a non-positive frame duration", layer));
+        }
+        let size = frames[0].dimensions();
+        if frames.iter().any(|f| f.dimensions() != size) {
+            return Err(format!("Animation for layer {} has frames of different sizes", layer));
+        }
+        let period = sequence.iter().map(|&(_, duration)| duration).sum();
+        Ok(Self { layer, frames, sequence, interpolate, period })
+    }
+
+    /// Splits a vertical strip of square frames (as used by resource packs) into an animation,
+    /// taking timing from the `.mcmeta` JSON when present.
+    pub fn from_strip(layer: u32, strip: &RgbaImage, meta: Option<&Json>) -> Result<Self, String> {
+        let (w, h) = strip.dimensions();
+        if w == 0 || h <= w || h % w != 0 {
+            return Err(format!("Animation strip for layer {} is {}x{}, expected a column of square frames", layer, w, h));
+        }
+        let frames: Vec<RgbaImage> = (0..h / w)
+            .map(|i| image::imageops::crop_imm(strip, 0, i * w, w, w).to_image())
+            .collect();
+
+        let anim = meta.map(|m| m.get("animation")).unwrap_or(&Json::Null);
+        let ticks = anim.get("frametime").as_f64().unwrap_or(1.0) as f32;
+        let frame_time = ticks * TILE_ANIMATION_TICK_SECS;
+        let interpolate = anim.get("interpolate").as_bool().unwrap_or(false);
+        let sequence: Vec<(usize, f32)> = match anim.get("frames") {
+            Json::Array(entries) => entries.iter()
+                .filter_map(|entry| match entry {
+                    Json::Number(index) => Some((*index as usize, frame_time)),
+                    Json::Object(_) => {
+                        let index = entry.get("index").as_usize()?;
+                        let time = entry.get("time").as_f64().map(|t| t as f32 * TILE_ANIMATION_TICK_SECS);
+                        Some((index, time.unwrap_or(frame_time)))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => (0..frames.len()).map(|i| (i, frame_time)).collect(),
+        };
+        Self::with_sequence(layer, frames, sequence, interpolate)
+    }
+
+    /// Scales every frame (nearest) to `size` x `size`.
+    pub fn resized(mut self, size: u32) -> Self {
+        for frame in &mut self.frames {
+            if frame.dimensions() != (size, size) {
+                *frame = image::imageops::resize(frame, size, size, image::imageops::FilterType::Nearest);
+            }
+        }
+        self
+    }
+
+    /// Frame shown at `time`, the frame after it, and how far (0..1) into the current frame we are.
+    pub fn sample(&self, time: f32) -> (usize, usize, f32) {
+        let mut t = time.rem_euclid(self.period);
+        for (i, &(frame, duration)) in self.sequence.iter().enumerate() {
+            if t < duration {
+                let next = self.sequence[(i + 1) % self.sequence.len()].0;
+                return (frame, next, t / duration);
+            }
+            t -= duration;
+        }
+        let last = self.sequence[self.sequence.len() - 1].0;
+        (last, self.sequence[0].0, 1.0)
+    }
+
+    /// Displayed state at `time`: (current frame, next frame, blend weight 0-255).
+    fn state(&self, time: f32) -> (usize, usize, u8) {
+        let (current, next, blend) = self.sample(time);
+        let weight = if self.interpolate && current != next { (blend * 255.0) as u8 } else { 0 };
+        (current, next, weight)
+    }
+
+    /// Writes the RGBA pixels for a displayed state into `out`.
+    fn write_pixels(&self, (current, next, weight): (usize, usize, u8), out: &mut Vec<u8>) {
+        out.clear();
+        if weight == 0 {
+            out.extend_from_slice(self.frames[current].as_raw());
+        } else {
+            let (a, b) = (self.frames[current].as_raw(), self.frames[next].as_raw());
+            let w = weight as u32;
+            out.extend(a.iter().zip(b).map(|(&a, &b)| ((a as u32 * (255 - w) + b as u32 * w) / 255) as u8));
+        }
+    }
+}
+
+/// All animated tiles of the block texture array, advanced from `Engine::time` each frame
+/// by re-uploading the affected layers.
+#[derive(Default)]
+pub struct TileAnimations {
+    animations: Vec<TileAnimation>,
+    shown: Vec<Option<(usize, usize, u8)>>,
+    scratch: Vec<u8>,
+}
+
+impl TileAnimations {
+    pub fn new(animations: Vec<TileAnimation>) -> Self {
+        let shown = vec![None; animations.len()];
+        Self { animations, shown, scratch: Vec::new() }
+    }
+
+    /// Collects the block tile animations: resource pack strips (with optional `.mcmeta` timing)
+    /// take priority, and tiles the pack doesn't provide use the built-in procedural animations.
+    /// Frames are scaled to `tile_size` to match the texture array layers.
+    pub fn load_blocks(pack: Option<&ResourcePack>, tile_size: u32) -> Self {
+        let mut animations = Vec::new();
+        for (index, name) in block_textures::NAMES.iter().enumerate() {
+            let layer = index as u32;
+            let result = match pack.and_then(|p| p.load_image(name)) {
+                Some(strip) if strip.height() > strip.width() => {
+                    let meta = pack.and_then(|p| std::fs::read_to_string(p.animation_path(name)).ok())
+                        .map(|text| Json::parse(&text))
+                        .transpose();
+                    meta.and_then(|meta| TileAnimation::from_strip(layer, &strip, meta.as_ref()))
+                }
+                Some(_) => continue, // Static tile from the pack overrides the built-in animation
+                None => match PROCEDURAL_ANIMATIONS.iter().find(|a| a.0 == layer) {
+                    Some(&(_, count, frame_time, interpolate)) => {
+                        let frames = (0..count).map(|i| procedural_frame(layer, i as f32 / count as f32)).collect();
+                        TileAnimation::new(layer, frames, frame_time, interpolate)
+                    }
+                    None => continue,
+                },
+            };
+            match result {
+                Ok(animation) => animations.push(animation.resized(tile_size)),
+                Err(e) => eprintln!("Warning: animation for {}: {}", name, e),
+            }
+        }
+        Self::new(animations)
+    }
+
+    /// Uploads the frame for `time`, mip chain included, of every animation whose
+    /// displayed frame changed.
+    pub unsafe fn update(&mut self, textures: &TextureArray, time: f32) {
+        for (animation, shown) in self.animations.iter().zip(self.shown.iter_mut()) {
+            if animation.layer >= textures.layers { continue; }
+            let state = animation.state(time);
+            if *shown == Some(state) { continue; }
+            animation.write_pixels(state, &mut self.scratch);
+            textures.upload_layer(animation.layer, &self.scratch);
+            *shown = Some(state);
+        }
+    }
+}
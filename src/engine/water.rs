@@ -0,0 +1,94 @@
+use glam::{Vec2, Vec3};
+use crate::engine::constants::water;
+use crate::engine::framebuffer::{Framebuffer, FramebufferDesc};
+use crate::engine::shader::ShaderProgram;
+
+/// Texture units the water shader reads its inputs from (unit 0 is the block texture array).
+pub const SCENE_COLOR_UNIT: u32 = 1;
+pub const SCENE_DEPTH_UNIT: u32 = 2;
+
+/// Copy of the opaque scene's color and depth, sampled by the water shader for
+/// screen-space refraction and depth-based absorption.
+#[derive(Default)]
+pub struct SceneCopy {
+    target: Option<Framebuffer>,
+}
+
+impl SceneCopy {
+    pub fn new() -> Self {
+        Self { target: None }
+    }
+
+    /// Copies color and depth of the currently bound draw framebuffer (`width` x `height`)
+    /// into the copy's textures, recreating them when the size changed.
+    pub unsafe fn capture(&mut self, width: i32, height: i32) {
+        if width <= 0 || height <= 0 { return; }
+        let target = match &mut self.target {
+            Some(target) => {
+                if let Err(e) = target.resize(width, height) { eprintln!("Warning: water scene copy: {}", e); }
+                target
+            }
+            None => {
+                // Same formats as the HDR scene target so color and depth can be blitted
+                match Framebuffer::new(FramebufferDesc::hdr_scene(), width, height) {
+                    Ok(target) => self.target.insert(target),
+                    Err(e) => { eprintln!("Warning: water scene copy: {}", e); return; }
+                }
+            }
+        };
+        let mut source = 0;
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut source);
+        target.blit_from(source as u32, gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+    }
+
+    /// Binds the copied color and depth to `SCENE_COLOR_UNIT` and `SCENE_DEPTH_UNIT`.
+    pub unsafe fn bind(&self) {
+        if let Some(target) = &self.target {
+            target.bind_color(0, SCENE_COLOR_UNIT);
+            target.bind_depth(SCENE_DEPTH_UNIT);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+}
+
+/// Tunable look of the water surface, uploaded as uniforms of the water shader.
+#[derive(Debug, Clone, Copy)]
+pub struct WaterParams {
+    pub wave_amplitude: f32,
+    pub wave_speed: f32,
+    /// Screen-space UV offset applied along the surface normal for refraction
+    pub refraction_strength: f32,
+    /// Per-channel absorption per block of water depth
+    pub absorption: Vec3,
+    /// Color approached in deep water
+    pub deep_color: Vec3,
+}
+
+impl Default for WaterParams {
+    fn default() -> Self {
+        Self {
+            wave_amplitude: water::WAVE_AMPLITUDE,
+            wave_speed: water::WAVE_SPEED,
+            refraction_strength: water::REFRACTION_STRENGTH,
+            absorption: Vec3::from(water::ABSORPTION),
+            deep_color: Vec3::from(water::DEEP_COLOR),
+        }
+    }
+}
+
+impl WaterParams {
+    /// Sets the water shader's uniforms. `underwater` switches the surface to its
+    /// seen-from-below look (no sky reflection).
+    pub unsafe fn apply(&self, shader: &ShaderProgram, time: f32, screen_size: Vec2, underwater: bool) {
+        shader.set("uTime", &time);
+        shader.set("uScreenSize", &screen_size);
+        shader.set("uUnderwater", &underwater);
+        shader.set("uWaveAmplitude", &self.wave_amplitude);
+        shader.set("uWaveSpeed", &self.wave_speed);
+        shader.set("uRefractionStrength", &self.refraction_strength);
+        shader.set("uAbsorption", &self.absorption);
+        shader.set("uDeepColor", &self.deep_color);
+        shader.set_sampler("uSceneColor", SCENE_COLOR_UNIT);
+        shader.set_sampler("uSceneDepth", SCENE_DEPTH_UNIT);
+    }
+}
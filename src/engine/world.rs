@@ -3,8 +3,12 @@ use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use crate::engine::block::Block;
 use crate::engine::chunk::{Chunk, ChunkPos, CHUNK_SIZE};
-use crate::engine::camera::Camera;
-use crate::engine::constants::{MAX_NEW_CHUNKS_PER_FRAME, DEFAULT_RENDER_DISTANCE, MAX_MESH_REBUILDS_PER_FRAME, MAX_CHUNK_RECEIVES_PER_FRAME};
+use crate::engine::camera::{Camera, Frustum};
+use crate::engine::buffer_pool::{ChunkBufferPool, ChunkMesh};
+use crate::engine::debug_draw::{self, DebugDraw};
+use crate::engine::render_mode::RenderMode;
+use crate::engine::lod::{ColumnRect, LodTerrain};
+use crate::engine::constants::{MAX_NEW_CHUNKS_PER_FRAME, DEFAULT_RENDER_DISTANCE, MAX_MESH_REBUILDS_PER_FRAME, MAX_CHUNK_RECEIVES_PER_FRAME, MIN_CHUNK_Y, MAX_CHUNK_Y};
 use crate::engine::constants::{noise, blocks};
 
 /// Message sent to worker threads for chunk generation
@@ -18,11 +22,59 @@ struct ChunkGenResult {
     blocks: Vec<Block>,
 }
 
+/// Which chunk meshes `World::render_chunks` draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkPass {
+    /// Solid blocks
+    Opaque,
+    /// Water faces, for the water shader
+    Water,
+    /// Other alpha-blended blocks
+    Transparent,
+}
+
+/// Chunk and geometry counts for debug output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WorldStats {
+    pub loaded_chunks: usize,
+    /// Requested from the generator threads but not back yet
+    pub pending_chunks: usize,
+    /// Waiting for a mesh rebuild
+    pub dirty_chunks: usize,
+    pub lod_chunks: usize,
+    /// Vertices in all terrain meshes, full resolution and LOD
+    pub vertices: usize,
+}
+
+/// A block hit by `World::raycast`.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockHit {
+    pub pos: glam::IVec3,
+    pub block: Block,
+    /// Outward normal of the face the ray entered through (zero if the ray started inside the block)
+    pub normal: glam::IVec3,
+    pub distance: f32,
+}
+
 /// Manages the voxel world, including chunk loading and terrain generation.
 pub struct World {
     pub chunks: HashMap<(i32, i32, i32), Chunk>,
     pub render_distance: i32,
     pub last_player_chunk: (i32, i32, i32),
+    /// Shared vertex buffers holding all chunk geometry
+    pub mesh_pool: ChunkBufferPool,
+    /// Draw terrain with multi-draw indirect when the context supports it
+    pub use_indirect_draws: bool,
+    /// Coarser terrain rings beyond the render distance
+    pub lod: LodTerrain,
+    /// Culls against this instead of the camera's frustum when set, to inspect culling from outside
+    pub frozen_frustum: Option<Frustum>,
+    /// GL state for `render_chunks`; the caller binds the matching shader variant
+    pub render_mode: RenderMode,
+    /// Wait for every requested chunk each frame instead of taking what the workers have finished
+    /// so far, so which chunks exist depends only on the frames simulated. Set while input is
+    /// recorded or replayed.
+    pub deterministic_loading: bool,
     
     // Threading for chunk generation
     chunk_request_tx: Sender<ChunkGenRequest>,
@@ -85,6 +137,12 @@ impl World {
             chunks: HashMap::new(),
             render_distance: DEFAULT_RENDER_DISTANCE,
             last_player_chunk: (i32::MAX, i32::MAX, i32::MAX), // Force initial load
+            mesh_pool: ChunkBufferPool::new(),
+            use_indirect_draws: true,
+            lod: LodTerrain::new(),
+            frozen_frustum: None,
+            render_mode: RenderMode::Normal,
+            deterministic_loading: false,
             chunk_request_tx: request_tx,
             chunk_result_rx: result_rx,
             pending_chunks: HashSet::new(),
@@ -102,12 +160,126 @@ impl World {
         self.render_distance
     }
 
+    /// Distance from the player to where terrain stops being drawn, for fading it into fog.
+    pub fn view_distance(&self) -> f32 {
+        let full_res = (self.render_distance * CHUNK_SIZE as i32) as f32;
+        self.lod.view_distance().map_or(full_res, |d| d.max(full_res))
+    }
+
     /// Returns the number of loaded chunks.
     #[allow(dead_code)]
     pub fn chunk_count(&self) -> usize {
         self.chunks.len()
     }
 
+    /// Columns drawn with full-resolution chunks: the render distance around the player's chunk.
+    /// Chunks kept loaded beyond it are hidden while LOD terrain covers that area.
+    pub fn full_res_columns(&self) -> ColumnRect {
+        let size = CHUNK_SIZE as i32;
+        let (px, _, pz) = self.last_player_chunk;
+        if px == i32::MAX { return ColumnRect::EMPTY; } // Nothing loaded yet
+        ColumnRect {
+            min_x: (px - self.render_distance) * size,
+            min_z: (pz - self.render_distance) * size,
+            max_x: (px + self.render_distance + 1) * size,
+            max_z: (pz + self.render_distance + 1) * size,
+        }
+    }
+
+    /// Returns the block at a world block position, or air if its chunk isn't loaded.
+    pub fn block_at(&self, x: i32, y: i32, z: i32) -> Block {
+        self.loaded_block_at(x, y, z).unwrap_or(Block::Air)
+    }
+
+    /// Returns the block at a world position, or `None` if its chunk hasn't loaded yet.
+    /// Above and below the generated chunk layers everything is air.
+    pub fn loaded_block_at(&self, x: i32, y: i32, z: i32) -> Option<Block> {
+        let size = CHUNK_SIZE as i32;
+        let key = (x.div_euclid(size), y.div_euclid(size), z.div_euclid(size));
+        if !(MIN_CHUNK_Y..=MAX_CHUNK_Y).contains(&key.1) { return Some(Block::Air); }
+        let chunk = self.chunks.get(&key)?;
+        Some(chunk.get_block(x.rem_euclid(size) as usize, y.rem_euclid(size) as usize, z.rem_euclid(size) as usize))
+    }
+
+    /// Returns the block containing a world-space point.
+    pub fn block_at_point(&self, point: glam::Vec3) -> Block {
+        let p = point.floor();
+        self.block_at(p.x as i32, p.y as i32, p.z as i32)
+    }
+
+    /// Walks the blocks along a ray (Amanatides-Woo DDA) and returns the first one that isn't air
+    /// or water, within `max_distance`.
+    pub fn raycast(&self, origin: glam::Vec3, direction: glam::Vec3, max_distance: f32) -> Option<BlockHit> {
+        let dir = direction.normalize_or_zero();
+        if dir == glam::Vec3::ZERO { return None; }
+        let mut pos = origin.floor().as_ivec3();
+        let step = dir.signum().as_ivec3();
+        // Ray distance between voxel boundaries on each axis, and to the first boundary
+        let delta = (1.0 / dir).abs();
+        let first_boundary = |o: f32, d: f32, delta: f32| {
+            if d > 0.0 { (o.floor() + 1.0 - o) * delta } else if d < 0.0 { (o - o.floor()) * delta } else { f32::INFINITY }
+        };
+        let mut t_max = glam::Vec3::new(
+            first_boundary(origin.x, dir.x, delta.x),
+            first_boundary(origin.y, dir.y, delta.y),
+            first_boundary(origin.z, dir.z, delta.z),
+        );
+        let mut normal = glam::IVec3::ZERO;
+        let mut distance = 0.0;
+        while distance <= max_distance {
+            let block = self.block_at(pos.x, pos.y, pos.z);
+            if block != Block::Air && block != Block::Solid(blocks::WATER) {
+                return Some(BlockHit { pos, block, normal, distance });
+            }
+            let axis = if t_max.x < t_max.y && t_max.x < t_max.z { 0 } else if t_max.y < t_max.z { 1 } else { 2 };
+            distance = t_max[axis];
+            t_max[axis] += delta[axis];
+            pos[axis] += step[axis];
+            normal = glam::IVec3::ZERO;
+            normal[axis] = -step[axis];
+        }
+        None
+    }
+
+    /// Boxes every loaded chunk: green once meshed, yellow while waiting for a rebuild,
+    /// red while its blocks are still being generated. Empty chunks are left out.
+    pub fn debug_draw_chunks(&self, draw: &mut DebugDraw) {
+        let size = CHUNK_SIZE as f32;
+        let bounds = |(x, y, z): (i32, i32, i32)| {
+            let min = glam::Vec3::new(x as f32, y as f32, z as f32) * size;
+            (min, min + glam::Vec3::splat(size))
+        };
+        for chunk in self.chunks.values() {
+            let color = if chunk.dirty {
+                debug_draw::YELLOW
+            } else if chunk.mesh.is_some() || chunk.transparent_mesh.is_some() || chunk.water_mesh.is_some() {
+                debug_draw::GREEN
+            } else {
+                continue;
+            };
+            let (min, max) = bounds((chunk.pos.x, chunk.pos.y, chunk.pos.z));
+            draw.aabb(min, max, color);
+        }
+        for &key in &self.pending_chunks {
+            let (min, max) = bounds(key);
+            draw.aabb(min, max, debug_draw::RED);
+        }
+    }
+
+    pub fn stats(&self) -> WorldStats {
+        let vertices = |meshes: [&Option<ChunkMesh>; 3]| -> usize {
+            meshes.iter().filter_map(|m| m.as_ref()).map(|m| m.vertex_count() as usize).sum()
+        };
+        WorldStats {
+            loaded_chunks: self.chunks.len(),
+            pending_chunks: self.pending_chunks.len(),
+            dirty_chunks: self.chunks.values().filter(|c| c.dirty).count(),
+            lod_chunks: self.lod.chunk_count(),
+            vertices: self.chunks.values().map(|c| vertices([&c.mesh, &c.transparent_mesh, &c.water_mesh])).sum::<usize>()
+                + self.lod.chunks.values().map(|c| vertices([&c.mesh, &c.transparent_mesh, &c.water_mesh])).sum::<usize>(),
+        }
+    }
+
     pub fn update_chunks(&mut self, player_pos: glam::Vec3) {
         let player_chunk_x = (player_pos.x / (CHUNK_SIZE as f32)).floor() as i32;
         let player_chunk_z = (player_pos.z / (CHUNK_SIZE as f32)).floor() as i32;
@@ -117,11 +289,20 @@ impl World {
         let moved = current_chunk != self.last_player_chunk;
         if moved { self.last_player_chunk = current_chunk; }
 
-        // Receive completed chunks from worker threads (limit per frame)
+        // Receive completed chunks from worker threads (limit per frame, or all of them when
+        // loading deterministically)
         let mut received_this_frame = 0;
-        while let Ok(result) = self.chunk_result_rx.try_recv() {
+        loop {
+            let result = if self.deterministic_loading {
+                if self.pending_chunks.is_empty() { break; }
+                self.chunk_result_rx.recv().ok()
+            } else {
+                self.chunk_result_rx.try_recv().ok()
+            };
+            let Some(result) = result else { break };
             let key = (result.pos.x, result.pos.y, result.pos.z);
-            self.pending_chunks.remove(&key);
+            // Requests cancelled while in flight would otherwise land at unpredictable times
+            if !self.pending_chunks.remove(&key) { continue; }
             
             // Only add chunk if it's still in render distance
             let dx = (result.pos.x - player_chunk_x).abs();
@@ -130,7 +311,9 @@ impl World {
                 let mut chunk = Chunk::new(result.pos);
                 chunk.blocks = result.blocks;
                 chunk.dirty = true;
-                self.chunks.insert(key, chunk);
+                if let Some(mut old) = self.chunks.insert(key, chunk) {
+                    old.release_meshes(&mut self.mesh_pool);
+                }
                 
                 // Mark neighbors dirty
                 let neighbor_offsets = [(-1,0,0),(1,0,0),(0,0,-1),(0,0,1),(0,-1,0),(0,1,0)];
@@ -142,14 +325,13 @@ impl World {
             }
             
             received_this_frame += 1;
-            if received_this_frame >= MAX_CHUNK_RECEIVES_PER_FRAME { break; }
+            if received_this_frame >= MAX_CHUNK_RECEIVES_PER_FRAME && !self.deterministic_loading { break; }
         }
 
         // Request new chunks (prioritize by distance to player)
         let mut chunks_to_request: Vec<(i32, i32, i32, i32)> = Vec::new(); // (cx, cy, cz, dist_sq)
         
-        // Load chunks vertically from y=-1 (underground) to y=4 (up to ~160 blocks high)
-        for cy in -1..=4 {
+        for cy in MIN_CHUNK_Y..=MAX_CHUNK_Y {
             for cz in (player_chunk_z - self.render_distance)..=(player_chunk_z + self.render_distance) {
                 for cx in (player_chunk_x - self.render_distance)..=(player_chunk_x + self.render_distance) {
                     let key = (cx, cy, cz);
@@ -185,7 +367,11 @@ impl World {
                 })
                 .cloned()
                 .collect();
-            for key in to_remove { self.chunks.remove(&key); }
+            for key in to_remove {
+                if let Some(mut chunk) = self.chunks.remove(&key) {
+                    chunk.release_meshes(&mut self.mesh_pool);
+                }
+            }
             
             // Also cancel pending chunks that are now out of range
             self.pending_chunks.retain(|(cx, _cy, cz)| {
@@ -194,10 +380,14 @@ impl World {
                 dx <= unload_distance && dz <= unload_distance
             });
         }
+
+        let full_res = self.full_res_columns();
+        self.lod.deterministic_loading = self.deterministic_loading;
+        self.lod.update(player_pos, full_res, &mut self.mesh_pool);
     }
 
     /// Generate terrain data on a worker thread (no Chunk creation, just block data)
-    fn generate_terrain_data(pos: ChunkPos) -> Vec<Block> {
+    pub fn generate_terrain_data(pos: ChunkPos) -> Vec<Block> {
         use ::noise::{Perlin, NoiseFn};
         
         let terrain_noise = Perlin::new(noise::SEED);
@@ -215,32 +405,7 @@ impl World {
                 let global_z = pos.z * CHUNK_SIZE as i32 + z as i32;
                 let fx = global_x as f64;
                 let fz = global_z as f64;
-
-                // Main terrain height using fractal noise
-                let mut height = 0.0;
-                let mut amp = 1.0;
-                let mut freq = noise::TERRAIN_SCALE;
-                let mut max_amp = 0.0;
-                
-                for _ in 0..noise::TERRAIN_OCTAVES {
-                    height += terrain_noise.get([fx * freq, fz * freq]) * amp;
-                    max_amp += amp;
-                    amp *= noise::TERRAIN_PERSISTENCE;
-                    freq *= 2.0;
-                }
-                height = height / max_amp; // Normalize to -1 to 1
-                
-                // Add detail noise
-                let detail = detail_noise.get([fx * noise::DETAIL_SCALE, fz * noise::DETAIL_SCALE]);
-                
-                // Calculate final surface height
-                let surface_y = noise::BASE_HEIGHT 
-                    + (height * noise::TERRAIN_HEIGHT) as i32
-                    + (detail * noise::DETAIL_HEIGHT) as i32;
-                
-                // Determine biome based on height
-                let is_beach = surface_y >= noise::SEA_LEVEL - 2 && surface_y <= noise::SEA_LEVEL + 2;
-                let is_underwater = surface_y < noise::SEA_LEVEL;
+                let surface_y = surface_height(&terrain_noise, &detail_noise, global_x, global_z);
 
                 // Fill blocks for this column
                 for y in 0..CHUNK_SIZE {
@@ -269,22 +434,8 @@ impl World {
                         } else {
                             blocks::STONE
                         }
-                    } else if depth == 0 {
-                        // Surface
-                        if is_underwater || is_beach {
-                            blocks::SAND
-                        } else {
-                            blocks::GRASS
-                        }
-                    } else if depth <= noise::DIRT_DEPTH {
-                        // Subsurface
-                        if is_underwater || is_beach {
-                            blocks::SAND
-                        } else {
-                            blocks::DIRT
-                        }
                     } else {
-                        blocks::STONE
+                        column_block(surface_y, depth)
                     };
                     
                     block_data[idx] = Block::Solid(block_id);
@@ -358,131 +509,170 @@ impl World {
     }
 }
 
-/// Sample 2D fractal Perlin noise with multiple octaves
-fn sample_fractal_noise_2d(
-    noise: &::noise::Perlin,
-    x: f64, z: f64,
-    base_scale: f64,
-    octaves: usize,
-    persistence: f64,
-) -> f64 {
+/// Height of the topmost solid block of the terrain column at a world X/Z, before caves are carved.
+pub fn surface_height(terrain_noise: &::noise::Perlin, detail_noise: &::noise::Perlin, x: i32, z: i32) -> i32 {
     use ::noise::NoiseFn;
-    
-    let mut total = 0.0;
-    let mut amplitude = 1.0;
-    let mut frequency = base_scale;
-    let mut max_amplitude = 0.0;
-    
-    for _ in 0..octaves {
-        total += noise.get([x * frequency, z * frequency]) * amplitude;
-        max_amplitude += amplitude;
-        amplitude *= persistence;
-        frequency *= 2.0; // Lacunarity
-    }
-    
-    total / max_amplitude
-}
 
-/// Sample 3D fractal Perlin noise with multiple octaves
-fn sample_3d_fractal_noise(
-    noise: &::noise::Perlin,
-    x: f64, y: f64, z: f64,
-    base_scale: f64,
-    octaves: usize,
-    persistence: f64,
-) -> f64 {
-    use ::noise::NoiseFn;
-    
-    let mut total = 0.0;
-    let mut amplitude = 1.0;
-    let mut frequency = base_scale;
-    let mut max_amplitude = 0.0;
+    let fx = x as f64;
+    let fz = z as f64;
+
+    // Main terrain height using fractal noise
+    let mut height = 0.0;
+    let mut amp = 1.0;
+    let mut freq = noise::TERRAIN_SCALE;
+    let mut max_amp = 0.0;
     
-    for _ in 0..octaves {
-        total += noise.get([x * frequency, y * frequency, z * frequency]) * amplitude;
-        max_amplitude += amplitude;
-        amplitude *= persistence;
-        frequency *= 2.0;
+    for _ in 0..noise::TERRAIN_OCTAVES {
+        height += terrain_noise.get([fx * freq, fz * freq]) * amp;
+        max_amp += amp;
+        amp *= noise::TERRAIN_PERSISTENCE;
+        freq *= 2.0;
     }
+    height /= max_amp; // Normalize to -1 to 1
+    
+    // Add detail noise
+    let detail = detail_noise.get([fx * noise::DETAIL_SCALE, fz * noise::DETAIL_SCALE]);
     
-    total / max_amplitude
+    // Calculate final surface height
+    noise::BASE_HEIGHT 
+        + (height * noise::TERRAIN_HEIGHT) as i32
+        + (detail * noise::DETAIL_HEIGHT) as i32
 }
 
-/// Smoothstep function for smooth blending (cubic Hermite interpolation)
-fn smoothstep(t: f64) -> f64 {
-    let t = t.clamp(0.0, 1.0);
-    t * t * (3.0 - 2.0 * t)
+/// Block `depth` blocks below the top of a column whose surface is at `surface_y`
+/// (bedrock layers not included).
+pub fn column_block(surface_y: i32, depth: i32) -> u8 {
+    // Determine biome based on height
+    let is_beach = (noise::SEA_LEVEL - 2..=noise::SEA_LEVEL + 2).contains(&surface_y);
+    let is_underwater = surface_y < noise::SEA_LEVEL;
+
+    if depth == 0 {
+        // Surface
+        if is_underwater || is_beach {
+            blocks::SAND
+        } else {
+            blocks::GRASS
+        }
+    } else if depth <= noise::DIRT_DEPTH {
+        // Subsurface
+        if is_underwater || is_beach {
+            blocks::SAND
+        } else {
+            blocks::DIRT
+        }
+    } else {
+        blocks::STONE
+    }
 }
 
 impl World {
-    pub fn render_chunks(&mut self, camera: &Camera, shader: &crate::engine::shader::ShaderProgram) {
-        let frustum = camera.frustum();
+    /// Draws one pass of chunk geometry with the currently bound shader.
+    /// Opaque and water meshes go front-to-back with depth writes; transparent meshes are
+    /// alpha blended back-to-front. Water is drawn double-sided so the surface shows from below.
+    pub fn render_chunks(&mut self, camera: &Camera, pass: ChunkPass) {
+        let frustum = self.frozen_frustum.unwrap_or_else(|| camera.frustum());
+        let mode = self.render_mode;
+        unsafe { mode.begin(); }
+        self.render_chunks_in(&frustum, camera.position, pass, mode);
+        unsafe { mode.end(); }
+    }
+
+    /// Draws the opaque chunks inside a light's view-projection, for shadow map passes.
+    pub fn render_shadow_casters(&mut self, light_view_proj: glam::Mat4) {
+        let center = light_view_proj.inverse().transform_point3(glam::Vec3::ZERO);
+        self.render_chunks_in(&Frustum::from_matrix(light_view_proj), center, ChunkPass::Opaque, RenderMode::Normal);
+    }
+
+    /// Draws one pass of the chunks inside `frustum`, sorted by distance from `origin`.
+    fn render_chunks_in(&mut self, frustum: &Frustum, origin: glam::Vec3, pass: ChunkPass, mode: RenderMode) {
         let chunk_size_f = CHUNK_SIZE as f32;
-        let cam_pos = camera.position;
+        let cam_pos = origin;
         
-        // Collect visible chunks with distances for sorting
-        // We need both opaque (front-to-back) and transparent (back-to-front) meshes
-        let mut opaque_visible: Vec<_> = Vec::new();
-        let mut transparent_visible: Vec<_> = Vec::new();
+        // Collect visible chunk meshes for this pass with distances for sorting
+        let mut visible: Vec<(ChunkMesh, glam::Vec3, f32)> = Vec::new();
         
+        let full_res = self.full_res_columns();
         for ((cx, cy, cz), chunk) in self.chunks.iter() {
-            let chunk_center = glam::vec3(
-                *cx as f32 * chunk_size_f + chunk_size_f * 0.5,
-                *cy as f32 * chunk_size_f + chunk_size_f * 0.5,
-                *cz as f32 * chunk_size_f + chunk_size_f * 0.5,
-            );
+            if self.lod.enabled && !full_res.contains(cx * CHUNK_SIZE as i32, cz * CHUNK_SIZE as i32) { continue; }
+            let mesh = match pass {
+                ChunkPass::Opaque => chunk.mesh,
+                ChunkPass::Water => chunk.water_mesh,
+                ChunkPass::Transparent => chunk.transparent_mesh,
+            };
+            let Some(mesh) = mesh else { continue };
+            let chunk_world_pos = glam::vec3(*cx as f32 * chunk_size_f, *cy as f32 * chunk_size_f, *cz as f32 * chunk_size_f);
+            let max = chunk_world_pos + glam::vec3(chunk_size_f, chunk_size_f, chunk_size_f);
+            if !frustum.contains_aabb(chunk_world_pos, max) { continue; }
+
+            let chunk_center = chunk_world_pos + glam::Vec3::splat(chunk_size_f * 0.5);
             let dist_sq = (chunk_center - cam_pos).length_squared();
-            
-            if let Some(mesh) = chunk.mesh.as_ref() {
-                opaque_visible.push((*cx, *cy, *cz, mesh, dist_sq));
-            }
-            if let Some(mesh) = chunk.transparent_mesh.as_ref() {
-                transparent_visible.push((*cx, *cy, *cz, mesh, dist_sq));
-            }
+            visible.push((mesh, chunk_world_pos, dist_sq));
         }
+        for chunk in self.lod.chunks.values() {
+            let mesh = match pass {
+                ChunkPass::Opaque => chunk.mesh,
+                ChunkPass::Water => chunk.water_mesh,
+                ChunkPass::Transparent => chunk.transparent_mesh,
+            };
+            let Some(mesh) = mesh else { continue };
+            let min = chunk.offset();
+            let size = chunk.key.size() as f32;
+            if !frustum.contains_aabb(min, min + glam::Vec3::splat(size)) { continue; }
+            let dist_sq = (min + glam::Vec3::splat(size * 0.5) - cam_pos).length_squared();
+            visible.push((mesh, min, dist_sq));
+        }
+        if visible.is_empty() { return; }
         
-        // Sort opaque front-to-back to improve depth test efficiency
-        opaque_visible.sort_by(|a, b| a.4.partial_cmp(&b.4).unwrap_or(std::cmp::Ordering::Equal));
-        
-        // Sort transparent back-to-front for correct alpha blending
-        transparent_visible.sort_by(|a, b| b.4.partial_cmp(&a.4).unwrap_or(std::cmp::Ordering::Equal));
+        if pass != ChunkPass::Transparent {
+            // Sort by pool page (fewer indirect batches), then front-to-back to improve depth test efficiency
+            visible.sort_by(|a, b| a.0.page.cmp(&b.0.page).then(a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal)));
+        } else {
+            // Back-to-front for correct alpha blending
+            visible.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+        }
         
+        let indirect = self.use_indirect_draws && ChunkBufferPool::supports_indirect();
         unsafe {
-            // Pass 1: Render opaque geometry
-            for (cx, cy, cz, mesh, _) in opaque_visible {
-                let chunk_world_pos = glam::vec3(cx as f32 * chunk_size_f, cy as f32 * chunk_size_f, cz as f32 * chunk_size_f);
-                let min = chunk_world_pos;
-                let max = chunk_world_pos + glam::vec3(chunk_size_f, chunk_size_f, chunk_size_f);
-                if !frustum.contains_aabb(min, max) { continue; }
-                
-                let model = glam::Mat4::from_translation(chunk_world_pos);
-                shader.set_mat4("uModel", &model);
-                mesh.draw();
-            }
-            
-            // Pass 2: Render transparent geometry with blending
-            if !transparent_visible.is_empty() {
-                gl::Enable(gl::BLEND);
-                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-                gl::DepthMask(gl::FALSE); // Don't write to depth buffer for transparent objects
-                
-                for (cx, cy, cz, mesh, _) in transparent_visible {
-                    let chunk_world_pos = glam::vec3(cx as f32 * chunk_size_f, cy as f32 * chunk_size_f, cz as f32 * chunk_size_f);
-                    let min = chunk_world_pos;
-                    let max = chunk_world_pos + glam::vec3(chunk_size_f, chunk_size_f, chunk_size_f);
-                    if !frustum.contains_aabb(min, max) { continue; }
+            match pass {
+                ChunkPass::Opaque => self.draw_chunk_meshes(&visible, indirect),
+                ChunkPass::Water => {
+                    gl::Disable(gl::CULL_FACE);
+                    self.draw_chunk_meshes(&visible, indirect);
+                    gl::Enable(gl::CULL_FACE);
+                }
+                ChunkPass::Transparent if mode.overrides_blending() => self.draw_chunk_meshes(&visible, indirect),
+                ChunkPass::Transparent => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                    gl::DepthMask(gl::FALSE); // Don't write to depth buffer for transparent objects
+                    
+                    self.draw_chunk_meshes(&visible, indirect);
                     
-                    let model = glam::Mat4::from_translation(chunk_world_pos);
-                    shader.set_mat4("uModel", &model);
-                    mesh.draw();
+                    gl::DepthMask(gl::TRUE);
+                    gl::Disable(gl::BLEND);
                 }
-                
-                gl::DepthMask(gl::TRUE);
-                gl::Disable(gl::BLEND);
             }
         }
     }
 
+    /// Draws a sorted list of (mesh, chunk offset, distance) either with multi-draw indirect
+    /// or, on GL 3.3, one draw call per chunk.
+    unsafe fn draw_chunk_meshes(&mut self, meshes: &[(ChunkMesh, glam::Vec3, f32)], indirect: bool) {
+        if indirect {
+            let draws: Vec<(ChunkMesh, glam::Vec3)> = meshes.iter().map(|&(mesh, offset, _)| (mesh, offset)).collect();
+            self.mesh_pool.draw_indirect(&draws);
+        } else {
+            for (mesh, offset, _) in meshes {
+                self.mesh_pool.draw_at(mesh, *offset);
+            }
+        }
+    }
+
+    /// Call once at the start of every frame; recycles chunk buffer ranges the GPU has finished with.
+    pub fn begin_frame(&mut self) {
+        self.mesh_pool.begin_frame();
+    }
+
     /// Rebuilds meshes for all chunks marked as dirty.
     /// Uses a two-pass approach to avoid unsafe aliasing: first collect neighbor data, then rebuild.
     /// Limits rebuilds per frame to prevent lag spikes.
@@ -518,12 +708,15 @@ impl World {
                 .map(|c| c.blocks.clone());
 
             if let (Some(chunk), Some(chunk_blocks)) = (self.chunks.get_mut(&key), current_chunk_blocks) {
-                chunk.rebuild_mesh(|lx, ly, lz| {
+                chunk.rebuild_mesh(&mut self.mesh_pool, |lx, ly, lz| {
                     Self::get_block_from_neighbors_with_blocks(key, lx, ly, lz, &neighbor_blocks, &chunk_blocks)
                 });
                 rebuilt_count += 1;
             }
         }
+
+        let player_pos = glam::vec3(((cam_chunk.0 as f32) + 0.5) * CHUNK_SIZE as f32, 0.0, ((cam_chunk.2 as f32) + 0.5) * CHUNK_SIZE as f32);
+        self.lod.rebuild_dirty(&mut self.mesh_pool, player_pos);
     }
 
     /// Collects blocks from neighboring chunks that might be needed for mesh generation.
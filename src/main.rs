@@ -1,17 +1,58 @@
 mod engine;
 
+use std::collections::hash_map::{Entry, HashMap};
+use std::path::Path;
+use std::rc::Rc;
 use engine::game::Game;
 use engine::core::Engine;
-use engine::shader::ShaderProgram;
-use engine::world::World;
-use engine::texture::{Texture, generate_block_atlas};
-use engine::shader_sources::{BLOCK_WORLD_VERT, BLOCK_WORLD_FRAG};
-use engine::constants::{DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT};
+use engine::replay::ReplayStart;
+use engine::shader::UniformBuffer;
+use engine::shader_loader::ReloadableShader;
+use engine::actions;
+use engine::camera::{CameraUniform, CAMERA_UNIFORM_BINDING};
+use engine::camera_controller::{CameraController, CollisionQuery, FlyCamController, WalkController, OrbitController};
+use engine::world::{World, ChunkPass};
+use engine::render_mode::RenderMode;
+use engine::block::Block;
+use engine::fog::{FogUniform, FOG_UNIFORM_BINDING};
+use engine::lighting::{SunUniform, SUN_UNIFORM_BINDING};
+use engine::sky::{Sky, SkyRenderer};
+use engine::shadow::{ShadowMap, ShadowSettings};
+use engine::water::{SceneCopy, WaterParams};
+use engine::mesh::Mesh;
+use engine::entity::Entity;
+use engine::entity_renderer::EntityRenderer;
+use engine::texture::{TextureArray, ResourcePack, build_block_texture_array};
+use engine::tile_animation::TileAnimations;
+use engine::shader_sources::{BLOCK_WORLD_VERT, BLOCK_WORLD_FRAG, ENTITY_VERT, ENTITY_FRAG, WATER_VERT, WATER_FRAG, SHADOW_DEPTH_VERT, SHADOW_DEPTH_FRAG};
+use engine::constants::{DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT, RESOURCE_PACK_DIR, blocks};
+
+/// How far the debug HUD looks for the targeted block.
+const LOOK_TARGET_DISTANCE: f32 = 64.0;
 
 pub struct DemoGame {
-    shader: Option<ShaderProgram>,
-    block_atlas: Option<Texture>,
+    shader: Option<ReloadableShader>,
+    entity_shader: Option<ReloadableShader>,
+    water_shader: Option<ReloadableShader>,
+    shadow_shader: Option<ReloadableShader>,
+    /// Block shader variants for debug render modes, compiled the first time each is used
+    render_mode_shaders: HashMap<RenderMode, ReloadableShader>,
+    camera_ubo: Option<UniformBuffer>,
+    fog_ubo: Option<UniformBuffer>,
+    sun_ubo: Option<UniformBuffer>,
+    sky: Sky,
+    sky_renderer: Option<SkyRenderer>,
+    shadow_map: Option<ShadowMap>,
+    scene_copy: SceneCopy,
+    water: WaterParams,
+    block_textures: Option<TextureArray>,
+    tile_animations: TileAnimations,
+    entity_renderer: Option<EntityRenderer>,
+    entities: Vec<Entity>,
     world: World,
+    /// Camera controls to cycle through; the first is active at startup
+    controllers: Vec<Box<dyn CameraController>>,
+    active_controller: usize,
 }
 
 impl Default for DemoGame {
@@ -22,46 +63,329 @@ impl Default for DemoGame {
 
 impl DemoGame {
     pub fn new() -> Self {
-        Self { shader: None, block_atlas: None, world: World::new() }
+        Self {
+            shader: None,
+            entity_shader: None,
+            water_shader: None,
+            shadow_shader: None,
+            render_mode_shaders: HashMap::new(),
+            camera_ubo: None,
+            fog_ubo: None,
+            sun_ubo: None,
+            sky: Sky::new(),
+            sky_renderer: None,
+            shadow_map: None,
+            scene_copy: SceneCopy::new(),
+            water: WaterParams::default(),
+            block_textures: None,
+            tile_animations: TileAnimations::default(),
+            entity_renderer: None,
+            entities: Vec::new(),
+            world: World::new(),
+            controllers: vec![
+                Box::new(FlyCamController::default()),
+                Box::new(WalkController::default()),
+                Box::new(OrbitController::default()),
+            ],
+            active_controller: 0,
+        }
     }
+
+    fn cycle_render_mode(&mut self) {
+        let mode = self.world.render_mode.next();
+        if let Some(define) = mode.shader_define() {
+            if let Entry::Vacant(slot) = self.render_mode_shaders.entry(mode) {
+                match unsafe { load_render_mode_shader(mode, define) } {
+                    Ok(shader) => { slot.insert(shader); }
+                    Err(e) => eprintln!("Render mode {} unavailable: {}", mode.name(), e),
+                }
+            }
+        }
+        self.world.render_mode = mode;
+        println!("Render mode: {}", mode.name());
+    }
+}
+
+/// What the camera controllers collide with: solid blocks other than water, which the
+/// player moves through. Chunks that haven't loaded yet count as solid.
+struct WorldCollision<'a>(&'a World);
+
+impl CollisionQuery for WorldCollision<'_> {
+    fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        match self.0.loaded_block_at(x, y, z) {
+            Some(Block::Solid(id)) => id != blocks::WATER,
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    fn is_loaded(&self, x: i32, y: i32, z: i32) -> bool { self.0.loaded_block_at(x, y, z).is_some() }
+}
+
+/// Draws one terrain pass, timing its CPU side as `render_chunks`.
+fn render_chunks(world: &mut World, engine: &mut Engine, pass: ChunkPass) {
+    let scope = engine.profiler.cpu_begin("render_chunks");
+    world.render_chunks(&engine.camera, pass);
+    engine.profiler.cpu_end(scope);
+}
+
+/// Compiles the block shader for a debug render mode, binding only the uniform blocks it reads.
+unsafe fn load_render_mode_shader(mode: RenderMode, define: &str) -> Result<ReloadableShader, String> {
+    let mut shader = ReloadableShader::load(BLOCK_WORLD_VERT, BLOCK_WORLD_FRAG, &[(define, "1")])?;
+    shader.bind_uniform_block("Camera", CAMERA_UNIFORM_BINDING);
+    if mode.is_fogged() { shader.bind_uniform_block("Fog", FOG_UNIFORM_BINDING); }
+    if mode.is_lit() { shader.bind_uniform_block("Sun", SUN_UNIFORM_BINDING); }
+    Ok(shader)
 }
 
 impl Game for DemoGame {
-    fn on_start(&mut self, _engine: &mut Engine) {
+    fn on_start(&mut self, engine: &mut Engine) {
+        engine.set_cursor_captured(true);
         unsafe {
-            self.shader = Some(ShaderProgram::from_source(BLOCK_WORLD_VERT, BLOCK_WORLD_FRAG)
+            self.shader = Some(ReloadableShader::load(BLOCK_WORLD_VERT, BLOCK_WORLD_FRAG, &[])
                 .expect("shader compile"));
+            self.entity_shader = Some(ReloadableShader::load(ENTITY_VERT, ENTITY_FRAG, &[])
+                .expect("entity shader compile"));
+            self.water_shader = Some(ReloadableShader::load(WATER_VERT, WATER_FRAG, &[])
+                .expect("water shader compile"));
+            for shader in self.shader.iter_mut().chain(self.entity_shader.iter_mut()).chain(self.water_shader.iter_mut()) {
+                shader.bind_uniform_block("Camera", CAMERA_UNIFORM_BINDING);
+                shader.bind_uniform_block("Fog", FOG_UNIFORM_BINDING);
+                shader.bind_uniform_block("Sun", SUN_UNIFORM_BINDING);
+            }
+            self.shadow_shader = Some(ReloadableShader::load(SHADOW_DEPTH_VERT, SHADOW_DEPTH_FRAG, &[])
+                .expect("shadow shader compile"));
+            self.sky_renderer = Some(SkyRenderer::new().expect("sky shader compile"));
+            self.shadow_map = Some(ShadowMap::new(ShadowSettings::default()));
+            self.camera_ubo = Some(UniformBuffer::new(CameraUniform::STD140_FLOATS * 4, CAMERA_UNIFORM_BINDING));
+            self.fog_ubo = Some(UniformBuffer::new(FogUniform::STD140_FLOATS * 4, FOG_UNIFORM_BINDING));
+            self.sun_ubo = Some(UniformBuffer::new(SunUniform::STD140_FLOATS * 4, SUN_UNIFORM_BINDING));
             
-            // Generate and load the block texture atlas
-            self.block_atlas = Some(generate_block_atlas());
+            // Build the block texture array from the resource pack, with procedural fallbacks
+            let pack = ResourcePack::open(RESOURCE_PACK_DIR);
+            let textures = build_block_texture_array(pack.as_ref());
+            self.tile_animations = TileAnimations::load_blocks(pack.as_ref(), textures.width);
+            self.block_textures = Some(textures);
         }
+        self.entity_renderer = Some(EntityRenderer::new());
+
+        // A ring of floating marker cubes around spawn, all sharing one mesh
+        let cube = Rc::new(Mesh::cube([1.0, 1.0, 1.0, 1.0]));
+        for i in 0..8 {
+            let angle = i as f32 / 8.0 * std::f32::consts::TAU;
+            let mut entity = Entity::new(cube.clone());
+            entity.position = glam::vec3(angle.cos() * 12.0, 75.0, angle.sin() * 12.0);
+            entity.tint = glam::vec4(0.5 + 0.5 * angle.cos(), 0.5 + 0.5 * angle.sin(), 0.8, 1.0);
+            self.entities.push(entity);
+        }
+    }
+    fn begin_frame(&mut self, _engine: &mut Engine) {
+        self.world.begin_frame();
     }
-    fn update(&mut self, engine: &mut Engine, _dt: f32) { 
+    fn update(&mut self, engine: &mut Engine, dt: f32) {
+        if engine.actions.was_pressed(actions::QUIT) { engine.should_close = true; }
+        if engine.actions.was_pressed(actions::TOGGLE_CURSOR) {
+            let captured = !engine.is_cursor_captured();
+            engine.set_cursor_captured(captured);
+        }
+        if engine.actions.was_pressed(actions::CYCLE_CAMERA) {
+            self.active_controller = (self.active_controller + 1) % self.controllers.len();
+            let controller = &mut self.controllers[self.active_controller];
+            controller.activate(&engine.camera);
+            println!("Camera: {}", controller.name());
+        }
+        if engine.actions.was_pressed(actions::CYCLE_RENDER_MODE) { self.cycle_render_mode(); }
+        let input = engine.control_input(dt);
+        self.controllers[self.active_controller].update(&mut engine.camera, &input, &WorldCollision(&self.world), dt);
+
+        let scope = engine.profiler.cpu_begin("update_chunks");
+        // Replays only follow the recorded path if the same chunks arrive on the same frames
+        self.world.deterministic_loading = engine.is_recording() || engine.is_replaying();
         self.world.update_chunks(engine.camera.position);
+        engine.profiler.cpu_end(scope);
+        let scope = engine.profiler.cpu_begin("rebuild_dirty");
         self.world.rebuild_dirty();
+        engine.profiler.cpu_end(scope);
+        self.sky.update(dt);
+
+        for entity in &mut self.entities {
+            entity.rotation.y += dt;
+        }
+
+        // A frozen debug frustum also freezes culling, so it can be inspected from outside
+        self.world.frozen_frustum = engine.debug_draw.settings.frozen_frustum;
+        if engine.debug_draw.settings.chunk_states {
+            self.world.debug_draw_chunks(&mut engine.debug_draw);
+        }
+
+        if engine.hud.visible {
+            let stats = self.world.stats();
+            let entity_vertices: usize = self.entities.iter().map(|e| e.mesh.vertex_count() as usize).sum();
+            engine.hud.line(format!("Chunks: {} loaded, {} pending, {} dirty, {} LOD",
+                stats.loaded_chunks, stats.pending_chunks, stats.dirty_chunks, stats.lod_chunks));
+            engine.hud.line(format!("Vertices: {} ({} terrain, {} entities)",
+                stats.vertices + entity_vertices, stats.vertices, entity_vertices));
+            let camera = &engine.camera;
+            let target = match self.world.raycast(camera.position, camera.front(), LOOK_TARGET_DISTANCE) {
+                Some(hit) => format!("{} at {} {} {} ({:.1} away, face {} {} {})", hit.block.name(),
+                    hit.pos.x, hit.pos.y, hit.pos.z, hit.distance, hit.normal.x, hit.normal.y, hit.normal.z),
+                None => "none".to_string(),
+            };
+            engine.hud.line(format!("Looking at: {}", target));
+            engine.hud.line(format!("Camera: {}  Time: {:.2}", self.controllers[self.active_controller].name(), self.sky.time_of_day));
+            engine.hud.line(format!("Render mode: {}", self.world.render_mode.name()));
+        }
+
+        // Pick up shader edits from disk (dev builds only)
+        let shaders = self.shader.iter_mut()
+            .chain(self.entity_shader.iter_mut())
+            .chain(self.water_shader.iter_mut())
+            .chain(self.shadow_shader.iter_mut())
+            .chain(self.render_mode_shaders.values_mut());
+        for shader in shaders {
+            unsafe { shader.poll(); }
+        }
+        if let Some(sky) = &mut self.sky_renderer {
+            unsafe { sky.poll_shader(); }
+        }
+    }
+    fn save_replay_state(&mut self, engine: &Engine, start: &mut ReplayStart) {
+        // Controllers restart from the camera alone, so the replay can rebuild them
+        self.controllers[self.active_controller].activate(&engine.camera);
+        start.set("controller", self.active_controller as f64);
+        start.set("time_of_day", self.sky.time_of_day as f64);
+    }
+    fn restore_replay_state(&mut self, engine: &mut Engine, start: &ReplayStart) {
+        if let Some(index) = start.get("controller") {
+            self.active_controller = (index as usize).min(self.controllers.len() - 1);
+        }
+        self.controllers[self.active_controller].activate(&engine.camera);
+        if let Some(time_of_day) = start.get("time_of_day") { self.sky.time_of_day = time_of_day as f32; }
     }
     fn render(&mut self, engine: &mut Engine) {
+        let underwater = self.world.block_at_point(engine.camera.position) == Block::Solid(blocks::WATER);
+        let sky = self.sky.state();
+        let fog = if underwater { FogUniform::underwater() } else { self.sky.fog(&sky, self.world.view_distance()) };
+        let (fb_width, fb_height) = engine.framebuffer_size;
         unsafe {
-            let cam_uni = engine.camera.projection_matrix() * engine.camera.view_matrix();
+            if underwater {
+                // Nothing is visible beyond the fog underwater, so clear to it instead of the sky
+                gl::ClearColor(fog.color.x, fog.color.y, fog.color.z, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+            }
+            if let Some(ubo) = &self.camera_ubo {
+                ubo.update(&CameraUniform::new(&engine.camera).to_std140());
+            }
+            if let Some(ubo) = &self.fog_ubo {
+                ubo.update(&fog.to_std140());
+            }
+            if let Some(ubo) = &self.sun_ubo {
+                ubo.update(&sky.light.to_std140());
+            }
+
+            // The overdraw heatmap starts from black so every fragment counts
+            let overdraw = self.world.render_mode == RenderMode::Overdraw;
+            if overdraw {
+                gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+            } else if !underwater {
+                if let Some(renderer) = &self.sky_renderer {
+                    let pass = engine.profiler.gpu_begin("sky");
+                    renderer.render(&self.sky, &sky);
+                    engine.profiler.gpu_end(pass);
+                }
+            }
+
+            // Sun shadow cascades from the opaque terrain
+            if let (Some(shadow_map), Some(shader)) = (&mut self.shadow_map, &self.shadow_shader) {
+                let pass = engine.profiler.gpu_begin("shadows");
+                shadow_map.update(&engine.camera, sky.light.direction);
+                shadow_map.render(&mut self.world, shader, (fb_width, fb_height));
+                engine.profiler.gpu_end(pass);
+            }
+            if let Some(textures) = &self.block_textures {
+                self.tile_animations.update(textures, engine.time);
+                textures.bind(0);
+            }
+
+            // Debug render modes draw every terrain pass with their own variant of the block shader
+            if let Some(shader) = self.render_mode_shaders.get(&self.world.render_mode) {
+                shader.use_program();
+                if self.world.render_mode.is_lit() {
+                    if let Some(shadow_map) = &self.shadow_map {
+                        shadow_map.apply(shader);
+                    }
+                }
+                let pass = engine.profiler.gpu_begin("terrain");
+                for chunk_pass in [ChunkPass::Opaque, ChunkPass::Water, ChunkPass::Transparent] {
+                    render_chunks(&mut self.world, engine, chunk_pass);
+                }
+                engine.profiler.gpu_end(pass);
+                if !overdraw {
+                    if let (Some(shader), Some(renderer)) = (&self.entity_shader, &mut self.entity_renderer) {
+                        let pass = engine.profiler.gpu_begin("entities");
+                        shader.use_program();
+                        renderer.render(&self.entities, &engine.camera);
+                        engine.profiler.gpu_end(pass);
+                    }
+                }
+                return;
+            }
+
             if let Some(shader) = &self.shader {
                 shader.use_program();
-                shader.set_mat4("uViewProj", &cam_uni);
-                shader.set_vec3("uCameraPos", &engine.camera.position);
-                
-                // Bind texture atlas
-                if let Some(atlas) = &self.block_atlas {
-                    atlas.bind(0);
-                    shader.set_int("uTexture", 0);
+                shader.set_sampler("uTextures", 0);
+                if let Some(shadow_map) = &self.shadow_map {
+                    shadow_map.apply(shader);
                 }
-                
-                self.world.render_chunks(&engine.camera, shader);
-            }            
+                let pass = engine.profiler.gpu_begin("terrain");
+                render_chunks(&mut self.world, engine, ChunkPass::Opaque);
+                engine.profiler.gpu_end(pass);
+            }
+
+            if let (Some(shader), Some(renderer)) = (&self.entity_shader, &mut self.entity_renderer) {
+                let pass = engine.profiler.gpu_begin("entities");
+                shader.use_program();
+                renderer.render(&self.entities, &engine.camera);
+                engine.profiler.gpu_end(pass);
+            }
+
+            // Water refracts everything opaque drawn so far
+            if let Some(shader) = &self.water_shader {
+                let pass = engine.profiler.gpu_begin("water");
+                self.scene_copy.capture(fb_width, fb_height);
+                self.scene_copy.bind();
+                shader.use_program();
+                shader.set_sampler("uTextures", 0);
+                self.water.apply(shader, engine.time, glam::vec2(fb_width as f32, fb_height as f32), underwater);
+                render_chunks(&mut self.world, engine, ChunkPass::Water);
+                engine.profiler.gpu_end(pass);
+            }
+
+            if let Some(shader) = &self.shader {
+                let pass = engine.profiler.gpu_begin("transparent");
+                shader.use_program();
+                render_chunks(&mut self.world, engine, ChunkPass::Transparent);
+                engine.profiler.gpu_end(pass);
+            }
         }
     }
 }
 
 fn main() {
     let mut engine = Engine::new(DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT, "Oxidize");
+    // `--record <file>` records the session's input; `--replay <file>` plays one back and exits.
+    // `--capture-frames <dir>` saves every frame as a PNG, e.g. to turn a replay into a video.
+    let args: Vec<String> = std::env::args().collect();
+    for pair in args.windows(2) {
+        let result = match pair[0].as_str() {
+            "--record" => engine.start_recording(Path::new(&pair[1])),
+            "--replay" => engine.start_replay(Path::new(&pair[1]), true),
+            "--capture-frames" => { engine.start_frame_sequence(pair[1].clone().into()); Ok(()) }
+            _ => Ok(()),
+        };
+        if let Err(e) = result { eprintln!("{}", e); }
+    }
     let mut game = DemoGame::new();
     engine.run(&mut game);
-}
\ No newline at end of file
+}